@@ -160,6 +160,17 @@ fn main() -> Result<(), Error> {
         music.evaluate(&guitarist_name)?.into_tuples()
     );
 
+    let (_, band_artist) = song.builder().project(|s| s.artist.clone()).split_either();
+
+    assert_eq!(
+        vec![
+            "Artcic Monkeys".to_string(),
+            "Dream Theater".into(),
+            "Metallica".into(),
+        ],
+        music.evaluate(&band_artist.build())?.into_tuples()
+    );
+
     let dt_member = musician
         .builder()
         .with_key(|m| m.band.clone())
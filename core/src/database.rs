@@ -1,23 +1,47 @@
 /*! Implements a minimal database with the following features:
 * Relation and view instances are generic over [`Tuple`] types.
 * Supports incremental view update by keeping track of recently added tuples.
-* Relation instances monotonically grow (supports insertion but not deletion).
+* Relation instances monotonically grow by insertion; replacing the content of a relation
+  recomputes the views that depend on it.
 */
+#[cfg(feature = "arena")]
+mod arena;
+mod audit;
+mod builder;
+mod diff;
 mod evaluate;
 mod expression_ext;
 mod helpers;
 mod instance;
+mod intern;
+#[cfg(feature = "pretty")]
+mod pretty;
+#[cfg(feature = "spill")]
+mod spill;
 mod validate;
 
 use crate::{
-    expression::{dependency, view::ViewRef, Expression, IntoExpression, Relation, View},
-    Error, Tuple,
+    expression::{
+        dependency, optimize, view::ViewRef, Counter, Expression, IntoExpression, Mono, Params,
+        Relation, Select, View,
+    },
+    shared::{Atomic, Gate, Lock},
+    Error, MaybeSync, Tuple,
 };
-use expression_ext::ExpressionExt;
-pub use instance::Tuples;
+#[cfg(feature = "arena")]
+pub use arena::Arena;
+pub use audit::Mutation;
+pub use builder::DatabaseBuilder;
+pub use diff::{diff_databases, DatabaseDiff, RelationDiff};
+pub(crate) use expression_ext::ExpressionExt;
+pub use instance::{ColumnStats, RelationStats, Tuples};
+pub use intern::{Internable, Interner};
 use std::{
+    any::Any,
     cell::Cell,
-    collections::{HashMap, HashSet},
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    sync::{atomic::AtomicBool, mpsc::Sender},
 };
 
 use instance::{DynInstance, Instance};
@@ -29,10 +53,10 @@ struct RelationEntry {
 
     /// Contains references to the views that this relation appears in their
     /// expression. These are the views that depend on the content of this relation.
-    dependent_views: HashSet<ViewRef>,
+    dependent_views: BTreeSet<ViewRef>,
 
     /// A flag that indicating if this relation is being stabilized.
-    stabilizing: Cell<bool>,
+    stabilizing: Atomic<bool>,
 }
 
 impl RelationEntry {
@@ -43,8 +67,8 @@ impl RelationEntry {
     {
         Self {
             instance: Box::new(Instance::<T>::new()),
-            dependent_views: HashSet::new(),
-            stabilizing: Cell::new(false),
+            dependent_views: BTreeSet::new(),
+            stabilizing: Atomic::new(false),
         }
     }
 
@@ -74,20 +98,20 @@ struct ViewEntry {
     /// Contains references (relation names) to the relations that
     /// appear in the view's expression. These are the relations to
     /// which the content of this view depends.
-    dependee_relations: HashSet<String>,
+    dependee_relations: BTreeSet<String>,
 
     /// Contains references to the views that appear in the view's
     /// expression. These are the views to which the content of this
     /// view depends.
-    dependee_views: HashSet<ViewRef>,
+    dependee_views: BTreeSet<ViewRef>,
 
     /// Contains references to the views that this view appears in
     /// their expressions. These are the views that depend on the
     /// content of this view.
-    dependent_views: HashSet<ViewRef>,
+    dependent_views: BTreeSet<ViewRef>,
 
     /// A flag that indicating if this view is being stabilized.
-    stabilizing: Cell<bool>,
+    stabilizing: Atomic<bool>,
 }
 
 impl ViewEntry {
@@ -99,10 +123,10 @@ impl ViewEntry {
     {
         Self {
             instance: Box::new(view_instance),
-            dependee_relations: HashSet::new(),
-            dependee_views: HashSet::new(),
-            dependent_views: HashSet::new(),
-            stabilizing: Cell::new(false),
+            dependee_relations: BTreeSet::new(),
+            dependee_views: BTreeSet::new(),
+            dependent_views: BTreeSet::new(),
+            stabilizing: Atomic::new(false),
         }
     }
 
@@ -124,8 +148,35 @@ impl Clone for ViewEntry {
     }
 }
 
+/// Sets the `stabilizing` flag of a relation or a view for as long as it lives, so that the
+/// flag is reset even if the stabilization fails halfway.
+struct Stabilizing<'a>(&'a Atomic<bool>);
+
+impl<'a> Stabilizing<'a> {
+    /// Sets `flag` and returns a guard that resets it, or `None` if `flag` is already set.
+    fn enter(flag: &'a Atomic<bool>) -> Option<Self> {
+        if flag.replace(true) {
+            None
+        } else {
+            Some(Self(flag))
+        }
+    }
+}
+
+impl Drop for Stabilizing<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
 /// Stores data in relation instances and implements incremental view maintenance over them.
 ///
+/// With the `sync` feature, [`Database`] is [`Send`] and [`Sync`]; evaluations on different
+/// threads read the database at the same time, while insertions and the stabilization of
+/// the relations and views that evaluations read run one at a time. The closures of an
+/// expression are locked while they are applied; hence, the clones of an expression that
+/// are evaluated on different threads apply their closures one tuple at a time.
+///
 /// **Example**:
 /// ```rust
 /// use codd::{Database, expression::Select};
@@ -160,22 +211,131 @@ impl Clone for ViewEntry {
 /// assert_eq!(vec![15, 23, 101, 1001], odds_data.into_tuples());
 /// ```
 pub struct Database {
-    relations: HashMap<String, RelationEntry>,
-    views: HashMap<ViewRef, ViewEntry>,
-    view_counter: i32,
+    relations: BTreeMap<String, RelationEntry>,
+    views: BTreeMap<ViewRef, ViewEntry>,
+    view_names: BTreeMap<String, ViewRef>,
+    view_counter: u64,
+    free_view_ids: BTreeSet<u64>,
+    audit_log: Option<Lock<Vec<Mutation>>>,
+    auto_compact_threshold: Option<usize>,
+    eager_dedup: bool,
+    gate: Gate,
 }
 
 impl Database {
     /// Creates a new empty database.
     pub fn new() -> Self {
         Self {
-            relations: HashMap::new(),
-            views: HashMap::new(),
+            relations: BTreeMap::new(),
+            views: BTreeMap::new(),
+            view_names: BTreeMap::new(),
             view_counter: 0,
+            free_view_ids: BTreeSet::new(),
+            audit_log: None,
+            auto_compact_threshold: None,
+            eager_dedup: false,
+            gate: Gate::default(),
+        }
+    }
+
+    /// Enables recording the mutations of the database, such as inserting tuples into
+    /// relations or storing views, in its audit log (see [`Database::audit_log`]).
+    /// Mutations that are performed before the log is enabled are not recorded.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Mutation};
+    ///
+    /// let mut db = Database::new();
+    /// db.enable_audit_log();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// db.insert(&numbers, vec![1, 2, 3].into()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     vec![Mutation::Insert { relation: "numbers".to_string(), count: 3 }],
+    ///     db.audit_log()
+    /// );
+    /// ```
+    pub fn enable_audit_log(&mut self) {
+        if self.audit_log.is_none() {
+            self.audit_log = Some(Lock::new(Vec::new()));
         }
     }
 
+    /// Returns the mutations that are recorded in the audit log of the database in the
+    /// order that they were performed. The log is empty unless it is enabled by
+    /// [`Database::enable_audit_log`].
+    pub fn audit_log(&self) -> Vec<Mutation> {
+        self.audit_log
+            .as_ref()
+            .map(|log| log.borrow().clone())
+            .unwrap_or_default()
+    }
+
+    /// Bounds the number of batches of inserted tuples that are pending in every relation
+    /// instance until the next evaluation of the instance. After every insertion, the last
+    /// pending batch of the instance is merged into the batch before it while that batch is
+    /// at most twice as large or there are more than `threshold` pending batches. Passing
+    /// `None` disables merging, which is the default.
+    ///
+    /// **Note**: merging the pending batches bounds the memory overhead of many small
+    /// insertions between evaluations; the merged tuples still reach the views of the
+    /// database at the next evaluation. Since the sizes of the pending batches decrease
+    /// geometrically, the merges take `O(n log n)` time in total for `n` pending tuples
+    /// as long as `threshold` is at least `log2(n)`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// db.set_auto_compact_threshold(Some(8));
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// for i in 0..100 {
+    ///     db.insert(&r, vec![i].into()).unwrap();
+    /// }
+    /// assert_eq!(100, db.evaluate(&r).unwrap().len());
+    /// ```
+    pub fn set_auto_compact_threshold(&mut self, threshold: Option<usize>) {
+        self.auto_compact_threshold = threshold;
+    }
+
+    /// Enables (or disables) the eager deduplication of inserted tuples. When enabled, every
+    /// insertion is merged into a single batch of pending tuples in its relation instance,
+    /// so that a tuple that is inserted more than once before the next evaluation of the
+    /// instance is held only once. Eager deduplication is disabled by default.
+    ///
+    /// **Note**: eager deduplication reduces the memory that overlapping insertions hold
+    /// before evaluation at the cost of merging the pending tuples on every insertion.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// db.set_eager_dedup(true);
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2].into()).unwrap();
+    /// db.insert(&r, vec![2, 3].into()).unwrap();
+    ///
+    /// assert_eq!(3, db.relation_stats(&r).unwrap().to_add);
+    /// ```
+    pub fn set_eager_dedup(&mut self, eager: bool) {
+        self.eager_dedup = eager;
+    }
+
     /// Evaluates `expression` in the database and returns the result in a [`Tuples`] object.
+    ///
+    /// **Note**: the tuples of the result are deduplicated and sorted by their [`Ord`]
+    /// implementation; hence, the order of the result is deterministic and independent of
+    /// the order in which tuples were inserted (see [`Database::evaluate_sorted_by`] for
+    /// custom orders).
+    ///
+    /// A [`Select`] that occurs more than once in `expression`, as clones of the same
+    /// expression, is collected only once per evaluation; its predicate is applied once to
+    /// every tuple regardless of the number of its occurrences.
+    ///
+    /// [`Select`]: crate::expression::Select
     pub fn evaluate<T, E>(&self, expression: &E) -> Result<Tuples<T>, Error>
     where
         T: Tuple,
@@ -184,12 +344,533 @@ impl Database {
         expression.collect_recent(&evaluate::Evaluator::new(self))
     }
 
+    /// Evaluates `expression` in the database like [`Database::evaluate`] after rewriting it
+    /// with the optimization passes of [`optimize`], such as fusing every [`Project`] over a
+    /// [`Select`] into a single [`FilterMap`] that filters and maps tuples in one pass.
+    ///
+    /// [`optimize`]: crate::expression::optimize
+    /// [`Project`]: crate::expression::Project
+    /// [`Select`]: crate::expression::Select
+    /// [`FilterMap`]: crate::expression::FilterMap
+    pub fn evaluate_optimized<T>(&self, expression: &Mono<T>) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        self.evaluate(&optimize::fuse(expression.clone()))
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] and, every 1000
+    /// produced tuples, calls `progress` with the running number of tuples that the operators
+    /// of `expression` have produced.
+    ///
+    /// **Note**: the produced tuples include intermediate tuples and duplicates; therefore,
+    /// the reported numbers may exceed the size of the result.
+    pub fn evaluate_with_progress<T, E>(
+        &self,
+        expression: &E,
+        mut progress: impl FnMut(usize),
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let monitor = evaluate::Monitor::new(&mut progress);
+        expression.collect_recent(&evaluate::Evaluator::with_monitor(self, &monitor))
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] but allocates the
+    /// scratch memory of the evaluation in `arena`. The arena is reset when the evaluation
+    /// is done; therefore, reusing the same arena across evaluations reuses its memory
+    /// instead of allocating on the heap for every evaluation (see [`Arena`]).
+    #[cfg(feature = "arena")]
+    pub fn evaluate_in<T, E>(&self, expression: &E, arena: &mut Arena) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let result = expression.collect_recent(&evaluate::Evaluator::with_arena(self, arena));
+        arena.reset();
+        result
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] and returns the
+    /// result together with the peak number of tuples that the operators of `expression`
+    /// held at the same time during evaluation. It is useful for estimating the memory that
+    /// evaluating `expression` requires.
+    ///
+    /// **Note**: the peak is an estimate in number of tuples, counting the copies of the
+    /// tuples of instances and the intermediate tuples that are produced by the operators.
+    /// Tuples of instances that operators read without copying them and tuples that are
+    /// produced while stabilizing the views of the database are not counted.
+    pub fn evaluate_with_peak<T, E>(&self, expression: &E) -> Result<(Tuples<T>, usize), Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let mut progress = |_| {};
+        let monitor = evaluate::Monitor::new(&mut progress);
+        let result =
+            expression.collect_recent(&evaluate::Evaluator::with_monitor(self, &monitor))?;
+        Ok((result, monitor.peak()))
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] but returns
+    /// [`Error::UnexpectedEmpty`] if the result contains no tuples. It is useful as a guard
+    /// against misconfigured expressions, such as joins on mismatching keys.
+    pub fn evaluate_expect_non_empty<T, E>(&self, expression: &E) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let result = self.evaluate(expression)?;
+        if result.is_empty() {
+            return Err(Error::UnexpectedEmpty);
+        }
+        Ok(result)
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] but returns at
+    /// most `n` tuples. When `expression` is a selection, a product or a join, the
+    /// evaluation stops as soon as the expression produces `n` tuples; otherwise, the expression is evaluated
+    /// in full and the first `n` tuples of the result are returned.
+    ///
+    /// **Note**: the tuples that a product or a join produces are deduplicated when the
+    /// evaluation stops; therefore, the result may contain fewer than `n` tuples even if
+    /// the full result of `expression` contains more. Also, the returned tuples are not
+    /// necessarily the smallest `n` tuples of the full result.
+    pub fn evaluate_take<T, E>(&self, expression: &E, n: usize) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let take = Cell::new(n);
+        let mut result =
+            expression.collect_recent(&evaluate::Evaluator::with_limit(self, &take))?;
+        result.truncate(n);
+        Ok(result)
+    }
+
+    /// Returns true if some tuple of the result of `expression` in the database satisfies
+    /// `predicate`. The tuples of `expression` are selected by `predicate` with a limit of
+    /// one tuple (see [`Database::evaluate_take`]); therefore, `predicate` is not applied
+    /// to the tuples that follow the first tuple that satisfies it.
+    ///
+    /// **Note**: the sub-expressions of `expression` are evaluated in full before their
+    /// tuples are tested; only the selection stops early.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// assert!(db.exists_where(&r, |&t| t > 2).unwrap());
+    /// assert!(!db.exists_where(&r, |&t| t > 3).unwrap());
+    /// ```
+    pub fn exists_where<T, E>(
+        &self,
+        expression: &E,
+        predicate: impl FnMut(&T) -> bool + MaybeSync + 'static,
+    ) -> Result<bool, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T>,
+    {
+        let take = Cell::new(1);
+        let select = Select::new(expression, predicate);
+        let result = select.collect_recent(&evaluate::Evaluator::with_limit(self, &take))?;
+        Ok(!result.is_empty())
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] where every
+    /// [`Param`] of `expression` evaluates to a single tuple, the value that `params` binds
+    /// to its name. Returns [`Error::UnboundParam`] if no value of the type of a parameter is
+    /// bound to its name.
+    ///
+    /// [`Param`]: crate::expression::Param
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression, expression::{Param, Params}};
+    ///
+    /// let mut db = Database::new();
+    /// let ages = db.add_relation::<(String, i32)>("Ages").unwrap();
+    /// db.insert(&ages, vec![("Alice".to_string(), 30), ("Bob".into(), 25)].into());
+    ///
+    /// let age = ages
+    ///     .builder()
+    ///     .with_key(|t| t.0.clone())
+    ///     .join(Param::<String>::new("name").builder().with_key(|t| t.clone()))
+    ///     .on(|_, l, _| l.1)
+    ///     .build();
+    ///
+    /// let params = Params::new().bind("name", "Bob".to_string());
+    /// assert_eq!(vec![25], db.evaluate_with_params(&age, &params).unwrap().into_tuples());
+    /// ```
+    pub fn evaluate_with_params<T, E>(
+        &self,
+        expression: &E,
+        params: &Params,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        expression.collect_recent(&evaluate::Evaluator::with_params(self, params))
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] while supplying
+    /// `context` to the closures of the [`Select`] and [`Project`] expressions that are
+    /// created by [`Select::with_context`] and [`Project::with_context`]. Unlike closures
+    /// that capture their state when the expression is built, the context may differ from
+    /// one evaluation to the next.
+    ///
+    /// **Note**: evaluating an expression with contextual closures fails with
+    /// [`Error::MissingContext`] if no context of their expected type is supplied.
+    ///
+    /// [`Select`]: crate::expression::Select
+    /// [`Project`]: crate::expression::Project
+    /// [`Select::with_context`]: crate::expression::Select::with_context
+    /// [`Project::with_context`]: crate::expression::Project::with_context
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::Project};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into()).unwrap();
+    ///
+    /// let scaled = Project::with_context(&r, |factor: &i32, t| t * factor);
+    ///
+    /// assert_eq!(vec![10, 20, 30], db.evaluate_with(&scaled, &10).unwrap().into_tuples());
+    /// assert_eq!(vec![-3, -2, -1], db.evaluate_with(&scaled, &-1).unwrap().into_tuples());
+    /// ```
+    pub fn evaluate_with<T, E, C>(&self, expression: &E, context: &C) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+        C: Any,
+    {
+        expression.collect_recent(&evaluate::Evaluator::with_context(self, context))
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] but stops and
+    /// returns [`Error::Cancelled`] once `cancel` is set, e.g., by another thread when the
+    /// user aborts a long-running query.
+    ///
+    /// **Note**: the flag is checked while products and joins produce their tuples, where
+    /// evaluations spend most of their time, and when they complete.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Error, Expression};
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into()).unwrap();
+    /// let pairs = r.builder().product(&r).on(|&l, &r| (l, r)).build();
+    ///
+    /// let cancel = AtomicBool::new(false);
+    /// assert_eq!(9, db.evaluate_cancellable(&pairs, &cancel).unwrap().len());
+    ///
+    /// cancel.store(true, Ordering::Relaxed);
+    /// assert!(matches!(db.evaluate_cancellable(&pairs, &cancel), Err(Error::Cancelled)));
+    /// ```
+    pub fn evaluate_cancellable<T, E>(
+        &self,
+        expression: &E,
+        cancel: &AtomicBool,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        expression.collect_recent(&evaluate::Evaluator::with_cancel(self, cancel))
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] but returns the
+    /// resulting tuples in a vector without sorting and deduplicating them. Operators of
+    /// `expression` still sort their intermediate tuples where their algorithms require it;
+    /// only the final merge of the result is skipped, which saves time on large results
+    /// whose order does not matter.
+    ///
+    /// **Note**: the result is in no particular order and may contain duplicate tuples.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    /// use std::collections::HashSet;
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// let doubles = r.builder().project(|t| t * 2).build();
+    /// let result: HashSet<i32> = db.evaluate_unordered(&doubles).unwrap().into_iter().collect();
+    /// assert_eq!(HashSet::from([2, 4, 6]), result);
+    /// ```
+    pub fn evaluate_unordered<T, E>(&self, expression: &E) -> Result<Vec<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        expression
+            .collect_recent(&evaluate::Evaluator::unordered(self))
+            .map(Tuples::into_tuples)
+    }
+
+    /// Evaluates `expression` in the database like [`Database::evaluate`] but sends the
+    /// resulting tuples over `sender` instead of returning them. The tuples are sent batch
+    /// by batch as soon as the operator at the root of `expression` produces each batch of
+    /// them, so the receiving end may consume the result while `expression` is still being
+    /// evaluated. The evaluation stops without an error when the receiving end of the
+    /// channel is dropped.
+    ///
+    /// **Note**: the tuples of every batch are sent in their sorted order but the result as
+    /// a whole is not sorted. To avoid sending a tuple more than once, a copy of the sent
+    /// tuples is kept until the evaluation is done.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    /// use std::sync::mpsc;
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// db.evaluate_to_sender(&r.builder().project(|t| t * 2).build(), sender).unwrap();
+    ///
+    /// let mut result = receiver.iter().collect::<Vec<_>>();
+    /// result.sort();
+    /// assert_eq!(vec![2, 4, 6], result);
+    /// ```
+    pub fn evaluate_to_sender<T, E>(&self, expression: &E, sender: Sender<T>) -> Result<(), Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let mut sent = Vec::<Tuples<T>>::new();
+        evaluate::Evaluator::new(self).stream(expression, &mut |batch| {
+            let mut tuples = batch.into_tuples();
+            for batch in &sent {
+                helpers::retain_absent(&mut tuples, batch);
+            }
+            for tuple in &tuples {
+                if sender.send(tuple.clone()).is_err() {
+                    return false;
+                }
+            }
+            sent.push(Tuples::from_sorted_unchecked(tuples));
+            true
+        })
+    }
+
+    /// Evaluates `expression` in the database and returns the resulting tuples in a vector
+    /// that is sorted by `compare`.
+    ///
+    /// **Note**: the result of [`Database::evaluate`] is always sorted by the natural order
+    /// of tuples; because `compare` may disagree with that order, the result is returned as
+    /// a vector rather than [`Tuples`]. Tuples that are equal according to `compare` retain
+    /// their natural order.
+    pub fn evaluate_sorted_by<T, E>(
+        &self,
+        expression: &E,
+        compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let mut result = self.evaluate(expression)?.into_tuples();
+        result.sort_by(compare);
+        Ok(result)
+    }
+
+    /// Evaluates `expression` in the database and interns the resulting tuples, so that
+    /// identical field values of different tuples share the same allocation (see
+    /// [`Internable`]). It is useful for reducing the memory footprint of large results
+    /// that repeat large values across many tuples.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    /// use std::sync::Arc;
+    ///
+    /// let mut db = Database::new();
+    /// let colors = db.add_relation::<String>("Colors").unwrap();
+    /// db.insert(&colors, vec!["Red".to_string(), "Blue".into()].into());
+    ///
+    /// let interned = db.evaluate_interned(&colors).unwrap();
+    /// assert_eq!(vec![Arc::from("Blue"), Arc::from("Red")], interned.into_tuples());
+    /// ```
+    pub fn evaluate_interned<T, E>(&self, expression: &E) -> Result<Tuples<T::Interned>, Error>
+    where
+        T: Internable,
+        E: ExpressionExt<T>,
+    {
+        let mut interner = Interner::new();
+        Ok(self
+            .evaluate(expression)?
+            .iter()
+            .map(|t| t.intern(&mut interner))
+            .collect())
+    }
+
+    /// Runs `f` against a snapshot of the database and returns its result. The snapshot is
+    /// a clone of the database; therefore, `f` observes a fixed state that is isolated from
+    /// the changes that are made to the database while `f` runs, and the changes that `f`
+    /// makes to the snapshot are discarded.
+    ///
+    /// **Note**: taking a snapshot copies the tuples of every relation and view instance.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// db.insert(&numbers, vec![1, 2].into());
+    ///
+    /// let snapshot = db.with_snapshot(|snapshot| {
+    ///     db.insert(&numbers, vec![3].into()).unwrap();
+    ///     snapshot.evaluate(&numbers).unwrap()
+    /// });
+    ///
+    /// assert_eq!(vec![1, 2], snapshot.into_tuples());
+    /// assert_eq!(vec![1, 2, 3], db.evaluate(&numbers).unwrap().into_tuples());
+    /// ```
+    pub fn with_snapshot<R>(&self, f: impl FnOnce(&Database) -> R) -> R {
+        f(&self.clone())
+    }
+
+    /// Evaluates `expression` in the database and groups the resulting tuples by `key`. The
+    /// tuples of every group are sorted by their natural order.
+    ///
+    /// **Note**: `evaluate_grouped` groups the result of [`Database::evaluate`] after it is
+    /// computed; it is a quick alternative to maintaining the groups in a view.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// db.insert(&numbers, vec![1, 2, 3, 4, 5].into());
+    ///
+    /// let grouped = db.evaluate_grouped(&numbers, |t| t % 2 == 0).unwrap();
+    /// assert_eq!(vec![1, 3, 5], grouped[&false]);
+    /// assert_eq!(vec![2, 4], grouped[&true]);
+    /// ```
+    pub fn evaluate_grouped<K, V, E>(
+        &self,
+        expression: &E,
+        key: impl Fn(&V) -> K,
+    ) -> Result<BTreeMap<K, Vec<V>>, Error>
+    where
+        K: Ord,
+        V: Tuple,
+        E: ExpressionExt<V>,
+    {
+        let mut result = BTreeMap::<K, Vec<V>>::new();
+        for tuple in self.evaluate(expression)?.into_tuples() {
+            result.entry(key(&tuple)).or_default().push(tuple);
+        }
+        Ok(result)
+    }
+
+    /// Evaluates `expression` in the database and returns the [`ColumnStats`] of its
+    /// numeric tuples, namely their count, minimum, maximum and sum.
+    ///
+    /// **Note**: the sum is computed in a single pass over the result, and the minimum and
+    /// maximum are read from the ends of the sorted result. Like every sum of primitive
+    /// integers, the sum panics on overflow in debug builds.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// db.insert(&numbers, vec![3, 1, 2].into());
+    ///
+    /// let stats = db.column_stats(&numbers).unwrap();
+    /// assert_eq!((3, Some(1), Some(3), 6), (stats.count, stats.min, stats.max, stats.sum));
+    /// ```
+    pub fn column_stats<T, E>(&self, expression: &E) -> Result<ColumnStats<T>, Error>
+    where
+        T: Tuple + for<'a> std::iter::Sum<&'a T>,
+        E: ExpressionExt<T>,
+    {
+        let tuples = self.evaluate(expression)?;
+        Ok(ColumnStats {
+            count: tuples.len(),
+            min: tuples.min().cloned(),
+            max: tuples.max().cloned(),
+            sum: tuples.iter().sum(),
+        })
+    }
+
+    /// Evaluates `expression` in the database and returns the result in a [`BTreeSet`].
+    pub fn evaluate_set<T, E>(&self, expression: &E) -> Result<BTreeSet<T>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        Ok(self
+            .evaluate(expression)?
+            .into_tuples()
+            .into_iter()
+            .collect())
+    }
+
+    /// Evaluates the relation identified by `name` with tuples of type `T` and returns the
+    /// result in a [`Tuples`] object.
+    pub fn evaluate_relation_by_name<T>(&self, name: &str) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let relation = self.relation::<T>(name)?;
+        self.evaluate(&relation)
+    }
+
+    /// Returns a [`Relation`] object for the existing relation instance identified by `name`.
+    /// It is useful for recovering the handles of relations whose names are known, e.g., of
+    /// a database that is restored from a snapshot. Returns [`Error::InstanceNotFound`] if
+    /// no such instance exists and [`Error::TypeMismatch`] if its tuples are not of type `T`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// db.add_relation::<i32>("numbers").unwrap();
+    ///
+    /// let numbers = db.relation::<i32>("numbers").unwrap();
+    /// db.insert(&numbers, vec![1, 2].into()).unwrap();
+    /// assert_eq!(vec![1, 2], db.evaluate(&numbers).unwrap().into_tuples());
+    /// ```
+    pub fn relation<T>(&self, name: &str) -> Result<Relation<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let relation = Relation::new(name);
+        self.relation_instance(&relation)?;
+        Ok(relation)
+    }
+
     /// Adds a new relation instance identified by `name` to the database and returns a
     /// [`Relation`] object that can be used to access the instance.
     pub fn add_relation<T>(&mut self, name: &str) -> Result<Relation<T>, Error>
     where
         T: Tuple + 'static,
     {
+        Self::validate_name(name)?;
         if !self.relations.contains_key(name) {
             self.relations
                 .insert(name.into(), RelationEntry::new::<T>());
@@ -199,254 +880,2768 @@ impl Database {
         }
     }
 
+    /// Returns a [`DatabaseBuilder`] for setting up a new database in a single chain of calls.
+    pub fn builder() -> DatabaseBuilder {
+        DatabaseBuilder::new()
+    }
+
+    /// Adds a new relation instance identified by `name` to the database like
+    /// [`Database::add_relation`] and inserts the `seed` tuples in the new instance.
+    pub fn add_relation_seeded<T>(
+        &mut self,
+        name: &str,
+        seed: Tuples<T>,
+    ) -> Result<Relation<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let relation = self.add_relation(name)?;
+        self.record(|| Mutation::Seed {
+            relation: name.into(),
+            count: seed.len(),
+        });
+        self.insert_unlogged(self.relation_instance(&relation)?, seed);
+        Ok(relation)
+    }
+
+    /// Evaluates `expression` once and adds a new relation instance identified by `name`
+    /// with the resulting tuples. Unlike a view, the new relation is not updated when the
+    /// instances of `expression` change; it can be updated like any other relation.
+    ///
+    /// **Note**: the relation is not added if `expression` fails to evaluate.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into()).unwrap();
+    ///
+    /// let evens = db
+    ///     .materialize_as_relation("evens", &r.builder().select(|t| t % 2 == 0).build())
+    ///     .unwrap();
+    /// db.insert(&r, vec![4].into()).unwrap();
+    ///
+    /// assert_eq!(vec![2], db.evaluate(&evens).unwrap().into_tuples());
+    /// ```
+    pub fn materialize_as_relation<T, E>(
+        &mut self,
+        name: &str,
+        expression: &E,
+    ) -> Result<Relation<T>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T>,
+    {
+        Self::validate_name(name)?;
+        if self.relations.contains_key(name) {
+            return Err(Error::InstanceExists { name: name.into() });
+        }
+
+        let tuples = self.evaluate(expression)?;
+        self.add_relation_seeded(name, tuples)
+    }
+
+    /// Adds a new relation instance identified by `name` to the database like
+    /// [`Database::add_relation`]. Every batch of stable tuples of the new instance with at
+    /// least `threshold` tuples is spilled to a file in a temporary directory and is loaded
+    /// on demand when the relation is evaluated.
+    ///
+    /// **Note**: spilling is intended for large relations whose stable tuples do not fit in
+    /// memory; tuples that are recently inserted remain in memory until they are stable.
+    #[cfg(feature = "spill")]
+    pub fn add_relation_spilled<T>(
+        &mut self,
+        name: &str,
+        threshold: usize,
+    ) -> Result<Relation<T>, Error>
+    where
+        T: Tuple + serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        Self::validate_name(name)?;
+        if self.relations.contains_key(name) {
+            return Err(Error::InstanceExists { name: name.into() });
+        }
+
+        let instance = Instance::<T>::spilled(spill::Spill::new(name, threshold)?);
+        let mut entry = RelationEntry::new::<T>();
+        entry.instance = Box::new(instance);
+        self.relations.insert(name.into(), entry);
+        Ok(Relation::new(name))
+    }
+
+    /// Adds a new relation instance identified by `new_name` to the database with a copy of
+    /// the tuples of `source` and returns the new relation. The two instances are updated
+    /// independently; views over `source` do not depend on the new relation.
+    pub fn clone_relation<T>(
+        &mut self,
+        source: &Relation<T>,
+        new_name: &str,
+    ) -> Result<Relation<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        // `relation_instance` validates the type of `source`:
+        let instance = self.relation_instance(source)?.clone_box();
+        Self::validate_name(new_name)?;
+        if self.relations.contains_key(new_name) {
+            return Err(Error::InstanceExists {
+                name: new_name.into(),
+            });
+        }
+
+        self.record(|| Mutation::Clone {
+            source: source.name().into(),
+            relation: new_name.into(),
+        });
+        let mut entry = RelationEntry::new::<T>();
+        entry.instance = instance;
+        self.relations.insert(new_name.into(), entry);
+        Ok(Relation::new(new_name))
+    }
+
     /// Inserts tuples in the instance corresponding to `relation`.
     pub fn insert<T>(&self, relation: &Relation<T>, tuples: Tuples<T>) -> Result<(), Error>
     where
         T: Tuple + 'static,
     {
-        let instance = self.relation_instance(&relation)?;
-        instance.insert(tuples);
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        self.record(|| Mutation::Insert {
+            relation: relation.name().into(),
+            count: tuples.len(),
+        });
+        self.insert_unlogged(instance, tuples);
+        Ok(())
+    }
+
+    /// Inserts tuples in the instance corresponding to `relation` like [`Database::insert`]
+    /// but borrows `tuples`. Small insertions are merged into the last pending batch of the
+    /// instance instead of a new batch, which makes this method suitable for inserting
+    /// many tiny slices at a high rate.
+    pub fn insert_slice<T>(&self, relation: &Relation<T>, tuples: &[T]) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        self.record(|| Mutation::Insert {
+            relation: relation.name().into(),
+            count: tuples.len(),
+        });
+        instance.insert_slice(tuples);
+        self.auto_compact(instance);
+        Ok(())
+    }
+
+    /// Inserts tuples in the instance corresponding to `relation` like [`Database::insert`]
+    /// but fails without inserting any tuples if some of `tuples` already exist in the
+    /// instance, including tuples that are inserted but not yet evaluated.
+    pub fn insert_strict<T>(&self, relation: &Relation<T>, tuples: Tuples<T>) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        if instance.contains_any(&tuples)? {
+            return Err(Error::DuplicateInsert {
+                name: relation.name().into(),
+            });
+        }
+        self.record(|| Mutation::Insert {
+            relation: relation.name().into(),
+            count: tuples.len(),
+        });
+        self.insert_unlogged(instance, tuples);
+        Ok(())
+    }
+
+    /// Inserts tuples in the instance corresponding to `relation` like [`Database::insert`]
+    /// and returns the number of `tuples` that are genuinely new to the instance.
+    ///
+    /// **Note**: duplicates are removed lazily when the instance is evaluated; therefore,
+    /// the count is the number of tuples that the next evaluation of `relation` will add.
+    /// It is computed eagerly against the existing tuples of the instance, including the
+    /// tuples that are inserted but not yet evaluated.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    ///
+    /// assert_eq!(3, db.insert_counting(&r, vec![1, 2, 3].into()).unwrap());
+    /// assert_eq!(1, db.insert_counting(&r, vec![2, 3, 4].into()).unwrap());
+    /// assert_eq!(vec![1, 2, 3, 4], db.evaluate(&r).unwrap().into_tuples());
+    /// ```
+    pub fn insert_counting<T>(
+        &self,
+        relation: &Relation<T>,
+        tuples: Tuples<T>,
+    ) -> Result<usize, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        let count = instance.count_new(&tuples)?;
+        self.record(|| Mutation::Insert {
+            relation: relation.name().into(),
+            count: tuples.len(),
+        });
+        self.insert_unlogged(instance, tuples);
+        Ok(count)
+    }
+
+    /// Loads `batches` directly as the stable tuples of the instance corresponding to
+    /// `relation`. Unlike [`Database::insert`], the tuples skip the promotion from tuples
+    /// to add to recent and stable tuples, which makes `load_stable` a fast path for
+    /// restoring relations from snapshots. The views that depend on `relation` are
+    /// recomputed against the new content.
+    ///
+    /// **Note**: the batches are not deduplicated against each other or against the
+    /// existing tuples of `relation`; the caller must ensure that they are disjoint.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// db.load_stable(&numbers, vec![vec![1, 2].into(), vec![3, 4].into()]).unwrap();
+    ///
+    /// assert_eq!(vec![1, 2, 3, 4], db.evaluate(&numbers).unwrap().into_tuples());
+    /// ```
+    pub fn load_stable<T>(
+        &self,
+        relation: &Relation<T>,
+        batches: Vec<Tuples<T>>,
+    ) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        self.record(|| Mutation::Load {
+            relation: relation.name().into(),
+            count: batches.iter().map(|batch| batch.len()).sum(),
+        });
+        let loaded = instance.load_stable(batches);
+        self.reset_dependent_views(relation.name())?;
+        loaded
+    }
+
+    /// Replaces the content of the instance corresponding to `relation` with `tuples`.
+    /// The views that depend on `relation` are recomputed against the new content.
+    pub fn replace_relation<T>(
+        &self,
+        relation: &Relation<T>,
+        tuples: Tuples<T>,
+    ) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        self.record(|| Mutation::Replace {
+            relation: relation.name().into(),
+            count: tuples.len(),
+        });
+        self.replace_unlogged(relation.name(), instance, tuples)
+    }
+
+    /// Removes the tuples of the instance corresponding to `relation` that satisfy `pred`
+    /// and returns the number of removed tuples. Like [`Database::replace_relation`], the
+    /// views that depend on `relation` are recomputed if any tuple is removed.
+    ///
+    /// **Note**: `remove_where` scans every batch of the instance and rebuilds the instance
+    /// from the remaining tuples; therefore, its cost is proportional to the size of the
+    /// relation rather than to the number of removed tuples.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// db.insert(&numbers, vec![1, 2, 3, 4].into());
+    ///
+    /// assert_eq!(2, db.remove_where(&numbers, |t| t % 2 == 0).unwrap());
+    /// assert_eq!(vec![1, 3], db.evaluate(&numbers).unwrap().into_tuples());
+    /// ```
+    pub fn remove_where<T>(
+        &self,
+        relation: &Relation<T>,
+        mut pred: impl FnMut(&T) -> bool,
+    ) -> Result<usize, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _writer = self.gate.write();
+        let instance = self.relation_instance(relation)?;
+        let tuples = instance.tuples()?;
+        let before = tuples.len();
+        let remaining: Vec<T> = tuples
+            .into_tuples()
+            .into_iter()
+            .filter(|t| !pred(t))
+            .collect();
+        let removed = before - remaining.len();
+
+        if removed > 0 {
+            self.record(|| Mutation::Remove {
+                relation: relation.name().into(),
+                count: removed,
+            });
+            self.replace_unlogged(relation.name(), instance, remaining.into())?;
+        }
+        Ok(removed)
+    }
+
+    /// Shrinks the capacity of the instances of all relations and views in the database
+    /// as much as possible, e.g., to reclaim memory after their content is replaced.
+    pub fn shrink_to_fit(&self) {
+        let _writer = self.gate.write();
+        for entry in self.relations.values() {
+            entry.instance.shrink();
+        }
+        for entry in self.views.values() {
+            entry.instance.instance().shrink();
+        }
+    }
+
+    /// Verifies the internal invariants of the database and returns [`Error::Corrupted`],
+    /// describing the first violation, if any. It checks that every batch of tuples in the
+    /// instances of relations and views is sorted and deduplicated, and that the dependency
+    /// edges between relations and views are symmetric: every view that depends on a
+    /// relation or a view is registered as a dependent of it and vice versa.
+    ///
+    /// **Note**: this is a diagnostic tool that visits every tuple in the database; it is
+    /// not intended to be called on a hot path.
+    pub fn validate_invariants(&self) -> Result<(), Error> {
+        let _reader = self.gate.read();
+        let corrupted = |detail: String| Err(Error::Corrupted { detail });
+
+        for (name, entry) in &self.relations {
+            match entry.instance.check_sorted() {
+                Err(Error::Corrupted { detail }) => {
+                    return corrupted(format!("relation `{}`: {}", name, detail))
+                }
+                result => result?,
+            }
+            for view_ref in &entry.dependent_views {
+                match self.views.get(view_ref) {
+                    Some(view) if view.dependee_relations.contains(name) => {}
+                    _ => {
+                        return corrupted(format!(
+                            "view {} is a dependent of relation `{}` but does not depend on it",
+                            view_ref, name
+                        ))
+                    }
+                }
+            }
+        }
+
+        for (view_ref, entry) in &self.views {
+            match entry.instance.instance().check_sorted() {
+                Err(Error::Corrupted { detail }) => {
+                    return corrupted(format!("view {}: {}", view_ref, detail))
+                }
+                result => result?,
+            }
+            for name in &entry.dependee_relations {
+                match self.relations.get(name) {
+                    Some(relation) if relation.dependent_views.contains(view_ref) => {}
+                    _ => {
+                        return corrupted(format!(
+                            "view {} depends on relation `{}` but is not its dependent",
+                            view_ref, name
+                        ))
+                    }
+                }
+            }
+            for dependee in &entry.dependee_views {
+                match self.views.get(dependee) {
+                    Some(view) if view.dependent_views.contains(view_ref) => {}
+                    _ => {
+                        return corrupted(format!(
+                            "view {} depends on view {} but is not its dependent",
+                            view_ref, dependee
+                        ))
+                    }
+                }
+            }
+            for dependent in &entry.dependent_views {
+                match self.views.get(dependent) {
+                    Some(view) if view.dependee_views.contains(view_ref) => {}
+                    _ => {
+                        return corrupted(format!(
+                            "view {} is a dependent of view {} but does not depend on it",
+                            dependent, view_ref
+                        ))
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Returns an estimate of the number of bytes that the tuples of all relations and views
+    /// of the database occupy in memory.
+    ///
+    /// **Note**: the estimate counts `size_of::<T>()` bytes for every tuple of type `T`;
+    /// it does not include the memory that tuples own on the heap (e.g., the contents of a
+    /// `String`), the unused capacity of batches, or the batches that are spilled to disk.
+    pub fn estimated_bytes(&self) -> usize {
+        let _reader = self.gate.read();
+        let relations: usize = self
+            .relations
+            .values()
+            .map(|entry| entry.instance.estimated_bytes())
+            .sum();
+        let views: usize = self
+            .views
+            .values()
+            .map(|entry| entry.instance.instance().estimated_bytes())
+            .sum();
+        relations + views
+    }
+
+    /// Returns true if the content of `view` reflects every tuple that is inserted into the
+    /// relations and the views on which it depends, directly or through other views. A view
+    /// that is not current is brought up to date when it is evaluated (see
+    /// [`Database::evaluate`]).
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// let v = db.store_view(r.builder().project(|t| t * 10).build()).unwrap();
+    /// assert!(db.is_view_current(&v).unwrap());
+    ///
+    /// db.insert(&r, vec![1, 2].into()).unwrap();
+    /// assert!(!db.is_view_current(&v).unwrap());
+    ///
+    /// db.evaluate(&v).unwrap();
+    /// assert!(db.is_view_current(&v).unwrap());
+    /// ```
+    pub fn is_view_current<T, E>(&self, view: &View<T, E>) -> Result<bool, Error>
+    where
+        T: Tuple + 'static,
+        E: Expression<T> + 'static,
+    {
+        let _reader = self.gate.read();
+        self.view_instance(view)?;
+        Ok(self.is_stable(&[], std::slice::from_ref(view.reference())))
+    }
+
+    /// Returns the layout of the batches of tuples in the instance corresponding to `relation`.
+    pub fn relation_stats<T>(&self, relation: &Relation<T>) -> Result<RelationStats, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let _reader = self.gate.read();
+        Ok(self.relation_instance(relation)?.stats())
+    }
+
+    /// Returns the version of the instance corresponding to `relation`. The version
+    /// increases whenever tuples are inserted into the relation, its inserted tuples are
+    /// evaluated, or its content is replaced; therefore, it can be polled to decide whether
+    /// the relation must be queried again.
+    pub fn relation_version<T>(&self, relation: &Relation<T>) -> Result<u64, Error>
+    where
+        T: Tuple + 'static,
+    {
+        Ok(self.relation_instance(relation)?.version())
+    }
+
     /// Returns the instance for `relation` if it exists.
     fn relation_instance<T>(&self, relation: &Relation<T>) -> Result<&Instance<T>, Error>
     where
         T: Tuple + 'static,
     {
-        let result = self
+        let entry = self
             .relations
             .get(relation.name())
-            .and_then(|r| r.instance.as_any().downcast_ref::<Instance<T>>())
             .ok_or(Error::InstanceNotFound {
                 name: relation.name().into(),
             })?;
+        let result = entry
+            .instance
+            .as_any()
+            .downcast_ref::<Instance<T>>()
+            .ok_or(Error::TypeMismatch {
+                name: relation.name().into(),
+            })?;
         Ok(result)
     }
 
     /// Stores a new view over `expression` and returns a [`View`] objeect that can be
     /// evaluated as a view.
+    ///
+    /// **Note**: a view over another view that is maintained by recomputation (see
+    /// [`Database::store_view_recompute`]) is also maintained by recomputation.
     pub fn store_view<T, E, I>(&mut self, expression: I) -> Result<View<T, E>, Error>
     where
         T: Tuple + 'static,
         E: ExpressionExt<T> + 'static,
         I: IntoExpression<T, E>,
     {
-        let expression = expression.into_expression();
-        // `validator` rejects views over `Difference` (not supported):
-        validate::validate_view_expression(&expression)?;
+        self.store_view_entry(expression.into_expression(), false, false)
+    }
+
+    /// Stores a new view over `expression` and returns a [`View`] object that can be
+    /// evaluated as a view. Unlike [`Database::store_view`], the view is not maintained
+    /// incrementally; instead, its content is recomputed from scratch when it is evaluated
+    /// after the relations and views on which it depends have changed. As a result,
+    /// `expression` may contain non-monotone expressions, such as `Difference`; the other
+    /// expressions that [`Database::store_view`] rejects, such as `Full`, are still rejected.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::Difference};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// let evens = db.add_relation::<i32>("evens").unwrap();
+    ///
+    /// let odds = db.store_view_recompute(Difference::new(numbers.clone(), evens.clone())).unwrap();
+    ///
+    /// db.insert(&numbers, vec![1, 2, 3, 4].into()).unwrap();
+    /// assert_eq!(vec![1, 2, 3, 4], db.evaluate(&odds).unwrap().into_tuples());
+    ///
+    /// db.insert(&evens, vec![2, 4].into()).unwrap();
+    /// assert_eq!(vec![1, 3], db.evaluate(&odds).unwrap().into_tuples());
+    /// ```
+    pub fn store_view_recompute<T, E, I>(&mut self, expression: I) -> Result<View<T, E>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+        I: IntoExpression<T, E>,
+    {
+        self.store_view_entry(expression.into_expression(), true, false)
+    }
+
+    /// Stores a new view over `expression` like [`Database::store_view`] and returns a
+    /// [`Counter`] that counts the distinct tuples that have ever arrived in the view. Unlike
+    /// the size of the view, the count is cumulative: it persists when the view shrinks,
+    /// e.g., after tuples are removed from the relations on which it depends. The current
+    /// count is returned by [`Database::count`].
+    ///
+    /// **Note**: the counter keeps every tuple it has seen to count it only once; hence, it
+    /// occupies memory and takes time in every update in proportion to the number of
+    /// distinct tuples that the view has ever had.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Database;
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// let seen = db.store_counter(r.clone()).unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2].into()).unwrap();
+    /// assert_eq!(2, db.count(&seen).unwrap());
+    ///
+    /// db.remove_where(&r, |_| true).unwrap();
+    /// db.insert(&r, vec![3].into()).unwrap();
+    /// assert_eq!(3, db.count(&seen).unwrap());
+    /// assert_eq!(vec![3], db.evaluate(seen.view()).unwrap().into_tuples());
+    /// ```
+    pub fn store_counter<T, E, I>(&mut self, expression: I) -> Result<Counter<T, E>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+        I: IntoExpression<T, E>,
+    {
+        self.store_view_entry(expression.into_expression(), false, true)
+            .map(Counter::new)
+    }
+
+    /// Returns the number of distinct tuples that have arrived in the view of `counter` since
+    /// it was stored, after bringing the view up to date with the relations and views on
+    /// which it depends.
+    pub fn count<T, E>(&self, counter: &Counter<T, E>) -> Result<u64, Error>
+    where
+        T: Tuple + 'static,
+        E: Expression<T> + 'static,
+    {
+        let view = counter.view();
+        let _writer = self.gate.write();
+        self.view_instance(view)?;
+        self.stabilize_view(view.reference())?;
+
+        Ok(self.views[view.reference()]
+            .instance
+            .arrived()
+            .unwrap_or_default())
+    }
+
+    /// Stores a new view over `expression`, which is maintained by recomputation if
+    /// `recompute` is true or if it depends on another view that is maintained by
+    /// recomputation. If `counting` is true, the view counts the tuples that arrive in it.
+    fn store_view_entry<T, E>(
+        &mut self,
+        expression: E,
+        recompute: bool,
+        counting: bool,
+    ) -> Result<View<T, E>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+    {
+        // reject expressions that cannot be maintained in the requested manner (the checks
+        // for incremental views also cover views that fall back to recomputation):
+        validate::validate_view_expression(&expression, recompute)?;
 
         let (relation_deps, view_deps) = dependency::expression_dependencies(&expression);
 
-        let mut entry = ViewEntry::new(ViewInstance::new(expression));
-        let reference = ViewRef(self.view_counter);
+        // catch views of other databases before they fail at evaluation:
+        if let Some(r) = view_deps.iter().find(|r| !self.views.contains_key(r)) {
+            return Err(Error::DanglingViewRef {
+                name: format!("{:?}", r),
+            });
+        }
+
+        // incremental updates cannot follow a view whose content may shrink:
+        let recompute = recompute
+            || view_deps.iter().any(|r| {
+                self.views
+                    .get(r)
+                    .map_or(false, |v| v.instance.is_recompute())
+            });
+
+        let instance = if !counting {
+            ViewInstance::new(expression, recompute)
+        } else if !recompute {
+            ViewInstance::counting(expression)
+        } else {
+            // arrivals are only observed when the view is updated incrementally:
+            return Err(Error::UnsupportedExpression {
+                name: "View maintained by recomputation".to_string(),
+                operation: "Create Counter".to_string(),
+            });
+        };
+        let mut entry = ViewEntry::new(instance);
+
+        // reuse the smallest identifier of the dropped views before minting a new one:
+        let id = self
+            .free_view_ids
+            .iter()
+            .next()
+            .copied()
+            .unwrap_or(self.view_counter);
+        let reference = ViewRef::new(id);
+
+        // track relation dependencies of this view:
+        for r in relation_deps.into_iter() {
+            if let Some(rs) = self.relations.get_mut(&r) {
+                rs.add_dependent_view(reference.clone())
+            }
+            entry.dependee_relations.insert(r);
+        }
+
+        // track view dependencies of this view:
+        for r in view_deps.into_iter() {
+            if let Some(rs) = self.views.get_mut(&r) {
+                rs.add_dependent_view(reference.clone())
+            }
+            entry.dependee_views.insert(r.clone());
+        }
+
+        entry.instance.initialize(self)?;
+
+        self.views.insert(reference.clone(), entry);
+        if !self.free_view_ids.remove(&id) {
+            self.view_counter += 1;
+        }
+        self.record(|| Mutation::StoreView {
+            view: reference.clone(),
+        });
+
+        Ok(View::new(reference))
+    }
+
+    /// Stores a new view over `expression` like [`Database::store_view`] and associates
+    /// the view with `name`, which can be used to look up the view by [`Database::named_view`].
+    pub fn store_named_view<T, E, I>(
+        &mut self,
+        name: &str,
+        expression: I,
+    ) -> Result<View<T, E>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+        I: IntoExpression<T, E>,
+    {
+        Self::validate_name(name)?;
+        if self.view_names.contains_key(name) {
+            return Err(Error::InstanceExists { name: name.into() });
+        }
+
+        let reference = self.store_view(expression)?.reference().with_name(name);
+        self.view_names.insert(name.into(), reference.clone());
+        Ok(View::new(reference))
+    }
+
+    /// Returns the reference to the view associated with `name` if it exists.
+    pub fn named_view(&self, name: &str) -> Option<ViewRef> {
+        self.view_names.get(name).cloned()
+    }
+
+    /// Resolves `reference`, e.g., a reference that is deserialized from a previous run,
+    /// to the reference of a view in the database. A named reference is resolved by the
+    /// name of its view, which is stable across runs, and an unnamed reference by its
+    /// numeric identifier. Returns `None` if no such view exists.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// let odds = db.store_named_view("odds", numbers.builder().select(|t| t % 2 == 1)).unwrap();
+    ///
+    /// let mut other = Database::new();
+    /// let numbers = other.add_relation::<i32>("numbers").unwrap();
+    /// other.store_view(numbers.clone()).unwrap();
+    /// other.store_named_view("odds", numbers.builder().select(|t| t % 2 == 1)).unwrap();
+    ///
+    /// let resolved = other.resolve_view(odds.reference()).unwrap();
+    /// assert_eq!(Some("odds"), resolved.name());
+    /// assert_ne!(odds.reference().id(), resolved.id());
+    /// ```
+    pub fn resolve_view(&self, reference: &ViewRef) -> Option<ViewRef> {
+        match reference.name() {
+            Some(name) => self.named_view(name),
+            None => self.views.get_key_value(reference).map(|(r, _)| r.clone()),
+        }
+    }
+
+    /// Forces a full recomputation of `view`: the content of the view is cleared and
+    /// computed again from the current content of the relations and views it depends on.
+    /// The views that depend on `view`, directly or through other views, are rebuilt as
+    /// well since they may have been computed from the drifted content of `view`.
+    ///
+    /// **Note**: views are maintained incrementally and need no rebuilding under normal
+    /// operation; `rebuild_view` is a recovery knob for views whose content has drifted
+    /// from their expressions, e.g., after their dependencies were modified out-of-band.
+    pub fn rebuild_view<T, E>(&self, view: &View<T, E>) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+    {
+        let _writer = self.gate.write();
+        self.view_instance(view)?;
+        self.reset_views(&self.views_transitive(vec![view.reference().clone()]))
+    }
+
+    /// Drops `view` from the database and frees its identifier, which is reused by the next
+    /// view that is stored in the database. Returns [`Error::InstanceNotFound`] if `view`
+    /// does not exist and [`Error::ViewInUse`] if other views depend on it.
+    ///
+    /// **Note**: a view that reuses an identifier is not distinguishable from the dropped
+    /// view by its [`ViewRef`]; hence, the handles of a dropped view, including the
+    /// expressions that contain it, must be discarded. Once the identifier is reused, such
+    /// handles refer to the new view and evaluating them reads its tuples if they are of
+    /// the same type.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// let odds = db.store_view(numbers.builder().select(|t| t % 2 == 1)).unwrap();
+    /// let id = odds.reference().id();
+    ///
+    /// db.drop_view(&odds).unwrap();
+    /// assert!(db.evaluate(&odds).is_err());
+    ///
+    /// let evens = db.store_view(numbers.builder().select(|t| t % 2 == 0)).unwrap();
+    /// assert_eq!(id, evens.reference().id());
+    /// ```
+    pub fn drop_view<T, E>(&mut self, view: &View<T, E>) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+        E: Expression<T> + 'static,
+    {
+        self.view_instance(view)?;
+        let reference = view.reference();
+        if !self.views[reference].dependent_views.is_empty() {
+            return Err(Error::ViewInUse {
+                name: format!("{:?}", reference),
+            });
+        }
+
+        let entry = self.views.remove(reference).unwrap();
+        for r in entry.dependee_relations.iter() {
+            if let Some(rs) = self.relations.get_mut(r) {
+                rs.dependent_views.remove(reference);
+            }
+        }
+        for r in entry.dependee_views.iter() {
+            if let Some(rs) = self.views.get_mut(r) {
+                rs.dependent_views.remove(reference);
+            }
+        }
+        self.view_names.retain(|_, r| r != reference);
+        self.free_view_ids.insert(reference.id());
+        self.record(|| Mutation::DropView {
+            view: reference.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Returns the views whose expressions directly contain the relation identified by
+    /// `relation_name`, sorted by their references.
+    pub fn dependent_views(&self, relation_name: &str) -> Vec<ViewRef> {
+        self.relations
+            .get(relation_name)
+            .map(|r| r.dependent_views.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the views that depend on the relation identified by `relation_name`, either
+    /// directly or through other views, sorted by their references.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    /// let odds = db.store_view(numbers.builder().select(|t| t % 2 == 1)).unwrap();
+    /// let big_odds = db.store_view(odds.builder().select(|&t| t > 100)).unwrap();
+    ///
+    /// assert_eq!(vec![odds.reference().clone()], db.dependent_views("numbers"));
+    /// assert_eq!(
+    ///     vec![odds.reference().clone(), big_odds.reference().clone()],
+    ///     db.dependent_views_transitive("numbers"),
+    /// );
+    /// ```
+    pub fn dependent_views_transitive(&self, relation_name: &str) -> Vec<ViewRef> {
+        self.views_transitive(self.dependent_views(relation_name))
+    }
+
+    /// Returns the views in `queue` together with the views that depend on them, either
+    /// directly or through other views, sorted by their references.
+    fn views_transitive(&self, mut queue: Vec<ViewRef>) -> Vec<ViewRef> {
+        let mut result = BTreeSet::new();
+        while let Some(view_ref) = queue.pop() {
+            if let Some(entry) = self.views.get(&view_ref) {
+                if result.insert(view_ref) {
+                    queue.extend(entry.dependent_views.iter().cloned());
+                }
+            }
+        }
+
+        result.into_iter().collect()
+    }
+
+    /// Merges the pending batches of `instance` into a single batch if eager deduplication
+    /// is enabled, or geometrically and down to the auto-compaction threshold of the
+    /// database if the threshold is set.
+    fn auto_compact<T: Tuple>(&self, instance: &Instance<T>) {
+        if self.eager_dedup {
+            instance.coalesce(1);
+        } else if let Some(threshold) = self.auto_compact_threshold {
+            instance.coalesce(threshold);
+        }
+    }
+
+    /// Inserts `tuples` in `instance` without recording the insertion in the audit log.
+    fn insert_unlogged<T>(&self, instance: &Instance<T>, tuples: Tuples<T>)
+    where
+        T: Tuple + 'static,
+    {
+        instance.insert(tuples);
+        self.auto_compact(instance);
+    }
+
+    /// Replaces the content of `instance`, the instance of the relation identified by
+    /// `name`, with `tuples` and recomputes the views that depend on the relation without
+    /// recording the replacement in the audit log.
+    fn replace_unlogged<T>(
+        &self,
+        name: &str,
+        instance: &Instance<T>,
+        tuples: Tuples<T>,
+    ) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+    {
+        instance.clear();
+        instance.insert(tuples);
+        self.reset_dependent_views(name)
+    }
+
+    /// Appends the mutation that `mutation` returns to the audit log of the database if
+    /// the log is enabled.
+    fn record(&self, mutation: impl FnOnce() -> Mutation) {
+        if let Some(log) = &self.audit_log {
+            log.borrow_mut().push(mutation());
+        }
+    }
+
+    /// Returns [`Error::InvalidName`] if `name` cannot identify a relation or a view: names
+    /// must contain a non-whitespace character and no control characters, such as line
+    /// breaks, which do not survive a round-trip through textual formats.
+    fn validate_name(name: &str) -> Result<(), Error> {
+        if name.trim().is_empty() || name.chars().any(char::is_control) {
+            return Err(Error::InvalidName { name: name.into() });
+        }
+        Ok(())
+    }
+
+    /// Returns the instance for `view` if it exists.
+    fn view_instance<T, E>(&self, view: &View<T, E>) -> Result<&Instance<T>, Error>
+    where
+        T: Tuple + 'static,
+        E: Expression<T> + 'static,
+    {
+        let not_found = || Error::InstanceNotFound {
+            name: format!("{:?}", view.reference()),
+        };
+        let entry = self.views.get(view.reference()).ok_or_else(not_found)?;
+        match entry.instance.as_any().downcast_ref::<ViewInstance<T, E>>() {
+            Some(result) => Ok(result.instance()),
+            // a view that is rebuilt from a `Shape` has a `Mono` expression instead of the
+            // expression of the stored view; its tuples are looked up by their type alone:
+            None => entry
+                .instance
+                .instance()
+                .as_any()
+                .downcast_ref::<Instance<T>>()
+                .ok_or_else(not_found),
+        }
+    }
+
+    /// Clears and re-initializes every view that depends on the relation identified
+    /// by `name`, either directly or through other views.
+    fn reset_dependent_views(&self, name: &str) -> Result<(), Error> {
+        self.reset_views(&self.dependent_views_transitive(name))
+    }
+
+    /// Clears and re-initializes the views in `affected`. A view is initialized from the
+    /// stable tuples of its dependees, which are empty for the affected dependees until
+    /// they are stabilized; hence, the views may be initialized in any order, e.g., when
+    /// a view reuses the smaller identifier of a dropped view (see [`Database::drop_view`]).
+    fn reset_views(&self, affected: &[ViewRef]) -> Result<(), Error> {
+        // clear all affected views before initializing any of them so that no view
+        // is initialized by the stale content of another view:
+        for r in affected.iter() {
+            self.views.get(r).unwrap().instance.instance().clear();
+        }
+        for r in affected.iter() {
+            self.views.get(r).unwrap().instance.initialize(self)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the relations identified by `relations` and the views identified by
+    /// `views`, together with the relations and views on which those views depend, have no
+    /// tuples to stabilize.
+    fn is_stable(&self, relations: &[String], views: &[ViewRef]) -> bool {
+        let relation_stable = |name: &str| {
+            self.relations
+                .get(name)
+                .map_or(true, |e| !e.instance.is_pending())
+        };
+        relations.iter().all(|r| relation_stable(r))
+            && views.iter().all(|view_ref| {
+                let (relations, views) = self.stabilization_order(view_ref);
+                relations.into_iter().all(relation_stable)
+                    && views.into_iter().all(|r| {
+                        let instance = &self.views[r].instance;
+                        !instance.is_dirty() && !instance.instance().is_pending()
+                    })
+            })
+    }
+
+    /// Stabilizes the view identified by `view_ref` by stabilizing its dependees and
+    /// dependencies. The relations and views on which the view depends, directly or
+    /// through other views, are stabilized in a single sweep in the order that is returned
+    /// by [`Database::stabilization_order`].
+    fn stabilize_view(&self, view_ref: &ViewRef) -> Result<(), Error> {
+        // do nothing if the view is missing or already stabilizing:
+        if self
+            .views
+            .get(view_ref)
+            .map_or(true, |entry| entry.stabilizing.get())
+        {
+            return Ok(());
+        }
+
+        let (relations, views) = self.stabilization_order(view_ref);
+        for r in relations {
+            self.stabilize_relation(r)?;
+        }
+        for r in views {
+            self.stabilize_view_instance(r)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the relations and the views on which the view identified by `view_ref`
+    /// depends, including the view itself. The relations are sorted by name and the views
+    /// are sorted topologically such that every view comes after its dependees; ties are
+    /// broken by the order of view references, making the order deterministic.
+    fn stabilization_order<'a>(
+        &'a self,
+        view_ref: &'a ViewRef,
+    ) -> (Vec<&'a str>, Vec<&'a ViewRef>) {
+        let mut relations = BTreeSet::new();
+        let mut views = Vec::new();
+        let mut visited = BTreeSet::new();
+
+        // post-order traversal of the dependees; a view is pushed to `views` when its
+        // entry is popped for the second time, after all of its dependees:
+        let mut stack = vec![(view_ref, false)];
+        while let Some((r, expanded)) = stack.pop() {
+            if expanded {
+                views.push(r);
+                continue;
+            }
+            if !visited.insert(r) {
+                continue;
+            }
+            if let Some(entry) = self.views.get(r) {
+                relations.extend(entry.dependee_relations.iter().map(String::as_str));
+
+                stack.push((r, true));
+                stack.extend(
+                    entry
+                        .dependee_views
+                        .iter()
+                        .rev()
+                        .filter(|d| !visited.contains(d))
+                        .map(|d| (d, false)),
+                );
+            }
+        }
+
+        (relations.into_iter().collect(), views)
+    }
+
+    /// Applies `changed()` on the instance of the view identified by `view_ref`, moving
+    /// all relevant `to_add` tuples to `recent` and `recent` tuples to `stable`, and
+    /// stabilizes the views that depend on it against its `recent` tuples.
+    ///
+    /// **Note**: the dependees of the view must be stable.
+    fn stabilize_view_instance(&self, view_ref: &ViewRef) -> Result<(), Error> {
+        if let Some(entry) = self.views.get(view_ref) {
+            // do nothing if the view is already stabilizing:
+            let _guard = match Stabilizing::enter(&entry.stabilizing) {
+                Some(guard) => guard,
+                None => return Ok(()),
+            };
+
+            entry.instance.recompute(self)?;
+
+            while entry.instance.instance().changed()? {
+                for r in entry.dependent_views.iter() {
+                    self.views.get(r).unwrap().instance.stabilize(self)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stabilizes the relation identified by `name`. It also stabilizes
+    /// all views depending on this `name`.
+    fn stabilize_relation(&self, name: &str) -> Result<(), Error> {
+        if let Some(entry) = self.relations.get(name) {
+            // do nothing if relation is already stabilizing:
+            let _guard = match Stabilizing::enter(&entry.stabilizing) {
+                Some(guard) => guard,
+                None => return Ok(()),
+            };
+
+            while entry.instance.changed()? {
+                for r in entry.dependent_views.iter() {
+                    self.views.get(r).unwrap().instance.stabilize(self)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Database {
+    fn clone(&self) -> Self {
+        let _reader = self.gate.read();
+        let mut relations = BTreeMap::new();
+        let mut views = BTreeMap::new();
+
+        self.relations.iter().for_each(|(k, v)| {
+            relations.insert(k.clone(), v.clone());
+        });
+        self.views.iter().for_each(|(k, v)| {
+            views.insert(k.clone(), v.clone());
+        });
+
+        Self {
+            relations,
+            views,
+            view_names: self.view_names.clone(),
+            view_counter: self.view_counter,
+            free_view_ids: self.free_view_ids.clone(),
+            audit_log: self.audit_log.clone(),
+            auto_compact_threshold: self.auto_compact_threshold,
+            eager_dedup: self.eager_dedup,
+            gate: Gate::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{Difference, Join, Project, Select};
+
+    #[test]
+    fn test_insert() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            assert!(database.insert(&r, vec![1, 2, 3].into()).is_ok());
+            assert_eq!(
+                Tuples::<i32>::from(vec![1, 2, 3]),
+                database.relation_instance(&r).unwrap().to_add()[0]
+            );
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            assert!(database.insert(&r, vec![1, 2, 3].into()).is_ok());
+            assert!(database.insert(&r, vec![1, 4].into()).is_ok());
+            assert_eq!(
+                Tuples::<i32>::from(vec![1, 2, 3]),
+                database.relation_instance(&r).unwrap().to_add()[0]
+            );
+            assert_eq!(
+                Tuples::<i32>::from(vec![1, 4]),
+                database.relation_instance(&r).unwrap().to_add()[1]
+            );
+        }
+        {
+            let database = Database::new();
+            let r = Database::new().add_relation("r").unwrap(); // dummy database
+            assert!(database.insert(&r, vec![1, 2, 3].into()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_replace_relation() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let v = database
+                .store_view(Select::new(r.clone(), |&t| t % 2 == 0))
+                .unwrap();
+            database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+            assert_eq!(vec![2, 4], database.evaluate(&v).unwrap().into_tuples());
+
+            database.replace_relation(&r, vec![5, 6, 8].into()).unwrap();
+            assert_eq!(vec![5, 6, 8], database.evaluate(&r).unwrap().into_tuples());
+            assert_eq!(vec![6, 8], database.evaluate(&v).unwrap().into_tuples());
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let v1 = database
+                .store_view(Project::new(r.clone(), |t| t * 10))
+                .unwrap();
+            let v2 = database
+                .store_view(Select::new(v1.clone(), |&t| t > 10))
+                .unwrap();
+            database.insert(&r, vec![1, 2, 3].into()).unwrap();
+            assert_eq!(vec![20, 30], database.evaluate(&v2).unwrap().into_tuples());
+
+            database.replace_relation(&r, vec![4].into()).unwrap();
+            database.insert(&r, vec![1].into()).unwrap();
+            assert_eq!(vec![10, 40], database.evaluate(&v1).unwrap().into_tuples());
+            assert_eq!(vec![40], database.evaluate(&v2).unwrap().into_tuples());
+        }
+        {
+            let database = Database::new();
+            let r = Database::new().add_relation::<i32>("r").unwrap(); // dummy database
+            assert!(database.replace_relation(&r, vec![1].into()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_remove_where() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database
+            .store_view(Project::new(r.clone(), |t| t * 10))
+            .unwrap();
+        database
+            .insert(&r, (1..=10).collect::<Vec<_>>().into())
+            .unwrap();
+        database.evaluate(&v).unwrap();
+        database.insert(&r, vec![11, 12].into()).unwrap();
+
+        assert_eq!(6, database.remove_where(&r, |t| t % 2 == 0).unwrap());
+        assert_eq!(
+            vec![1, 3, 5, 7, 9, 11],
+            database.evaluate(&r).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec![10, 30, 50, 70, 90, 110],
+            database.evaluate(&v).unwrap().into_tuples()
+        );
+        assert_eq!(0, database.remove_where(&r, |t| t % 2 == 0).unwrap());
+
+        let s = Database::new().add_relation::<i32>("s").unwrap(); // dummy database
+        assert!(database.remove_where(&s, |_| true).is_err());
+    }
+
+    #[test]
+    fn test_database_new() {
+        let database = Database::new();
+        assert!(database.relations.is_empty());
+        assert!(database.views.is_empty());
+        assert_eq!(0, database.view_counter);
+    }
+
+    #[test]
+    fn test_clone_database() {
+        {
+            let database = Database::new();
+            let cloned = database.clone();
+            assert!(cloned.relations.is_empty());
+            assert!(cloned.views.is_empty());
+            assert_eq!(0, cloned.view_counter);
+        }
+        {
+            let mut database = Database::new();
+            let a = database.add_relation::<i32>("a").unwrap();
+            let v = database.store_view(a.clone()).unwrap();
+            database.insert(&a, vec![1, 2, 3].into()).unwrap();
+
+            let cloned = database.clone();
+            database.insert(&a, vec![1, 4].into()).unwrap();
+
+            assert_eq!(
+                vec![1, 2, 3, 4],
+                database.evaluate(&v).unwrap().into_tuples()
+            );
+            assert_eq!(vec![1, 2, 3], cloned.evaluate(&v).unwrap().into_tuples());
+
+            cloned.insert(&a, vec![1, 5].into()).unwrap();
+            assert_eq!(
+                vec![1, 2, 3, 4],
+                database.evaluate(&v).unwrap().into_tuples()
+            );
+            assert_eq!(vec![1, 2, 3, 5], cloned.evaluate(&v).unwrap().into_tuples());
+        }
+    }
+
+    #[test]
+    fn test_with_snapshot() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database
+            .store_view(r.builder().project(|t| t * 10))
+            .unwrap();
+        database.insert(&r, vec![1, 2].into()).unwrap();
+
+        let (relation, view) = database.with_snapshot(|snapshot| {
+            database.insert(&r, vec![3].into()).unwrap();
+            snapshot.insert(&r, vec![4].into()).unwrap();
+            (
+                snapshot.evaluate(&r).unwrap().into_tuples(),
+                snapshot.evaluate(&v).unwrap().into_tuples(),
+            )
+        });
+        assert_eq!(vec![1, 2, 4], relation);
+        assert_eq!(vec![10, 20, 40], view);
+
+        database.insert(&r, vec![5].into()).unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 5],
+            database.evaluate(&r).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec![10, 20, 30, 50],
+            database.evaluate(&v).unwrap().into_tuples()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with_progress() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        let p = r.builder().product(&s).on(|&l, &r| (l, r)).build();
+
+        database
+            .insert(&r, (0..100).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(&s, (0..100).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let mut reports = Vec::new();
+        let result = database
+            .evaluate_with_progress(&p, |count| reports.push(count))
+            .unwrap();
+        assert_eq!(10000, result.len());
+        assert_eq!(
+            (1..=10)
+                .map(|i| i * evaluate::PROGRESS_STEP)
+                .collect::<Vec<_>>(),
+            reports
+        );
+        assert_eq!(database.evaluate(&p).unwrap(), result);
+    }
+
+    #[test]
+    fn test_evaluate_with_peak() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database
+            .insert(&r, (0..20).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(&s, (0..30).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let product = r.builder().product(s.clone()).on(|&l, &r| (l, r)).build();
+        let (result, peak) = database.evaluate_with_peak(&product).unwrap();
+        assert_eq!(600, result.len());
+        assert!(peak >= 20 * 30);
+
+        let select = r.builder().select(|t| t % 2 == 0).build();
+        let (result, peak) = database.evaluate_with_peak(&select).unwrap();
+        assert_eq!(10, result.len());
+        // the tuples of `r` are borrowed by the select, not copied:
+        assert_eq!(10, peak);
+    }
+
+    #[test]
+    fn test_evaluate_with_params() {
+        use crate::expression::Param;
+
+        let mut database = Database::new();
+        let users = database.add_relation::<(i32, String)>("users").unwrap();
+        let orders = database.add_relation::<(i32, i32)>("orders").unwrap();
+        database
+            .insert(&users, vec![(1, "alice".into()), (2, "bob".into())].into())
+            .unwrap();
+        database
+            .insert(&orders, vec![(1, 100), (1, 101), (2, 200)].into())
+            .unwrap();
+
+        let user = Param::<i32>::new("user");
+        let user_orders = user
+            .builder()
+            .with_key(|&t| t)
+            .join(users.builder().with_key(|t| t.0))
+            .on(|_, _, u| u.clone())
+            .with_key(|t| t.0)
+            .join(orders.builder().with_key(|t| t.0))
+            .on(|_, u, o| (u.1.clone(), o.1))
+            .build();
+
+        assert_eq!(
+            Tuples::from(vec![("alice".to_string(), 100), ("alice".into(), 101)]),
+            database
+                .evaluate_with_params(&user_orders, &Params::new().bind("user", 1))
+                .unwrap()
+        );
+        assert_eq!(
+            Tuples::from(vec![("bob".to_string(), 200)]),
+            database
+                .evaluate_with_params(&user_orders, &Params::new().bind("user", 2))
+                .unwrap()
+        );
+        assert!(matches!(
+            database.evaluate(&user_orders),
+            Err(Error::UnboundParam { .. })
+        ));
+        assert!(matches!(
+            database.evaluate_with_params(&user, &Params::new().bind("user", "1")),
+            Err(Error::UnboundParam { .. })
+        ));
+        assert!(database.store_view(user_orders).is_err());
+    }
+
+    #[test]
+    fn test_insert_counting() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+
+        let count = database.insert_counting(&r, vec![1, 2, 3].into()).unwrap();
+        assert_eq!(3, count);
+        let count = database.insert_counting(&r, vec![3, 4, 5].into()).unwrap();
+        assert_eq!(2, count);
+        database.evaluate(&r).unwrap();
+        let count = database.insert_counting(&r, vec![1, 5, 6].into()).unwrap();
+        assert_eq!(1, count);
+        let count = database.insert_counting(&r, vec![2, 6].into()).unwrap();
+        assert_eq!(0, count);
+        assert_eq!(
+            Tuples::<i32>::from(vec![1, 2, 3, 4, 5, 6]),
+            database.evaluate(&r).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_auto_compact() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database.store_view(r.builder().project(|t| t / 2)).unwrap();
+        database.set_auto_compact_threshold(Some(4));
+
+        for i in 0..100 {
+            database.insert(&r, vec![i, i + 1].into()).unwrap();
+            database.insert_slice(&r, &[i * 3]).unwrap();
+            assert!(database.relation_instance(&r).unwrap().to_add().len() <= 4);
+        }
+        assert_eq!(
+            (0..=100)
+                .chain((0..100).map(|i| i * 3))
+                .collect::<Tuples<_>>(),
+            database.evaluate(&r).unwrap()
+        );
+        assert_eq!(
+            (0..=100)
+                .chain((0..100).map(|i| i * 3))
+                .map(|t| t / 2)
+                .collect::<Tuples<_>>(),
+            database.evaluate(&v).unwrap()
+        );
+
+        database.set_auto_compact_threshold(None);
+        for i in 0..10 {
+            database.insert(&r, vec![i].into()).unwrap();
+        }
+        assert_eq!(10, database.relation_instance(&r).unwrap().to_add().len());
+    }
+
+    #[test]
+    fn test_eager_dedup() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database
+            .store_view(r.builder().project(|t| t * 10))
+            .unwrap();
+        database.set_eager_dedup(true);
+
+        database.insert(&r, vec![1].into()).unwrap();
+        database.insert_slice(&r, &[1]).unwrap();
+        database.insert(&r, vec![1].into()).unwrap();
+        {
+            let instance = database.relation_instance(&r).unwrap();
+            assert_eq!(vec![Tuples::from(vec![1])], *instance.to_add());
+        }
+
+        database.insert(&r, vec![0, 2].into()).unwrap();
+        assert_eq!(
+            vec![Tuples::from(vec![0, 1, 2])],
+            *database.relation_instance(&r).unwrap().to_add()
+        );
+        assert_eq!(vec![0, 1, 2], database.evaluate(&r).unwrap().into_tuples());
+        assert_eq!(
+            vec![0, 10, 20],
+            database.evaluate(&v).unwrap().into_tuples()
+        );
+
+        database.set_eager_dedup(false);
+        database.insert(&r, vec![3].into()).unwrap();
+        database.insert(&r, vec![3].into()).unwrap();
+        assert_eq!(2, database.relation_instance(&r).unwrap().to_add().len());
+    }
+
+    #[test]
+    fn test_audit_log() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1].into()).unwrap();
+        assert!(database.audit_log().is_empty());
+
+        database.enable_audit_log();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database.insert(&r, vec![2, 3].into()).unwrap();
+        database.insert(&s, vec![4, 5, 6].into()).unwrap();
+        assert!(database.insert_strict(&s, vec![6].into()).is_err());
+        database.insert_strict(&s, vec![7].into()).unwrap();
+        let v = database
+            .store_view(r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+        database.remove_where(&r, |&t| t > 2).unwrap();
+        database.remove_where(&r, |&t| t > 2).unwrap();
+        database.replace_relation(&s, vec![8, 9].into()).unwrap();
+        database.load_stable(&s, vec![vec![10].into()]).unwrap();
+        database
+            .add_relation_seeded::<i32>("t", vec![11, 12].into())
+            .unwrap();
+        database.clone_relation(&s, "u").unwrap();
+
+        let insert = |relation: &str, count| Mutation::Insert {
+            relation: relation.into(),
+            count,
+        };
+        assert_eq!(
+            vec![
+                insert("r", 2),
+                insert("s", 3),
+                insert("s", 1),
+                Mutation::StoreView {
+                    view: v.reference().clone()
+                },
+                Mutation::Remove {
+                    relation: "r".into(),
+                    count: 1
+                },
+                Mutation::Replace {
+                    relation: "s".into(),
+                    count: 2
+                },
+                Mutation::Load {
+                    relation: "s".into(),
+                    count: 1
+                },
+                Mutation::Seed {
+                    relation: "t".into(),
+                    count: 2
+                },
+                Mutation::Clone {
+                    source: "s".into(),
+                    relation: "u".into()
+                },
+            ],
+            database.audit_log()
+        );
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let mut database = Database::new();
+        for name in ["", "   ", "\t\n", "line\nbreak", "nul\0"] {
+            assert!(matches!(
+                database.add_relation::<i32>(name),
+                Err(Error::InvalidName { .. })
+            ));
+        }
+
+        let r = database.add_relation::<i32>("r").unwrap();
+        assert!(database.add_relation::<i32>("with space").is_ok());
+        assert!(database.add_relation::<i32>("naïve_名前").is_ok());
+        assert!(matches!(
+            database.clone_relation(&r, " "),
+            Err(Error::InvalidName { .. })
+        ));
+        assert!(matches!(
+            database.store_named_view("", r.clone()),
+            Err(Error::InvalidName { .. })
+        ));
+        assert!(database.store_named_view("view of r", r.clone()).is_ok());
+    }
+
+    #[test]
+    fn test_load_stable() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let odds = database
+            .store_view(r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+        database.insert(&r, vec![1, 2].into()).unwrap();
+        assert_eq!(vec![1], database.evaluate(&odds).unwrap().into_tuples());
+
+        database
+            .load_stable(&r, vec![vec![3, 4, 5].into(), vec![6, 7].into()])
+            .unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6, 7],
+            database.evaluate(&r).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec![1, 3, 5, 7],
+            database.evaluate(&odds).unwrap().into_tuples()
+        );
+
+        let stats = database.relation_stats(&r).unwrap();
+        assert_eq!(7, stats.stable.iter().sum::<usize>());
+        assert_eq!(0, stats.recent);
+        assert_eq!(0, stats.to_add);
+
+        let s = database.add_relation::<i32>("s").unwrap();
+        assert!(database.load_stable(&s, vec![]).is_ok());
+        assert!(database.relation_stats(&s).unwrap().stable.is_empty());
+    }
+
+    #[test]
+    fn test_store_view_try_project() {
+        use crate::expression::TryProject;
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let checked = TryProject::new(r.clone(), |&t| {
+            if t < 0 {
+                Err(format!("{} is negative", t))
+            } else {
+                Ok(t)
+            }
+        });
+        assert!(matches!(
+            database.store_view(checked.clone()),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        assert!(matches!(
+            database.store_view_recompute(checked.clone()),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        // a view over a view that is maintained by recomputation is maintained by
+        // recomputation as well:
+        let all = database
+            .store_view_recompute(r.builder().enumerate().build())
+            .unwrap();
+        assert!(matches!(
+            database.store_view(TryProject::new(all, |&(_, t)| Ok::<_, String>(t))),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        assert_eq!(1, database.views.len());
+
+        database.insert(&r, vec![-1, 1].into()).unwrap();
+        assert!(matches!(
+            database.evaluate(&checked),
+            Err(Error::ProjectionFailed { .. })
+        ));
+        // the failure is not hidden by a stale result:
+        assert!(matches!(
+            database.evaluate(&checked),
+            Err(Error::ProjectionFailed { .. })
+        ));
+        assert_eq!(vec![-1, 1], database.evaluate(&r).unwrap().into_tuples());
+
+        assert_eq!(1, database.remove_where(&r, |&t| t < 0).unwrap());
+        assert_eq!(vec![1], database.evaluate(&checked).unwrap().into_tuples());
+    }
+
+    #[test]
+    fn test_evaluate_cancellable() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database
+            .insert(&r, (0..20000).collect::<Vec<_>>().into())
+            .unwrap();
+        let pairs = r
+            .builder()
+            .product(&r)
+            .on_filter(|&l, &r| if l == r { Some(l) } else { None })
+            .build();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            flag.store(true, Ordering::Relaxed);
+        });
+        assert!(matches!(
+            database.evaluate_cancellable(&pairs, &cancel),
+            Err(Error::Cancelled)
+        ));
+        canceller.join().unwrap();
+
+        let join = r
+            .builder()
+            .with_key(|&t| t)
+            .join(r.builder().with_key(|&t| t))
+            .on(|_, &l, _| l)
+            .build();
+        assert!(matches!(
+            database.evaluate_cancellable(&join, &cancel),
+            Err(Error::Cancelled)
+        ));
+
+        cancel.store(false, Ordering::Relaxed);
+        assert_eq!(
+            database.evaluate(&join).unwrap(),
+            database.evaluate_cancellable(&join, &cancel).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with() {
+        use crate::expression::{Project, Select};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        let shifted = Project::with_context(&r, |offset: &i32, t| t + offset);
+        {
+            let result = database.evaluate_with(&shifted, &10).unwrap();
+            assert_eq!(vec![11, 12, 13], result.into_tuples());
+        }
+        {
+            let result = database.evaluate_with(&shifted, &-1).unwrap();
+            assert_eq!(vec![0, 1, 2], result.into_tuples());
+        }
+
+        database.insert(&r, vec![4].into()).unwrap();
+        let above = Select::with_context(&r, |min: &i32, t| t > min);
+        let shifted_above = Project::with_context(&above, |offset: &i32, t| t + offset);
+        {
+            let result = database.evaluate_with(&shifted_above, &2).unwrap();
+            assert_eq!(vec![5, 6], result.into_tuples());
+        }
+        {
+            let union = shifted_above.builder().union(&r).build();
+            let result = database.evaluate_with(&union, &3).unwrap();
+            assert_eq!(vec![1, 2, 3, 4, 7], result.into_tuples());
+        }
+
+        assert!(matches!(
+            database.evaluate(&shifted),
+            Err(Error::MissingContext)
+        ));
+        assert!(matches!(
+            database.evaluate_with(&shifted, &"10"),
+            Err(Error::MissingContext)
+        ));
+        assert!(matches!(
+            database.store_view(Project::with_context(r.clone(), |o: &i32, t| t + o)),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        assert!(matches!(
+            database.store_view(Select::with_context(r, |_: &(), _| true)),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_to_sender() {
+        use std::sync::mpsc;
+
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, i32)>("r").unwrap();
+        database
+            .insert(&r, (0..1000).map(|i| (i % 7, i)).collect::<Vec<_>>().into())
+            .unwrap();
+        let join = r
+            .builder()
+            .with_key(|t| t.0)
+            .join(r.builder().with_key(|t| t.0))
+            .on(|_, l, r| (l.1 + r.1) % 100)
+            .build();
+        let union = r.builder().union(r.clone()).build();
+
+        let (sender, receiver) = mpsc::channel();
+        database.evaluate_to_sender(&join, sender).unwrap();
+        let result: Tuples<_> = receiver.iter().collect::<Vec<_>>().into();
+        assert_eq!(database.evaluate(&join).unwrap(), result);
+
+        // the tuples that appear in more than one batch are sent once:
+        let (sender, receiver) = mpsc::channel();
+        database.evaluate_to_sender(&union, sender).unwrap();
+        let result = receiver.iter().collect::<Vec<_>>();
+        assert_eq!(1000, result.len());
+
+        let (sender, receiver) = mpsc::channel();
+        drop(receiver);
+        assert!(database.evaluate_to_sender(&join, sender).is_ok());
+    }
+
+    #[test]
+    fn test_evaluate_to_sender_streams() {
+        use std::{
+            sync::{mpsc, Mutex},
+            thread,
+            time::Duration,
+        };
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database
+            .load_stable(
+                &r,
+                vec![(0..100).collect::<Vec<_>>().into(), vec![1000].into()],
+            )
+            .unwrap();
+
+        // the selection of the second batch waits until a tuple of the first batch is
+        // received on the other end of the channel:
+        let (received, ack) = mpsc::channel();
+        let ack = Mutex::new(ack);
+        let select = r
+            .builder()
+            .select(move |&t| {
+                if t == 1000 {
+                    ack.lock()
+                        .unwrap()
+                        .recv_timeout(Duration::from_secs(10))
+                        .expect("a tuple is received before the evaluation is done");
+                }
+                t % 2 == 0
+            })
+            .build();
+
+        let (sender, receiver) = mpsc::channel();
+        let consumer = thread::spawn(move || {
+            let first = receiver.recv().unwrap();
+            received.send(()).unwrap();
+            std::iter::once(first).chain(receiver).collect::<Vec<_>>()
+        });
+        database.evaluate_to_sender(&select, sender).unwrap();
+
+        let mut result = consumer.join().unwrap();
+        result.sort();
+        let mut expected = (0..100).step_by(2).collect::<Vec<_>>();
+        expected.push(1000);
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_evaluate_unordered() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        let union = r.builder().union(s.clone()).build();
+        let project = r.builder().project(|t| t % 10).build();
+        let v = database.store_view(union.clone()).unwrap();
+
+        for i in 0..5 {
+            database
+                .insert(&r, (i * 20..i * 20 + 30).collect::<Vec<_>>().into())
+                .unwrap();
+            database
+                .insert(&s, (i * 15..i * 15 + 10).rev().collect::<Vec<_>>().into())
+                .unwrap();
+            database.evaluate(&v).unwrap();
+        }
+
+        fn normalized(tuples: Vec<i32>) -> Tuples<i32> {
+            tuples.into()
+        }
+        assert_eq!(
+            database.evaluate(&union).unwrap(),
+            normalized(database.evaluate_unordered(&union).unwrap())
+        );
+        assert_eq!(
+            database.evaluate(&project).unwrap(),
+            normalized(database.evaluate_unordered(&project).unwrap())
+        );
+        assert_eq!(
+            database.evaluate(&v).unwrap(),
+            normalized(database.evaluate_unordered(&v).unwrap())
+        );
+        assert_eq!(database.evaluate(&r).unwrap().into_tuples(), {
+            let mut tuples = database.evaluate_unordered(&r).unwrap();
+            tuples.sort_unstable();
+            tuples
+        });
+    }
+
+    #[test]
+    fn test_evaluate_take() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database
+            .insert(&r, (0..100).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(&s, (0..100).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let product = {
+            let calls = calls.clone();
+            r.builder()
+                .product(s.clone())
+                .on(move |&l, &r| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    (l, r)
+                })
+                .build()
+        };
+        let result = database.evaluate_take(&product, 5).unwrap();
+        assert_eq!(5, result.len());
+        assert_eq!(5, calls.load(Ordering::Relaxed));
+
+        let full = database.evaluate(&product).unwrap();
+        assert!(result.iter().all(|t| full.contains(t)));
+
+        // the mapper is not called once the limit is reached:
+        calls.store(0, Ordering::Relaxed);
+        assert!(database.evaluate_take(&product, 0).unwrap().is_empty());
+        assert_eq!(0, calls.load(Ordering::Relaxed));
+        database.insert(&r, vec![100].into()).unwrap();
+        database.insert(&s, vec![100].into()).unwrap();
+        assert_eq!(1, database.evaluate_take(&product, 1).unwrap().len());
+        assert_eq!(1, calls.load(Ordering::Relaxed));
+
+        let join = r
+            .builder()
+            .with_key(|&t| t % 10)
+            .join(s.builder().with_key(|&t| t % 10))
+            .on(|_, &l, &r| (l, r))
+            .build();
+        assert_eq!(7, database.evaluate_take(&join, 7).unwrap().len());
+        assert_eq!(0, database.evaluate_take(&join, 0).unwrap().len());
+
+        // sub-expressions of the root are not limited:
+        let select = Select::new(product.clone(), |&(l, r)| l == 99 && r == 99);
+        assert_eq!(
+            vec![(99, 99)],
+            database.evaluate_take(&select, 1).unwrap().into_tuples()
+        );
+    }
+
+    #[test]
+    fn test_exists_where() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database.store_view(r.builder().project(|t| t * 2)).unwrap();
+        assert!(!database.exists_where(&v, |_| true).unwrap());
+
+        database
+            .insert(&r, (0..1000).collect::<Vec<_>>().into())
+            .unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = |calls: &Arc<AtomicUsize>, predicate: fn(&i32) -> bool| {
+            let calls = calls.clone();
+            move |t: &i32| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                predicate(t)
+            }
+        };
+
+        // the predicate is not applied after the first tuple that satisfies it:
+        assert!(database
+            .exists_where(&v, counted(&calls, |&t| t < 10))
+            .unwrap());
+        assert_eq!(1, calls.load(Ordering::Relaxed));
+
+        calls.store(0, Ordering::Relaxed);
+        assert!(!database
+            .exists_where(&v, counted(&calls, |&t| t % 2 == 1))
+            .unwrap());
+        assert_eq!(1000, calls.load(Ordering::Relaxed));
+
+        // the tuples are tested in their sorted order:
+        let s = database.add_relation::<i32>("s").unwrap();
+        database.insert(&s, vec![1, 2, 3].into()).unwrap();
+        calls.store(0, Ordering::Relaxed);
+        assert!(database
+            .exists_where(&s, counted(&calls, |&t| t == 2))
+            .unwrap());
+        assert_eq!(2, calls.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_evaluate_expect_non_empty() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        assert!(matches!(
+            database.evaluate_expect_non_empty(&r),
+            Err(Error::UnexpectedEmpty)
+        ));
+
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        assert_eq!(
+            vec![1, 2, 3],
+            database
+                .evaluate_expect_non_empty(&r)
+                .unwrap()
+                .into_tuples()
+        );
+
+        let s = Select::new(r, |&t| t > 3);
+        assert!(matches!(
+            database.evaluate_expect_non_empty(&s),
+            Err(Error::UnexpectedEmpty)
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_sorted_by() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database
+            .insert(&r, vec![3, 1, 4, 1, 5, 9, 2, 6].into())
+            .unwrap();
+
+        assert_eq!(
+            vec![9, 6, 5, 4, 3, 2, 1],
+            database.evaluate_sorted_by(&r, |a, b| b.cmp(a)).unwrap()
+        );
+        assert_eq!(
+            vec![2, 4, 6, 1, 3, 5, 9],
+            database
+                .evaluate_sorted_by(&r, |a, b| (b % 2 == 0).cmp(&(a % 2 == 0)))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_interned() {
+        use std::sync::Arc;
+
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+        struct Row(i32, String);
+
+        impl Internable for Row {
+            type Interned = (i32, Arc<str>);
+
+            fn intern(&self, interner: &mut Interner) -> Self::Interned {
+                (self.0, interner.intern_str(&self.1))
+            }
+        }
+
+        let mut database = Database::new();
+        let r = database.add_relation::<Row>("r").unwrap();
+        database
+            .insert(
+                &r,
+                vec![
+                    Row(1, "shared".into()),
+                    Row(2, "shared".into()),
+                    Row(3, "other".into()),
+                ]
+                .into(),
+            )
+            .unwrap();
+
+        let result = database.evaluate_interned(&r).unwrap();
+        assert_eq!(
+            vec![
+                (1, Arc::from("shared")),
+                (2, Arc::from("shared")),
+                (3, Arc::from("other"))
+            ],
+            result.items()
+        );
+        assert!(Arc::ptr_eq(&result[0].1, &result[1].1));
+        assert!(!Arc::ptr_eq(&result[0].1, &result[2].1));
+    }
+
+    #[test]
+    fn test_evaluate_grouped() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(String, String)>("r").unwrap();
+        database
+            .insert(
+                &r,
+                vec![
+                    ("sales".to_string(), "dan".to_string()),
+                    ("eng".into(), "bob".into()),
+                    ("sales".into(), "carol".into()),
+                    ("eng".into(), "alice".into()),
+                    ("hr".into(), "erin".into()),
+                ]
+                .into(),
+            )
+            .unwrap();
+
+        let grouped = database.evaluate_grouped(&r, |t| t.0.clone()).unwrap();
+        assert_eq!(
+            vec!["eng".to_string(), "hr".into(), "sales".into()],
+            grouped.keys().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                ("eng".to_string(), "alice".to_string()),
+                ("eng".into(), "bob".into())
+            ],
+            grouped["eng"]
+        );
+        assert_eq!(vec![("hr".to_string(), "erin".to_string())], grouped["hr"]);
+        assert_eq!(
+            vec![
+                ("sales".to_string(), "carol".to_string()),
+                ("sales".into(), "dan".into())
+            ],
+            grouped["sales"]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_set() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database.store_view(r.builder().project(|t| t % 3)).unwrap();
+        database.insert(&r, vec![4, 2, 3, 7, 1].into()).unwrap();
+
+        let result = database.evaluate_set(&v).unwrap();
+        let tuples = database.evaluate(&v).unwrap();
+        assert_eq!(tuples.len(), result.len());
+        assert!(tuples.iter().all(|t| result.contains(t)));
+        assert_eq!(tuples.into_tuples(), result.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_evaluate_relation_by_name() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![3, 1, 2].into()).unwrap();
+
+        assert_eq!(
+            vec![1, 2, 3],
+            database
+                .evaluate_relation_by_name::<i32>("r")
+                .unwrap()
+                .into_tuples()
+        );
+        assert!(matches!(
+            database.evaluate_relation_by_name::<String>("r"),
+            Err(Error::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            database.evaluate_relation_by_name::<i32>("s"),
+            Err(Error::InstanceNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_relation() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, String)>("r").unwrap();
+        database
+            .insert(&r, vec![(1, "a".to_string())].into())
+            .unwrap();
+
+        let handle = database.relation::<(i32, String)>("r").unwrap();
+        database
+            .insert(&handle, vec![(2, "b".to_string())].into())
+            .unwrap();
+        assert_eq!(
+            database.evaluate(&r).unwrap(),
+            database.evaluate(&handle).unwrap()
+        );
+        assert_eq!(2, database.evaluate(&handle).unwrap().len());
+
+        assert!(matches!(
+            database.relation::<(i32, i32)>("r"),
+            Err(Error::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            database.relation::<(i32, String)>("s"),
+            Err(Error::InstanceNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_relation_stats() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        assert_eq!(
+            RelationStats::default(),
+            database.relation_stats(&r).unwrap()
+        );
+
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        database.insert(&r, vec![3, 4].into()).unwrap();
+        assert_eq!(
+            RelationStats {
+                stable: vec![],
+                recent: 0,
+                to_add: 5,
+            },
+            database.relation_stats(&r).unwrap()
+        );
+
+        database.evaluate(&r).unwrap();
+        database.insert(&r, vec![5].into()).unwrap();
+        assert_eq!(
+            RelationStats {
+                stable: vec![4],
+                recent: 0,
+                to_add: 1,
+            },
+            database.relation_stats(&r).unwrap()
+        );
+
+        assert!(matches!(
+            database.relation_stats(&Relation::<String>::new("r")),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_relation_version() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        assert_eq!(0, database.relation_version(&r).unwrap());
+
+        database.insert(&r, vec![1, 2].into()).unwrap();
+        let inserted = database.relation_version(&r).unwrap();
+        assert!(inserted > 0);
+
+        database.evaluate(&r).unwrap();
+        let evaluated = database.relation_version(&r).unwrap();
+        assert!(evaluated > inserted);
+
+        database.evaluate(&r).unwrap();
+        database.evaluate(&r).unwrap();
+        assert_eq!(evaluated, database.relation_version(&r).unwrap());
+
+        database.replace_relation(&r, vec![3].into()).unwrap();
+        assert!(database.relation_version(&r).unwrap() > evaluated);
+    }
+
+    #[test]
+    fn test_is_view_current() {
+        use crate::expression::{Difference, Project};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        let v = database
+            .store_view(Project::new(r.clone(), |t| t + 1))
+            .unwrap();
+        let w = database
+            .store_view(Project::new(v.clone(), |t| t * 10))
+            .unwrap();
+        let d = database
+            .store_view_recompute(Difference::new(r.clone(), s.clone()))
+            .unwrap();
+        assert!(database.is_view_current(&v).unwrap());
+        assert!(database.is_view_current(&w).unwrap());
+        assert!(database.is_view_current(&d).unwrap());
+
+        database.insert(&r, vec![1, 2].into()).unwrap();
+        assert!(!database.is_view_current(&v).unwrap());
+        assert!(!database.is_view_current(&w).unwrap());
+        assert!(!database.is_view_current(&d).unwrap());
+
+        assert_eq!(vec![20, 30], database.evaluate(&w).unwrap().into_tuples());
+        assert!(database.is_view_current(&v).unwrap());
+        assert!(database.is_view_current(&w).unwrap());
+        assert!(!database.is_view_current(&d).unwrap());
+
+        assert_eq!(vec![1, 2], database.evaluate(&d).unwrap().into_tuples());
+        assert!(database.is_view_current(&d).unwrap());
+
+        database.insert(&s, vec![2].into()).unwrap();
+        assert!(database.is_view_current(&w).unwrap());
+        assert!(!database.is_view_current(&d).unwrap());
+    }
+
+    #[test]
+    fn test_store_counter() {
+        use crate::expression::{Difference, Select};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database.insert(&r, vec![1].into()).unwrap();
+        let evens = database
+            .store_counter(Select::new(r.clone(), |t| t % 2 == 0))
+            .unwrap();
+        let all = database.store_counter(r.clone()).unwrap();
+        assert_eq!(0, database.count(&evens).unwrap());
+        assert_eq!(1, database.count(&all).unwrap());
+
+        database.insert(&r, vec![2, 3, 4].into()).unwrap();
+        assert_eq!(2, database.count(&evens).unwrap());
+        assert_eq!(4, database.count(&all).unwrap());
+
+        database.remove_where(&r, |t| *t < 4).unwrap();
+        database.insert(&r, vec![4, 5, 6].into()).unwrap();
+        assert_eq!(3, database.count(&evens).unwrap());
+        assert_eq!(6, database.count(&all).unwrap());
+
+        database.replace_relation(&r, vec![1, 8].into()).unwrap();
+        database.insert(&r, vec![8, 10].into()).unwrap();
+        assert_eq!(5, database.count(&evens).unwrap());
+        assert_eq!(8, database.count(&all).unwrap());
+        assert_eq!(
+            vec![8, 10],
+            database.evaluate(evens.view()).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec![1, 8, 10],
+            database.evaluate(all.view()).unwrap().into_tuples()
+        );
+
+        let cloned = database.clone();
+        database.insert(&r, vec![12].into()).unwrap();
+        assert_eq!(6, database.count(&evens).unwrap());
+        assert_eq!(5, cloned.count(&evens).unwrap());
+
+        let difference = database
+            .store_view_recompute(Difference::new(r, s))
+            .unwrap();
+        assert!(matches!(
+            database.store_counter(difference),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tuples_extremes() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let empty = database.evaluate(&r).unwrap();
+        assert_eq!(None, empty.min());
+        assert_eq!(None, empty.max());
+
+        database.insert(&r, vec![42, -7, 15, 3].into()).unwrap();
+        let tuples = database.evaluate(&r).unwrap();
+        assert_eq!(Some(&-7), tuples.min());
+        assert_eq!(Some(&42), tuples.max());
+        assert_eq!(Some(&3), tuples.nth(1));
+        assert_eq!(None, tuples.nth(4));
+    }
+
+    #[test]
+    fn test_column_stats() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        assert_eq!(
+            ColumnStats {
+                count: 0,
+                min: None,
+                max: None,
+                sum: 0,
+            },
+            database.column_stats(&r).unwrap()
+        );
+
+        database
+            .insert(&r, (1..=100).collect::<Vec<_>>().into())
+            .unwrap();
+        assert_eq!(
+            ColumnStats {
+                count: 100,
+                min: Some(1),
+                max: Some(100),
+                sum: 5050,
+            },
+            database.column_stats(&r).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validate_invariants() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let s = database.add_relation::<i32>("s").unwrap();
+            let v = database
+                .store_view(r.builder().union(s.clone()).build())
+                .unwrap();
+            database
+                .store_view(v.builder().select(|t| t % 2 == 0))
+                .unwrap();
+            database.insert(&r, vec![1, 2, 3].into()).unwrap();
+            database.evaluate(&v).unwrap();
+            database.insert(&s, vec![3, 4].into()).unwrap();
+            assert!(database.validate_invariants().is_ok());
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let mut batch: Tuples<i32> = vec![1, 2, 3].into();
+            batch.reverse(); // `Tuples` can be corrupted through `DerefMut`
+            database.insert(&r, batch).unwrap();
+            assert!(matches!(
+                database.validate_invariants(),
+                Err(Error::Corrupted { .. })
+            ));
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let v = database.store_view(r.builder().build()).unwrap();
+            database
+                .relations
+                .get_mut("r")
+                .unwrap()
+                .dependent_views
+                .clear();
+            assert!(matches!(
+                database.validate_invariants(),
+                Err(Error::Corrupted { .. })
+            ));
+            database
+                .relations
+                .get_mut("r")
+                .unwrap()
+                .add_dependent_view(v.reference().clone());
+            assert!(database.validate_invariants().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_estimated_bytes() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i64, i64)>("r").unwrap();
+        assert_eq!(0, database.estimated_bytes());
+
+        database
+            .insert(&r, (0..1000).map(|i| (i, i)).collect::<Vec<_>>().into())
+            .unwrap();
+        let inserted = database.estimated_bytes();
+        assert_eq!(1000 * 16, inserted);
 
-        // track relation dependencies of this view:
-        for r in relation_deps.into_iter() {
-            if let Some(rs) = self.relations.get_mut(&r) {
-                rs.add_dependent_view(reference.clone())
-            }
-            entry.dependee_relations.insert(r);
-        }
+        let v = database.store_view(r.builder().project(|t| t.0)).unwrap();
+        database.evaluate(&v).unwrap();
+        assert_eq!(inserted + 1000 * 8, database.estimated_bytes());
+    }
 
-        // track view dependencies of this view:
-        for r in view_deps.into_iter() {
-            if let Some(rs) = self.views.get_mut(&r) {
-                rs.add_dependent_view(reference.clone())
-            }
-            entry.dependee_views.insert(r.clone());
-        }
+    #[test]
+    fn test_rebuild_view() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let odds = r.builder().select(|t| t % 2 == 1).build();
+        let v = database.store_view(odds.clone()).unwrap();
+        let w = database
+            .store_view(v.builder().project(|t| t * 10))
+            .unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        database.evaluate(&w).unwrap();
+        database.insert(&r, vec![4, 5].into()).unwrap();
 
-        entry.instance.initialize(self)?;
+        // simulate a drift by clearing the view behind the back of the database:
+        database.views[v.reference()].instance.instance().clear();
+        database.views[w.reference()].instance.instance().clear();
+        assert_eq!(Tuples::<i32>::from(vec![5]), database.evaluate(&v).unwrap());
 
-        self.views.insert(reference.clone(), entry);
-        self.view_counter += 1;
+        database.rebuild_view(&v).unwrap();
+        assert_eq!(
+            database.evaluate(&odds).unwrap(),
+            database.evaluate(&v).unwrap()
+        );
+        assert_eq!(
+            Tuples::<i32>::from(vec![10, 30, 50]),
+            database.evaluate(&w).unwrap()
+        );
 
-        Ok(View::new(reference))
+        assert!(Database::new().rebuild_view(&v).is_err());
     }
 
-    /// Returns the instance for `view` if it exists.
-    fn view_instance<T, E>(&self, view: &View<T, E>) -> Result<&Instance<T>, Error>
-    where
-        T: Tuple + 'static,
-        E: Expression<T> + 'static,
-    {
-        let result = self
-            .views
-            .get(view.reference())
-            .and_then(|v| v.instance.as_any().downcast_ref::<ViewInstance<T, E>>())
-            .ok_or(Error::InstanceNotFound {
-                name: format!("{:?}", view.reference()),
-            })?;
-        Ok(result.instance())
+    #[test]
+    fn test_add_relation_seeded() {
+        let mut database = Database::new();
+        let r = database
+            .add_relation_seeded::<i32>("r", vec![3, 1, 2].into())
+            .unwrap();
+        let v = database.store_view(r.builder().select(|&t| t > 1)).unwrap();
+
+        assert_eq!(vec![1, 2, 3], database.evaluate(&r).unwrap().into_tuples());
+        assert_eq!(vec![2, 3], database.evaluate(&v).unwrap().into_tuples());
+        assert!(database
+            .add_relation_seeded::<i32>("r", vec![4].into())
+            .is_err());
+        assert_eq!(vec![1, 2, 3], database.evaluate(&r).unwrap().into_tuples());
     }
 
-    /// Stabilizes the view identified by `view_ref` by stabilizing its dependees and
-    /// dependencies. It also applies `changed()` on the view's instance, moving all
-    /// relevant `to_add` tuples to `recent` and `recent` tuples to `stable`.
-    fn stabilize_view(&self, view_ref: &ViewRef) -> Result<(), Error> {
-        if let Some(entry) = self.views.get(view_ref) {
-            // do nothing if the view is already stabilizing:
-            if entry.stabilizing.get() {
-                return Ok(());
-            }
+    #[test]
+    fn test_builder() {
+        let database = Database::builder()
+            .relation_seeded::<i32>("r", vec![3, 1, 2].into())
+            .relation_seeded::<(i32, bool)>("s", vec![(1, true)].into())
+            .relation::<String>("t")
+            .build()
+            .unwrap();
+        let r = database.relation::<i32>("r").unwrap();
+        let s = database.relation::<(i32, bool)>("s").unwrap();
+        let t = database.relation::<String>("t").unwrap();
 
-            entry.stabilizing.set(true);
+        assert_eq!(vec![1, 2, 3], database.evaluate(&r).unwrap().into_tuples());
+        assert_eq!(
+            vec![(1, true)],
+            database.evaluate(&s).unwrap().into_tuples()
+        );
+        assert!(database.evaluate(&t).unwrap().is_empty());
 
-            for r in entry.dependee_relations.iter() {
-                self.stabilize_relation(r)?;
-            }
-            for r in entry.dependee_views.iter() {
-                self.stabilize_view(r)?;
-            }
+        assert!(matches!(
+            Database::builder()
+                .relation::<i32>("r")
+                .relation_seeded::<i32>("r", vec![4].into())
+                .relation::<i32>("")
+                .build(),
+            Err(Error::InstanceExists { .. })
+        ));
+    }
 
-            while entry.instance.instance().changed() {
-                for r in entry.dependent_views.iter() {
-                    self.views.get(r).unwrap().instance.stabilize(&self)?;
-                }
-            }
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_add_relation_spilled() {
+        let mut database = Database::new();
+        let r = database.add_relation_spilled::<i32>("r", 10).unwrap();
+        let v = database
+            .store_view(Select::new(r.clone(), |t| t % 3 == 0))
+            .unwrap();
+        assert!(matches!(
+            database.add_relation_spilled::<i32>("r", 10),
+            Err(Error::InstanceExists { .. })
+        ));
 
-            entry.stabilizing.set(false);
-        }
+        database
+            .insert(&r, (0..50).collect::<Vec<_>>().into())
+            .unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        assert_eq!(
+            (0..50).collect::<Vec<_>>(),
+            database.evaluate(&r).unwrap().into_tuples()
+        );
 
-        Ok(())
+        database
+            .insert(&r, (40..60).collect::<Vec<_>>().into())
+            .unwrap();
+        database.insert(&r, vec![100].into()).unwrap();
+        assert!(database.insert_strict(&r, vec![25].into()).is_err());
+        assert_eq!(
+            (0..60).chain(vec![100]).collect::<Vec<_>>(),
+            database.evaluate(&r).unwrap().into_tuples()
+        );
+        assert_eq!(
+            (0..60).filter(|t| t % 3 == 0).collect::<Vec<_>>(),
+            database.evaluate(&v).unwrap().into_tuples()
+        );
+
+        assert_eq!(vec![50, 11], database.relation_stats(&r).unwrap().stable);
+
+        let cloned = database.clone();
+        assert_eq!(database.evaluate(&r).unwrap(), cloned.evaluate(&r).unwrap());
     }
 
-    /// Stabilizes the relation identified by `name`. It also stabilizes
-    /// all views depending on this `name`.
-    fn stabilize_relation(&self, name: &str) -> Result<(), Error> {
-        if let Some(entry) = self.relations.get(name) {
-            // do nothing if relation is already stabilizing:
-            if entry.stabilizing.get() {
-                return Ok(());
-            }
+    #[test]
+    fn test_materialize_as_relation() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, char)>("r").unwrap();
+        let s = database.add_relation::<(i32, bool)>("s").unwrap();
+        database
+            .insert(&r, vec![(1, 'a'), (2, 'b'), (3, 'c')].into())
+            .unwrap();
+        database
+            .insert(&s, vec![(1, true), (3, false)].into())
+            .unwrap();
 
-            entry.stabilizing.set(true);
+        let join = Join::new(&r, &s, |t| t.0, |t| t.0, |_, &l, &r| (l.1, r.1));
+        let m = database.materialize_as_relation("m", &join).unwrap();
+        assert_eq!(
+            vec![('a', true), ('c', false)],
+            database.evaluate(&m).unwrap().into_tuples()
+        );
 
-            while entry.instance.changed() {
-                for r in entry.dependent_views.iter() {
-                    self.views.get(r).unwrap().instance.stabilize(&self)?;
-                }
-            }
+        database.insert(&r, vec![(4, 'd')].into()).unwrap();
+        database
+            .insert(&s, vec![(2, true), (4, true)].into())
+            .unwrap();
+        assert_eq!(
+            vec![('a', true), ('c', false)],
+            database.evaluate(&m).unwrap().into_tuples()
+        );
+        assert_eq!(4, database.evaluate(&join).unwrap().len());
 
-            entry.stabilizing.set(false);
-        }
+        database.insert(&m, vec![('z', false)].into()).unwrap();
+        assert_eq!(
+            vec![('a', true), ('c', false), ('z', false)],
+            database.evaluate(&m).unwrap().into_tuples()
+        );
 
-        Ok(())
+        assert!(matches!(
+            database.materialize_as_relation("m", &join),
+            Err(Error::InstanceExists { .. })
+        ));
+        assert!(matches!(
+            database.materialize_as_relation("n", &Relation::<i32>::new("t")),
+            Err(Error::InstanceNotFound { .. })
+        ));
+        assert!(matches!(
+            database.relation::<i32>("n"),
+            Err(Error::InstanceNotFound { .. })
+        ));
     }
-}
 
-impl Default for Database {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+    #[test]
+    fn test_clone_relation() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database
+            .store_view(Select::new(r.clone(), |&t| t > 1))
+            .unwrap();
+        database.insert(&r, vec![1, 2].into()).unwrap();
+        database.insert(&r, vec![3].into()).unwrap();
 
-impl Clone for Database {
-    fn clone(&self) -> Self {
-        let mut relations = HashMap::new();
-        let mut views = HashMap::new();
+        let s = database.clone_relation(&r, "s").unwrap();
+        assert_eq!(vec![1, 2, 3], database.evaluate(&s).unwrap().into_tuples());
 
-        self.relations.iter().for_each(|(k, v)| {
-            relations.insert(k.clone(), v.clone());
-        });
-        self.views.iter().for_each(|(k, v)| {
-            views.insert(k.clone(), v.clone());
-        });
+        database.insert(&s, vec![4, 5].into()).unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 4, 5],
+            database.evaluate(&s).unwrap().into_tuples()
+        );
+        assert_eq!(vec![1, 2, 3], database.evaluate(&r).unwrap().into_tuples());
+        assert_eq!(vec![2, 3], database.evaluate(&v).unwrap().into_tuples());
 
-        Self {
-            relations,
-            views,
-            view_counter: self.view_counter,
-        }
+        assert!(matches!(
+            database.clone_relation(&r, "s"),
+            Err(Error::InstanceExists { .. })
+        ));
+        assert!(matches!(
+            database.clone_relation(&Relation::<i32>::new("t"), "u"),
+            Err(Error::InstanceNotFound { .. })
+        ));
+        assert!(matches!(
+            database.clone_relation(&Relation::<bool>::new("r"), "u"),
+            Err(Error::TypeMismatch { .. })
+        ));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::expression::{Join, Project, Select};
 
     #[test]
-    fn test_insert() {
-        {
-            let mut database = Database::new();
-            let r = database.add_relation::<i32>("r").unwrap();
-            assert!(database.insert(&r, vec![1, 2, 3].into()).is_ok());
-            assert_eq!(
-                Tuples::<i32>::from(vec![1, 2, 3]),
-                database.relation_instance(&r).unwrap().to_add()[0]
-            );
-        }
-        {
-            let mut database = Database::new();
-            let r = database.add_relation::<i32>("r").unwrap();
-            assert!(database.insert(&r, vec![1, 2, 3].into()).is_ok());
-            assert!(database.insert(&r, vec![1, 4].into()).is_ok());
-            assert_eq!(
-                Tuples::<i32>::from(vec![1, 2, 3]),
-                database.relation_instance(&r).unwrap().to_add()[0]
-            );
-            assert_eq!(
-                Tuples::<i32>::from(vec![1, 4]),
-                database.relation_instance(&r).unwrap().to_add()[1]
-            );
-        }
-        {
-            let database = Database::new();
-            let r = Database::new().add_relation("r").unwrap(); // dummy database
-            assert!(database.insert(&r, vec![1, 2, 3].into()).is_err());
-        }
+    fn test_insert_strict() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+
+        database.insert_strict(&r, vec![1, 2].into()).unwrap();
+        database.evaluate(&r).unwrap();
+        database.insert_strict(&r, vec![3, 4].into()).unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            database.evaluate(&r).unwrap().into_tuples()
+        );
+
+        assert!(matches!(
+            database.insert_strict(&r, vec![2, 5].into()),
+            Err(Error::DuplicateInsert { .. })
+        ));
+        database.insert_strict(&r, vec![6].into()).unwrap();
+        assert!(matches!(
+            database.insert_strict(&r, vec![6].into()),
+            Err(Error::DuplicateInsert { .. })
+        ));
+        assert_eq!(
+            vec![1, 2, 3, 4, 6],
+            database.evaluate(&r).unwrap().into_tuples()
+        );
     }
 
     #[test]
-    fn test_database_new() {
-        let database = Database::new();
-        assert!(database.relations.is_empty());
-        assert!(database.views.is_empty());
-        assert_eq!(0, database.view_counter);
+    fn test_store_named_view() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.store_view(r.clone()).unwrap();
+        let v = database
+            .store_named_view("odds", r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+
+        assert_eq!(Some(v.reference().clone()), database.named_view("odds"));
+        assert_eq!(Some(ViewRef::new(1)), database.clone().named_view("odds"));
+        assert_eq!(None, database.named_view("evens"));
+        assert!(matches!(
+            database.store_named_view("odds", r.clone()),
+            Err(Error::InstanceExists { .. })
+        ));
     }
 
     #[test]
-    fn test_clone_database() {
-        {
-            let database = Database::new();
-            let cloned = database.clone();
-            assert!(cloned.relations.is_empty());
-            assert!(cloned.views.is_empty());
-            assert_eq!(0, cloned.view_counter);
-        }
-        {
-            let mut database = Database::new();
-            let a = database.add_relation::<i32>("a").unwrap();
-            let v = database.store_view(a.clone()).unwrap();
-            database.insert(&a, vec![1, 2, 3].into()).unwrap();
+    fn test_resolve_view() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let u = database.store_view(r.clone()).unwrap();
+        let v = database
+            .store_named_view("odds", r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+        assert_eq!(None, u.reference().name());
+        assert_eq!(Some("odds"), v.reference().name());
 
-            let cloned = database.clone();
-            database.insert(&a, vec![1, 4].into()).unwrap();
+        let cloned = database.clone();
+        let reference = cloned.named_view("odds").unwrap();
+        assert_eq!(Some("odds"), reference.name());
+        assert_eq!(Some(reference.clone()), cloned.resolve_view(v.reference()));
+        assert_eq!(
+            Some(u.reference().clone()),
+            cloned.resolve_view(u.reference())
+        );
+        assert_eq!(None, Database::new().resolve_view(u.reference()));
 
-            assert_eq!(
-                vec![1, 2, 3, 4],
-                database.evaluate(&v).unwrap().into_tuples()
-            );
-            assert_eq!(vec![1, 2, 3], cloned.evaluate(&v).unwrap().into_tuples());
+        // the name of a view survives the numeric identifier of the view:
+        let mut other = Database::new();
+        let r = other.add_relation::<i32>("r").unwrap();
+        let w = other
+            .store_named_view("odds", r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+        assert_eq!(
+            Some(w.reference().clone()),
+            other.resolve_view(v.reference())
+        );
+        other.insert(&r, vec![1, 2, 3].into()).unwrap();
+        let resolved = other.resolve_view(v.reference()).unwrap();
+        let resolved = View::<i32, crate::expression::Select<i32, Relation<i32>>>::new(resolved);
+        assert_eq!(
+            Tuples::<i32>::from(vec![1, 3]),
+            other.evaluate(&resolved).unwrap()
+        );
 
-            cloned.insert(&a, vec![1, 5].into()).unwrap();
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(v.reference()).unwrap();
+            let deserialized: ViewRef = serde_json::from_str(&json).unwrap();
+            assert_eq!(Some("odds"), deserialized.name());
             assert_eq!(
-                vec![1, 2, 3, 4],
-                database.evaluate(&v).unwrap().into_tuples()
+                Some(w.reference().clone()),
+                other.resolve_view(&deserialized)
             );
-            assert_eq!(vec![1, 2, 3, 5], cloned.evaluate(&v).unwrap().into_tuples());
         }
     }
 
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database
+            .store_view(r.builder().select(|t| t % 2 == 0))
+            .unwrap();
+
+        database
+            .insert(&r, (0..10000).collect::<Vec<_>>().into())
+            .unwrap();
+        assert_eq!(5000, database.evaluate(&v).unwrap().len());
+
+        database.replace_relation(&r, vec![1, 2].into()).unwrap();
+        database.shrink_to_fit();
+        assert_eq!(vec![1, 2], database.evaluate(&r).unwrap().into_tuples());
+        assert_eq!(vec![2], database.evaluate(&v).unwrap().into_tuples());
+    }
+
     #[test]
     fn test_add_relation() {
         let mut database = Database::new();
         assert!(database.add_relation::<i32>("a").is_ok());
         assert!(database.add_relation::<i32>("a").is_err()); // duplicate
-        assert!(database.relations.get("a").is_some());
-        assert!(database.relations.get("b").is_none());
+        assert!(database.relations.contains_key("a"));
+        assert!(!database.relations.contains_key("b"));
     }
 
     #[test]
@@ -457,7 +3652,14 @@ mod tests {
         let relation_string = dummy.add_relation::<String>("a").unwrap();
 
         assert!(database.relation_instance(&relation_i32).is_ok());
-        assert!(database.relation_instance(&relation_string).is_err());
+        assert!(matches!(
+            database.relation_instance(&relation_string),
+            Err(Error::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            database.relation_instance(&Relation::<i32>::new("b")),
+            Err(Error::InstanceNotFound { .. })
+        ));
     }
 
     #[test]
@@ -466,28 +3668,51 @@ mod tests {
             let mut database = Database::new();
             let a = database.add_relation::<i32>("a").unwrap();
             database.store_view(a.clone()).unwrap();
-            assert!(database.views.get(&ViewRef(0)).is_some());
-            assert!(database.views.get(&ViewRef(1000)).is_none());
+            assert!(database.views.contains_key(&ViewRef::new(0)));
+            assert!(!database.views.contains_key(&ViewRef::new(1000)));
         }
         {
             let mut database = Database::new();
             let _ = database.add_relation::<i32>("a").unwrap();
             database.store_view(Relation::<i32>::new("a")).unwrap();
-            assert!(database.views.get(&ViewRef(0)).is_some());
-            assert!(database.views.get(&ViewRef(1000)).is_none());
+            assert!(database.views.contains_key(&ViewRef::new(0)));
+            assert!(!database.views.contains_key(&ViewRef::new(1000)));
         }
         {
             let mut database = Database::new();
             assert!(database.store_view(Relation::<i32>::new("a")).is_err());
         }
+        {
+            let mut database = Database::new();
+            let a = database.add_relation::<i32>("a").unwrap();
+            assert!(matches!(
+                database.store_view(a.builder().enumerate().build()),
+                Err(Error::UnsupportedExpression { .. })
+            ));
+        }
+        {
+            use crate::expression::{Full, Intersect};
+
+            let mut database = Database::new();
+            let a = database.add_relation::<i32>("a").unwrap();
+            assert!(matches!(
+                database.store_view(Intersect::new(Full::new(), a)),
+                Err(Error::UnsupportedExpression { name, .. }) if name == "Full"
+            ));
+            assert!(matches!(
+                database.store_view(Full::<i32>::new()),
+                Err(Error::UnsupportedExpression { name, .. }) if name == "Full"
+            ));
+            assert!(database.views.is_empty());
+        }
 
         {
             let mut database = Database::new();
             let a = database.add_relation::<i32>("a").unwrap();
             database.store_view(Select::new(a, |&t| t != 0)).unwrap();
 
-            assert!(database.views.get(&ViewRef(0)).is_some());
-            assert!(database.views.get(&ViewRef(1000)).is_none());
+            assert!(database.views.contains_key(&ViewRef::new(0)));
+            assert!(!database.views.contains_key(&ViewRef::new(1000)));
         }
 
         {
@@ -495,8 +3720,8 @@ mod tests {
             let a = database.add_relation::<i32>("a").unwrap();
             database.store_view(Project::new(a, |t| t + 1)).unwrap();
 
-            assert!(database.views.get(&ViewRef(0)).is_some());
-            assert!(database.views.get(&ViewRef(1000)).is_none());
+            assert!(database.views.contains_key(&ViewRef::new(0)));
+            assert!(!database.views.contains_key(&ViewRef::new(1000)));
         }
 
         {
@@ -507,8 +3732,8 @@ mod tests {
                 .store_view(Join::new(a, b, |t| t.0, |t| t.0, |_, &l, &r| (l, r)))
                 .unwrap();
 
-            assert!(database.views.get(&ViewRef(0)).is_some());
-            assert!(database.views.get(&ViewRef(1000)).is_none());
+            assert!(database.views.contains_key(&ViewRef::new(0)));
+            assert!(!database.views.contains_key(&ViewRef::new(1000)));
         }
 
         {
@@ -517,10 +3742,230 @@ mod tests {
             let view = database.store_view(a).unwrap();
 
             database.store_view(view).unwrap();
-            assert!(database.views.get(&ViewRef(0)).is_some());
-            assert!(database.views.get(&ViewRef(1)).is_some());
-            assert!(database.views.get(&ViewRef(1000)).is_none());
+            assert!(database.views.contains_key(&ViewRef::new(0)));
+            assert!(database.views.contains_key(&ViewRef::new(1)));
+            assert!(!database.views.contains_key(&ViewRef::new(1000)));
+        }
+    }
+
+    #[test]
+    fn test_builder_store() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        let view = r.builder().select(|&t| t > 2).store(&mut database).unwrap();
+        assert!(database.views.contains_key(view.reference()));
+        assert_eq!(vec![3, 4], database.evaluate(&view).unwrap().into_tuples());
+
+        database.insert(&r, vec![5].into()).unwrap();
+        assert_eq!(
+            vec![3, 4, 5],
+            database.evaluate(&view).unwrap().into_tuples()
+        );
+
+        assert!(matches!(
+            r.builder().enumerate().store(&mut database),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+    }
+
+    #[test]
+    fn test_store_view_dangling() {
+        let mut other = Database::new();
+        let r = other.add_relation::<i32>("r").unwrap();
+        other.store_view(r.clone()).unwrap();
+        let v = other.store_view(r.clone()).unwrap();
+
+        let mut database = Database::new();
+        database.add_relation::<i32>("r").unwrap();
+        assert!(matches!(
+            database.store_view(Select::new(v.clone(), |&t| t > 0)),
+            Err(Error::DanglingViewRef { .. })
+        ));
+        assert!(matches!(
+            database.store_view_recompute(v),
+            Err(Error::DanglingViewRef { .. })
+        ));
+        assert!(database.views.is_empty());
+        assert_eq!(0, database.view_counter);
+    }
+
+    #[test]
+    fn test_store_view_recompute() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        let v = database
+            .store_view_recompute(Difference::new(r.clone(), s.clone()))
+            .unwrap();
+        let w = database
+            .store_view(Project::new(v.clone(), |t| t * 10))
+            .unwrap();
+
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        assert_eq!(vec![1, 2, 3], database.evaluate(&v).unwrap().into_tuples());
+        assert_eq!(
+            vec![10, 20, 30],
+            database.evaluate(&w).unwrap().into_tuples()
+        );
+
+        database.insert(&s, vec![2, 3].into()).unwrap();
+        assert_eq!(vec![10], database.evaluate(&w).unwrap().into_tuples());
+        assert_eq!(vec![1], database.evaluate(&v).unwrap().into_tuples());
+
+        database.insert(&r, vec![4].into()).unwrap();
+        assert_eq!(vec![1, 4], database.evaluate(&v).unwrap().into_tuples());
+        assert_eq!(vec![10, 40], database.evaluate(&w).unwrap().into_tuples());
+    }
+
+    #[test]
+    fn test_store_view_recompute_unsupported() {
+        use crate::expression::{Full, Intersect, Param};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        assert!(matches!(
+            database.store_view_recompute(Intersect::new(Full::new(), r.clone())),
+            Err(Error::UnsupportedExpression { name, .. }) if name == "Full"
+        ));
+        assert!(matches!(
+            database.store_view_recompute(Intersect::new(Param::new("p"), r.clone())),
+            Err(Error::UnsupportedExpression { name, .. }) if name == "Param"
+        ));
+        assert!(matches!(
+            database.store_view_recompute(Select::with_context(r.clone(), |_: &(), _| true)),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        assert!(matches!(
+            database.store_view_recompute(Project::with_context(r.clone(), |_: &(), &t| t)),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        assert!(matches!(
+            database.store_view_recompute(Difference::new(r.clone(), Full::new())),
+            Err(Error::UnsupportedExpression { name, .. }) if name == "Full"
+        ));
+        assert!(database.views.is_empty());
+
+        // non-monotone expressions are supported:
+        assert!(database
+            .store_view_recompute(r.builder().enumerate().build())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_dependent_views() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.add_relation::<i32>("s").unwrap();
+        let a = database.store_view(Select::new(r, |&t| t > 0)).unwrap();
+        let b = database
+            .store_view(Project::new(a.clone(), |t| t + 1))
+            .unwrap();
+
+        assert_eq!(vec![a.reference().clone()], database.dependent_views("r"));
+        assert_eq!(
+            vec![a.reference().clone(), b.reference().clone()],
+            database.dependent_views_transitive("r")
+        );
+        assert_eq!(Vec::<ViewRef>::new(), database.dependent_views("s"));
+        assert_eq!(
+            Vec::<ViewRef>::new(),
+            database.dependent_views_transitive("s")
+        );
+        assert_eq!(
+            Vec::<ViewRef>::new(),
+            database.dependent_views_transitive("t")
+        );
+    }
+
+    #[test]
+    fn test_deterministic_side_effects() {
+        use std::sync::{Arc, Mutex};
+
+        fn run() -> Vec<usize> {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let log = Arc::new(Mutex::new(Vec::new()));
+
+            let mut views = Vec::new();
+            for i in 0..8 {
+                let log = log.clone();
+                let view = database
+                    .store_view(r.builder().project(move |t| {
+                        log.lock().unwrap().push(i);
+                        *t
+                    }))
+                    .unwrap();
+                views.push(view);
+            }
+
+            database.insert(&r, vec![42].into()).unwrap();
+            database.evaluate(&views[0]).unwrap();
+
+            let log = log.lock().unwrap().clone();
+            log
         }
+
+        // the views over `r` are stabilized in the order of their references:
+        assert_eq!((0..8).collect::<Vec<_>>(), run());
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_stabilize_diamond() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let tens = database
+            .store_view(r.builder().project(move |t| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                t * 10
+            }))
+            .unwrap();
+        let evens = database
+            .store_view(r.builder().select(|t| t % 2 == 0))
+            .unwrap();
+        let both = database
+            .store_view(
+                tens.builder()
+                    .with_key(|&t| t / 10)
+                    .join(evens.builder().with_key(|&t| t))
+                    .on(|_, &l, &r| l + r),
+            )
+            .unwrap();
+
+        let (relations, views) = database.stabilization_order(both.reference());
+        assert_eq!(vec!["r"], relations);
+        assert_eq!(
+            vec![tens.reference(), evens.reference(), both.reference()],
+            views
+        );
+
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+        assert_eq!(
+            vec![22, 44],
+            database.evaluate(&both).unwrap().into_tuples()
+        );
+        assert_eq!(4, calls.load(Ordering::Relaxed));
+
+        database.insert(&r, vec![5, 6].into()).unwrap();
+        assert_eq!(
+            vec![22, 44, 66],
+            database.evaluate(&both).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec![10, 20, 30, 40, 50, 60],
+            database.evaluate(&tens).unwrap().into_tuples()
+        );
+        assert_eq!(6, calls.load(Ordering::Relaxed));
     }
 
     #[test]
@@ -531,4 +3976,184 @@ mod tests {
 
         assert!(database.view_instance(&view).is_ok());
     }
+
+    #[test]
+    fn test_drop_view() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let tens = database
+            .store_view(r.builder().project(|t| t * 10))
+            .unwrap();
+        let big = database
+            .store_named_view("big", tens.builder().select(|&t| t > 20))
+            .unwrap();
+        let odds = database
+            .store_view(r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        assert!(matches!(
+            database.drop_view(&tens),
+            Err(Error::ViewInUse { .. })
+        ));
+
+        database.drop_view(&big).unwrap();
+        database.drop_view(&odds).unwrap();
+        assert!(database.named_view("big").is_none());
+        assert!(database.evaluate(&odds).is_err());
+        assert!(matches!(
+            database.drop_view(&odds),
+            Err(Error::InstanceNotFound { .. })
+        ));
+        assert_eq!(
+            vec![tens.reference().clone()],
+            database.dependent_views("r")
+        );
+        database.validate_invariants().unwrap();
+
+        // the smallest freed identifier is reused before a new one is minted:
+        let evens = database
+            .store_view(r.builder().select(|t| t % 2 == 0))
+            .unwrap();
+        let small = database
+            .store_view(tens.builder().select(|&t| t < 20))
+            .unwrap();
+        let all = database.store_view(r.clone()).unwrap();
+        assert_eq!(big.reference().id(), evens.reference().id());
+        assert_eq!(odds.reference().id(), small.reference().id());
+        assert_eq!(3, all.reference().id());
+
+        assert_eq!(vec![2, 4], database.evaluate(&evens).unwrap().into_tuples());
+        assert_eq!(vec![10], database.evaluate(&small).unwrap().into_tuples());
+        database.validate_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_drop_view_reset_order() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let tens = database
+            .store_view(r.builder().project(|t| t * 10))
+            .unwrap();
+        let odds = database
+            .store_view(r.builder().select(|t| t % 2 == 1))
+            .unwrap();
+        database.drop_view(&tens).unwrap();
+
+        // the view reuses the identifier of `tens` although it depends on `odds`:
+        let odd_tens = database
+            .store_view(odds.builder().project(|t| t * 10))
+            .unwrap();
+        assert!(odd_tens.reference() < odds.reference());
+
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        database.replace_relation(&r, vec![3, 4, 5].into()).unwrap();
+        assert_eq!(
+            vec![30, 50],
+            database.evaluate(&odd_tens).unwrap().into_tuples()
+        );
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_concurrent_evaluate() {
+        use std::{
+            sync::{mpsc, Arc, Mutex},
+            thread,
+            time::Duration,
+        };
+
+        fn assert_sync<T: Send + Sync>() {}
+        assert_sync::<Database>();
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let doubles = database.store_view(r.builder().project(|t| t * 2)).unwrap();
+        database
+            .insert(&r, (0..100).collect::<Vec<_>>().into())
+            .unwrap();
+        let database = Arc::new(database);
+
+        // the predicate of every evaluation signals the other evaluation and waits for its
+        // signal; therefore, the evaluations complete only if they run at the same time:
+        let (left_sender, left_receiver) = mpsc::channel();
+        let (right_sender, right_receiver) = mpsc::channel();
+        let evaluations = vec![
+            (0, left_sender, right_receiver),
+            (2, right_sender, left_receiver),
+        ]
+        .into_iter()
+        .map(|(remainder, sender, receiver)| {
+            let database = database.clone();
+            let receiver = Mutex::new(receiver);
+            let mut waiting = true;
+            let select = doubles
+                .builder()
+                .select(move |&t| {
+                    if waiting {
+                        waiting = false;
+                        sender.send(()).unwrap();
+                        receiver
+                            .lock()
+                            .unwrap()
+                            .recv_timeout(Duration::from_secs(10))
+                            .expect("the evaluations overlap");
+                    }
+                    t % 4 == remainder
+                })
+                .build();
+            thread::spawn(move || database.evaluate(&select).unwrap().into_tuples())
+        })
+        .collect::<Vec<_>>();
+
+        let results = evaluations
+            .into_iter()
+            .map(|evaluation| evaluation.join().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!((0..200).step_by(4).collect::<Vec<_>>(), results[0]);
+        assert_eq!((2..200).step_by(4).collect::<Vec<_>>(), results[1]);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_concurrent_insert() {
+        use std::{sync::Arc, thread};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let doubles = database.store_view(r.builder().project(|t| t * 2)).unwrap();
+        let database = Arc::new(database);
+
+        let writer = {
+            let database = database.clone();
+            let r = r.clone();
+            thread::spawn(move || {
+                for i in 0..100 {
+                    database.insert(&r, vec![i].into()).unwrap();
+                }
+            })
+        };
+        let reader = {
+            let database = database.clone();
+            let doubles = doubles.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while last < 100 {
+                    let tuples = database.evaluate(&doubles).unwrap();
+                    assert!(tuples
+                        .iter()
+                        .copied()
+                        .eq((0..tuples.len() as i32).map(|t| t * 2)));
+                    last = tuples.len();
+                }
+            })
+        };
+
+        writer.join().unwrap();
+        reader.join().unwrap();
+        assert_eq!(
+            (0..100).map(|t| t * 2).collect::<Vec<_>>(),
+            database.evaluate(&doubles).unwrap().into_tuples()
+        );
+    }
 }
@@ -0,0 +1,134 @@
+/*! Implements [`Arena`], a bump allocator for the scratch memory of evaluations.
+
+Evaluating a join builds a sorted index of keyed tuples for every batch of its operands;
+these indices are dropped as soon as the join has produced its tuples. When an [`Arena`]
+is supplied to [`Database::evaluate_in`], the indices are allocated in the arena instead of
+the heap, and the arena is reset after the evaluation, so that repeated evaluations reuse
+the same memory.
+
+[`Database::evaluate_in`]: super::Database::evaluate_in
+*/
+use super::Tuples;
+use crate::Tuple;
+use std::ops::Deref;
+
+/// Is a reusable memory region for the scratch allocations of evaluations.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Arena, Database, Expression};
+///
+/// let mut db = Database::new();
+/// let r = db.add_relation::<(i32, i32)>("r").unwrap();
+/// db.insert(&r, vec![(1, 10), (2, 20)].into());
+///
+/// let join = r
+///     .builder()
+///     .with_key(|t| t.0)
+///     .join(r.builder().with_key(|t| t.0))
+///     .on(|_, l, r| l.1 + r.1)
+///     .build();
+///
+/// let mut arena = Arena::new();
+/// for _ in 0..10 {
+///     assert_eq!(vec![20, 40], db.evaluate_in(&join, &mut arena).unwrap().into_tuples());
+/// }
+/// ```
+#[derive(Default)]
+pub struct Arena {
+    bump: bumpalo::Bump,
+}
+
+impl Arena {
+    /// Creates a new empty [`Arena`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes that the arena has allocated, including the memory that
+    /// is retained for reuse after the arena is reset.
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    /// Returns the underlying bump allocator.
+    #[inline(always)]
+    pub(super) fn bump(&self) -> &bumpalo::Bump {
+        &self.bump
+    }
+
+    /// Frees all allocations of the arena while retaining its largest chunk of memory.
+    pub(super) fn reset(&mut self) {
+        self.bump.reset();
+    }
+}
+
+/// Is a sorted and deduplicated sequence of tuples that is allocated either on the heap
+/// or in an [`Arena`].
+pub(super) enum Scratch<'a, T: Tuple> {
+    Heap(Tuples<T>),
+    Arena(bumpalo::collections::Vec<'a, T>),
+}
+
+impl<'a, T: Tuple> Scratch<'a, T> {
+    /// Collects the tuples of `iter` in `bump`.
+    pub fn in_arena(iter: impl Iterator<Item = T>, bump: &'a bumpalo::Bump) -> Self {
+        let mut items = bumpalo::collections::Vec::from_iter_in(iter, bump);
+        items.sort_unstable();
+        items.dedup();
+        Self::Arena(items)
+    }
+}
+
+impl<T: Tuple> Deref for Scratch<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Heap(tuples) => &tuples[..],
+            Self::Arena(items) => items,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Expression};
+
+    #[test]
+    fn test_evaluate_in() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, i32)>("r").unwrap();
+        let s = database.add_relation::<(i32, i32)>("s").unwrap();
+        database
+            .insert(&r, (0..100).map(|i| (i % 10, i)).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(
+                &s,
+                (0..10).map(|i| (i, i * 1000)).collect::<Vec<_>>().into(),
+            )
+            .unwrap();
+
+        let join = r
+            .builder()
+            .with_key(|t| t.0)
+            .join(s.builder().with_key(|t| t.0))
+            .on(|_, l, r| l.1 + r.1)
+            .build();
+        let expected = database.evaluate(&join).unwrap();
+        assert_eq!(100, expected.len());
+
+        let mut arena = Arena::new();
+        assert_eq!(expected, database.evaluate_in(&join, &mut arena).unwrap());
+        let allocated = arena.allocated_bytes();
+        assert!(allocated > 0);
+
+        for _ in 0..1000 {
+            assert_eq!(expected, database.evaluate_in(&join, &mut arena).unwrap());
+        }
+        // the memory of the arena is reused across evaluations:
+        assert_eq!(allocated, arena.allocated_bytes());
+    }
+}
@@ -0,0 +1,41 @@
+/*! Implements the audit log of the mutations of a database.
+
+When the audit log of a [`Database`] is enabled by [`Database::enable_audit_log`], every
+operation that changes the content of the database is recorded as a [`Mutation`], in the
+order that the operations are performed. The log is intended for debugging and for
+replaying a sequence of operations onto a fresh database.
+
+[`Database`]: super::Database
+[`Database::enable_audit_log`]: super::Database::enable_audit_log
+*/
+use crate::expression::ViewRef;
+
+/// Is an entry of the audit log of a database, describing an operation that changed the
+/// content of the database.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Mutation {
+    /// Is recorded when `count` tuples are inserted into `relation`.
+    Insert { relation: String, count: usize },
+
+    /// Is recorded when `count` tuples are removed from `relation`.
+    Remove { relation: String, count: usize },
+
+    /// Is recorded when the content of `relation` is replaced by `count` tuples.
+    Replace { relation: String, count: usize },
+
+    /// Is recorded when `count` tuples are loaded as the stable tuples of `relation`.
+    Load { relation: String, count: usize },
+
+    /// Is recorded when a new relation `relation` is added with `count` seed tuples.
+    Seed { relation: String, count: usize },
+
+    /// Is recorded when a new relation `relation` is added with a copy of the tuples of
+    /// `source`.
+    Clone { source: String, relation: String },
+
+    /// Is recorded when a new view identified by `view` is stored.
+    StoreView { view: ViewRef },
+
+    /// Is recorded when the view identified by `view` is dropped.
+    DropView { view: ViewRef },
+}
@@ -0,0 +1,77 @@
+/*! Implements [`DatabaseBuilder`] for setting up a [`Database`] in a single chain of calls. */
+use super::{instance::Tuples, Database};
+use crate::{Error, Tuple};
+
+/// Accumulates the relations of a new [`Database`] and their (optional) seed tuples, and
+/// builds the database by [`DatabaseBuilder::build`]. The first failure to add a relation
+/// is reported by [`DatabaseBuilder::build`].
+///
+/// **Example**:
+/// ```rust
+/// use codd::Database;
+///
+/// # fn main() -> Result<(), codd::Error> {
+/// let db = Database::builder()
+///     .relation_seeded::<i32>("numbers", vec![1, 2, 3].into())
+///     .relation::<String>("words")
+///     .build()?;
+///
+/// let numbers = db.relation::<i32>("numbers")?;
+/// let words = db.relation::<String>("words")?;
+///
+/// assert_eq!(vec![1, 2, 3], db.evaluate(&numbers)?.into_tuples());
+/// assert!(db.evaluate(&words)?.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct DatabaseBuilder {
+    /// Is the database that is being built.
+    database: Database,
+
+    /// Is the first error that occurred while building the database.
+    error: Option<Error>,
+}
+
+impl DatabaseBuilder {
+    /// Creates a new builder for an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new relation instance identified by `name` to the database like
+    /// [`Database::add_relation`].
+    pub fn relation<T>(self, name: &str) -> Self
+    where
+        T: Tuple + 'static,
+    {
+        self.apply(|database| database.add_relation::<T>(name).map(|_| ()))
+    }
+
+    /// Adds a new relation instance identified by `name` with the `seed` tuples to the
+    /// database like [`Database::add_relation_seeded`].
+    pub fn relation_seeded<T>(self, name: &str, seed: Tuples<T>) -> Self
+    where
+        T: Tuple + 'static,
+    {
+        self.apply(|database| database.add_relation_seeded(name, seed).map(|_| ()))
+    }
+
+    /// Applies `f` to the database of the receiver unless an earlier step has failed, and
+    /// keeps the error of `f` if it fails.
+    fn apply(mut self, f: impl FnOnce(&mut Database) -> Result<(), Error>) -> Self {
+        if self.error.is_none() {
+            self.error = f(&mut self.database).err();
+        }
+        self
+    }
+
+    /// Consumes the receiver and returns the database or the first error that occurred
+    /// while adding its relations.
+    pub fn build(self) -> Result<Database, Error> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.database),
+        }
+    }
+}
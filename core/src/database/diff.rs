@@ -0,0 +1,162 @@
+/*! Implements the comparison of the relations of two databases.
+
+[`diff_databases`] compares the relations that two databases share by name and reports the
+tuples that one has and the other does not. It is intended for verifying that a replayed
+or restored database matches the original.
+*/
+use super::{instance::Tuples, Database};
+use crate::{expression::Relation, Error, Tuple};
+use std::{
+    any::Any,
+    collections::{BTreeMap, BTreeSet},
+};
+
+/// Contains the tuples of a relation that differ between two databases, as computed by
+/// [`diff_databases`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelationDiff<T: Tuple> {
+    /// Is the tuples that exist in the second database but not in the first.
+    pub added: Tuples<T>,
+
+    /// Is the tuples that exist in the first database but not in the second.
+    pub removed: Tuples<T>,
+}
+
+/// Is the difference between the relations of two databases, as computed by
+/// [`diff_databases`]. The tuples of a changed relation are probed by
+/// [`DatabaseDiff::relation`] with a [`Relation`] of the expected type.
+#[derive(Default)]
+pub struct DatabaseDiff {
+    /// Maps the names of the changed relations to their [`RelationDiff`]s (in a box).
+    relations: BTreeMap<String, Box<dyn Any>>,
+
+    /// Is the names of the shared relations whose tuples have different types.
+    mismatched: BTreeSet<String>,
+}
+
+impl DatabaseDiff {
+    /// Returns true if the shared relations of the two databases have the same tuples.
+    pub fn is_empty(&self) -> bool {
+        self.relations.is_empty() && self.mismatched.is_empty()
+    }
+
+    /// Returns the names of the shared relations whose tuples differ in the two databases,
+    /// sorted by name.
+    pub fn changed_relations(&self) -> impl Iterator<Item = &str> {
+        self.relations.keys().map(String::as_str)
+    }
+
+    /// Returns the names of the shared relations whose tuples are of different types in
+    /// the two databases, sorted by name.
+    pub fn mismatched_relations(&self) -> impl Iterator<Item = &str> {
+        self.mismatched.iter().map(String::as_str)
+    }
+
+    /// Returns the difference between the tuples of `relation` in the two databases, or
+    /// `None` if the relation is unchanged, is not shared by the two databases, or its
+    /// tuples are not of type `T`.
+    pub fn relation<T>(&self, relation: &Relation<T>) -> Option<&RelationDiff<T>>
+    where
+        T: Tuple + 'static,
+    {
+        self.relations
+            .get(relation.name())
+            .and_then(|diff| diff.downcast_ref::<RelationDiff<T>>())
+    }
+}
+
+/// Compares the relations that `a` and `b` share by name and returns the tuples that are
+/// added to and removed from every relation in `b` relative to `a`. Relations that exist
+/// in only one of the databases and views are not compared.
+///
+/// **Note**: the comparison includes the tuples that are inserted but not yet evaluated;
+/// it does not modify the databases.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{diff_databases, Database};
+///
+/// let mut original = Database::new();
+/// let r = original.add_relation::<i32>("r").unwrap();
+/// original.insert(&r, vec![1, 2, 3].into()).unwrap();
+///
+/// let restored = original.clone();
+/// assert!(diff_databases(&original, &restored).unwrap().is_empty());
+///
+/// restored.insert(&r, vec![4].into()).unwrap();
+/// let diff = diff_databases(&original, &restored).unwrap();
+/// assert_eq!(vec![4], diff.relation(&r).unwrap().added.to_vec());
+/// ```
+pub fn diff_databases(a: &Database, b: &Database) -> Result<DatabaseDiff, Error> {
+    // enter the gates of the databases in the order of their addresses to avoid deadlocks:
+    let (first, second) = if (a as *const Database) <= b {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let _first = first.gate.read();
+    let _second = (!std::ptr::eq(a, b)).then(|| second.gate.read());
+    let mut result = DatabaseDiff::default();
+    for (name, left) in a.relations.iter() {
+        let right = match b.relations.get(name) {
+            Some(right) => right,
+            None => continue,
+        };
+
+        if Any::type_id(left.instance.as_any()) != Any::type_id(right.instance.as_any()) {
+            result.mismatched.insert(name.clone());
+        } else if let Some(diff) = left.instance.diff(right.instance.as_ref())? {
+            result.relations.insert(name.clone(), diff);
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_databases() {
+        let mut a = Database::new();
+        let r = a.add_relation::<i32>("r").unwrap();
+        let s = a.add_relation::<(i32, String)>("s").unwrap();
+        a.insert(&r, vec![1, 2, 3].into()).unwrap();
+        a.insert(&s, vec![(1, "a".to_string())].into()).unwrap();
+        a.evaluate(&r).unwrap();
+
+        let mut b = a.clone();
+        assert!(diff_databases(&a, &b).unwrap().is_empty());
+
+        b.remove_where(&r, |&t| t == 2).unwrap();
+        b.insert(&r, vec![4, 5].into()).unwrap();
+        let t = b.add_relation::<i32>("t").unwrap();
+        b.insert(&t, vec![1].into()).unwrap();
+        a.add_relation::<String>("u").unwrap();
+        b.add_relation::<i32>("u").unwrap();
+
+        let diff = diff_databases(&a, &b).unwrap();
+        assert!(!diff.is_empty());
+        assert_eq!(vec!["r"], diff.changed_relations().collect::<Vec<_>>());
+        assert_eq!(vec!["u"], diff.mismatched_relations().collect::<Vec<_>>());
+        assert_eq!(
+            Some(&RelationDiff {
+                added: vec![4, 5].into(),
+                removed: vec![2].into(),
+            }),
+            diff.relation(&r)
+        );
+        assert_eq!(None, diff.relation(&s));
+        assert_eq!(None, diff.relation(&t));
+        assert_eq!(None, diff.relation(&Relation::<String>::new("r")));
+
+        let diff = diff_databases(&b, &a).unwrap();
+        assert_eq!(
+            Some(&RelationDiff {
+                added: vec![2].into(),
+                removed: vec![4, 5].into(),
+            }),
+            diff.relation(&r)
+        );
+    }
+}
@@ -1,10 +1,121 @@
+#[cfg(feature = "arena")]
+use super::arena::{Arena, Scratch};
 /// Implements an incremental algorithm for evaluating an expression in a database.
 use super::{
     expression_ext::{ExpressionExt, RecentCollector, StableCollector},
-    helpers::{diff_helper, intersect_helper, join_helper, product_helper, project_helper},
+    helpers::{
+        diff_helper, intersect_helper, join_helper, outer_join_helper, product_helper,
+        project_helper, range_helper, semi_join_helper,
+    },
+    instance::{BatchesRef, TuplesRef},
     Database, Tuples,
 };
-use crate::{expression::*, Error, Tuple};
+use crate::{expression::*, shared::Reader, Error, Tuple};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Is the type of the sorted indices of keyed tuples that are built for evaluating joins.
+#[cfg(feature = "arena")]
+type Keyed<'a, K, T> = Scratch<'a, (K, T)>;
+
+/// Is the type of the sorted indices of keyed tuples that are built for evaluating joins.
+#[cfg(not(feature = "arena"))]
+type Keyed<'a, K, T> = Tuples<(K, T)>;
+
+/// Is the cache of the tuples of the shared sub-expressions of an evaluation, keyed by the
+/// identifiers of the sub-expressions and whether their stable (or recent) tuples are cached.
+type Cache = RefCell<HashMap<(usize, bool), Box<dyn Any>>>;
+
+/// Is the number of tuples that are produced between two progress reports of a [`Monitor`].
+pub(super) const PROGRESS_STEP: usize = 1000;
+
+/// Observes the tuples that are produced by the operators of an expression while the
+/// expression is being evaluated.
+pub(super) struct Monitor<'m> {
+    /// Is the number of tuples that have been produced so far.
+    produced: Cell<usize>,
+
+    /// Is the number of produced tuples when progress was last reported.
+    reported: Cell<usize>,
+
+    /// Is the callback for reporting the number of produced tuples.
+    progress: RefCell<&'m mut dyn FnMut(usize)>,
+
+    /// Is the number of tuples that are currently held by the operators of the expression.
+    live: Cell<usize>,
+
+    /// Is the maximum of `live` since the monitor was created.
+    peak: Cell<usize>,
+}
+
+impl<'m> Monitor<'m> {
+    /// Creates a new monitor that reports progress to `progress` every [`PROGRESS_STEP`]
+    /// produced tuples.
+    pub fn new(progress: &'m mut dyn FnMut(usize)) -> Self {
+        Self {
+            produced: Cell::new(0),
+            reported: Cell::new(0),
+            progress: RefCell::new(progress),
+            live: Cell::new(0),
+            peak: Cell::new(0),
+        }
+    }
+
+    /// Returns the maximum number of tuples that have been held by the operators of the
+    /// expression at the same time.
+    pub fn peak(&self) -> usize {
+        self.peak.get()
+    }
+
+    /// Records `count` newly produced tuples.
+    fn produced(&self, count: usize) {
+        self.held(count);
+
+        let produced = self.produced.get() + count;
+        self.produced.set(produced);
+        if produced - self.reported.get() >= PROGRESS_STEP {
+            self.reported.set(produced);
+            (self.progress.borrow_mut())(produced);
+        }
+    }
+
+    /// Records `count` tuples that are held by an operator, e.g., copies of the tuples of
+    /// an instance.
+    fn held(&self, count: usize) {
+        let live = self.live.get() + count;
+        self.live.set(live);
+        self.peak.set(self.peak.get().max(live));
+    }
+
+    /// Records `count` tuples that are no longer held by the operators.
+    fn released(&self, count: usize) {
+        self.live.set(self.live.get().saturating_sub(count));
+    }
+}
+
+/// Returns the total number of tuples in `batches`.
+#[inline(always)]
+fn batches_len<T: Tuple>(batches: &[Tuples<T>]) -> usize {
+    batches.iter().map(|batch| batch.len()).sum()
+}
+
+/// Returns the value that `params` binds to `param`.
+fn bound<T>(params: Option<&Params>, param: &Param<T>) -> Result<T, Error>
+where
+    T: Tuple + 'static,
+{
+    params
+        .and_then(|params| params.get(param))
+        .cloned()
+        .ok_or_else(|| Error::UnboundParam {
+            name: param.name().to_string(),
+        })
+}
 
 /// Implements [`RecentCollector`] and [`StableCollector`] to incrementally
 /// collect recent and stable tuples of instances of a database for expressions.
@@ -12,12 +123,270 @@ use crate::{expression::*, Error, Tuple};
 pub(super) struct IncrementalCollector<'d> {
     /// Is the database in which the visited expression is evaluated.
     database: &'d Database,
+
+    /// Is the (optional) monitor that observes the tuples produced by the collector.
+    monitor: Option<&'d Monitor<'d>>,
+
+    /// Is the (optional) number of tuples that a selection, a product or a join at the root
+    /// of the visited expression may still produce before the collector stops.
+    take: Option<&'d Cell<usize>>,
+
+    /// Is the (optional) bindings of the parameters of the visited expression.
+    params: Option<&'d Params>,
+
+    /// Is the (optional) cache of the tuples of the shared sub-expressions of the visited
+    /// expression.
+    cache: Option<Rc<Cache>>,
+
+    /// Is the (optional) flag that cancels the collector when it is set.
+    cancel: Option<&'d AtomicBool>,
+
+    /// Is the (optional) context that is read by the contextual closures of the visited
+    /// expression.
+    context: Option<&'d dyn Any>,
+
+    /// Is the (optional) arena in which the collector allocates its scratch memory.
+    #[cfg(feature = "arena")]
+    arena: Option<&'d Arena>,
 }
 
 impl<'d> IncrementalCollector<'d> {
     /// Creates a new collector for incremental evaluation.
     pub fn new(database: &'d Database) -> Self {
-        Self { database }
+        Self {
+            database,
+            monitor: None,
+            take: None,
+            params: None,
+            cache: None,
+            cancel: None,
+            context: None,
+            #[cfg(feature = "arena")]
+            arena: None,
+        }
+    }
+
+    /// Returns a new collector over the database of the receiver, sharing its monitor but
+    /// not its limit: the sub-expressions of an operator are always collected in full.
+    fn incremental(&self) -> Self {
+        Self {
+            take: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the tuples that `collect` returns for the sub-expression identified by `key`.
+    /// If the receiver has a cache, the tuples are collected once per `key` and reused by
+    /// the later calls for the same `key`. The copy of the cached tuples that a later call
+    /// returns is reported as held, with its size computed by `len`, like the tuples that
+    /// `collect` produces.
+    fn cached<V, F>(
+        &self,
+        key: Option<(usize, bool)>,
+        len: impl FnOnce(&V) -> usize,
+        collect: F,
+    ) -> Result<V, Error>
+    where
+        V: Clone + 'static,
+        F: FnOnce() -> Result<V, Error>,
+    {
+        let (cache, key) = match (&self.cache, key) {
+            (Some(cache), Some(key)) => (cache, key),
+            _ => return collect(),
+        };
+        let hit = cache
+            .borrow()
+            .get(&key)
+            .and_then(|value| value.downcast_ref::<V>())
+            .cloned();
+        if let Some(value) = hit {
+            self.held(len(&value));
+            return Ok(value);
+        }
+        let value = collect()?;
+        cache.borrow_mut().insert(key, Box::new(value.clone()));
+        Ok(value)
+    }
+
+    /// Reports `count` produced tuples to the monitor of the receiver.
+    #[inline(always)]
+    fn produced(&self, count: usize) {
+        if let Some(monitor) = self.monitor {
+            monitor.produced(count);
+        }
+    }
+
+    /// Reports `count` tuples that are held by the collector to the monitor of the receiver.
+    #[inline(always)]
+    fn held(&self, count: usize) {
+        if let Some(monitor) = self.monitor {
+            monitor.held(count);
+        }
+    }
+
+    /// Reports `count` tuples that are released by the collector to the monitor of the
+    /// receiver.
+    #[inline(always)]
+    fn released(&self, count: usize) {
+        if let Some(monitor) = self.monitor {
+            monitor.released(count);
+        }
+    }
+
+    /// Returns the tuples of `batch` paired with their keys by `key` in a sorted index. The
+    /// index is allocated in the arena of the receiver if it has one.
+    fn keyed<'a, K, T>(
+        &'a self,
+        batch: &'a [T],
+        key: &mut dyn FnMut(&T) -> K,
+    ) -> Keyed<'a, K, &'a T>
+    where
+        K: Tuple,
+        T: Tuple,
+    {
+        let keyed = batch.iter().map(|t| (key(t), t));
+        #[cfg(feature = "arena")]
+        return match self.arena {
+            Some(arena) => Scratch::in_arena(keyed, arena.bump()),
+            None => Scratch::Heap(keyed.into()),
+        };
+        #[cfg(not(feature = "arena"))]
+        keyed.into()
+    }
+
+    /// Merges the `recent` and `stable` tuples of an expression into its result and reports
+    /// the merged tuples to the monitor of the receiver.
+    fn merge<T: Tuple>(&self, recent: Tuples<T>, stable: Vec<Tuples<T>>) -> Tuples<T> {
+        self.released(recent.len() + batches_len(&stable));
+        let result = Tuples::merge_all(std::iter::once(recent).chain(stable));
+        self.held(result.len());
+        result
+    }
+
+    /// Appends `tuples` to `result` and reports them to the monitor of the receiver.
+    #[inline(always)]
+    fn extend<T>(&self, result: &mut Vec<T>, tuples: Vec<T>) {
+        self.produced(tuples.len());
+        result.extend(tuples);
+    }
+
+    /// Pushes `tuple` (if any) to `result` and reports it to the monitor of the receiver.
+    #[inline(always)]
+    fn emit<T>(&self, result: &mut Vec<T>, tuple: Option<T>) {
+        if let Some(tuple) = tuple {
+            result.push(tuple);
+            self.produced(1);
+        }
+    }
+
+    /// Returns `true` if the cancellation flag of the receiver is set.
+    #[inline(always)]
+    fn cancelled(&self) -> bool {
+        self.cancel
+            .map_or(false, |cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Returns [`Error::Cancelled`] if the cancellation flag of the receiver is set.
+    #[inline(always)]
+    fn check_cancelled(&self) -> Result<(), Error> {
+        if self.cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the context of the receiver or [`Error::MissingContext`] if it has none.
+    #[inline(always)]
+    fn context(&self) -> Result<&'d dyn Any, Error> {
+        self.context.ok_or(Error::MissingContext)
+    }
+
+    /// Returns `true` if the receiver has produced as many tuples as its limit allows.
+    #[inline(always)]
+    fn exhausted(&self) -> bool {
+        self.take.map_or(false, |take| take.get() == 0)
+    }
+
+    /// Emits the tuple (if any) that `tuple` produces to `result` like [`emit`] while
+    /// counting it against the limit of the receiver. `tuple` is not called if the limit is
+    /// already reached or the collector is cancelled. Returns `false` when the limit is
+    /// reached or the collector is cancelled and the collector must stop producing tuples.
+    ///
+    /// [`emit`]: IncrementalCollector::emit
+    #[inline(always)]
+    fn emit_limited<T>(&self, result: &mut Vec<T>, tuple: impl FnOnce() -> Option<T>) -> bool {
+        if self.cancelled() || self.exhausted() {
+            return false;
+        }
+        let tuple = tuple();
+        match self.take {
+            None => {
+                self.emit(result, tuple);
+                true
+            }
+            Some(take) => {
+                if tuple.is_some() {
+                    take.set(take.get() - 1);
+                    self.emit(result, tuple);
+                }
+                take.get() > 0
+            }
+        }
+    }
+}
+
+impl<'d> IncrementalCollector<'d> {
+    /// Collects the rows of an anti-join or a left outer join in a single batch. Because
+    /// the rows of such joins may be retracted when new right tuples arrive, every left
+    /// tuple is checked against every right tuple, recent or stable, in a single pass over
+    /// the sorted indices of the sub-expressions.
+    fn collect_outer_join<K, L, R, Left, Right, T>(
+        &self,
+        join: &Join<K, L, R, Left, Right, T>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        K: Tuple,
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: ExpressionExt<L>,
+        Right: ExpressionExt<R>,
+    {
+        let mut result = Vec::new();
+        let incremental = self.incremental();
+        let mut left_key = join.left_key_mut();
+        let mut right_key = join.right_key_mut();
+
+        let left = self.merge(
+            join.left().collect_recent(&incremental)?,
+            join.left().collect_stable(&incremental)?,
+        );
+        let left_keyed = self.keyed(&left, &mut *left_key);
+        let right = self.merge(
+            join.right().collect_recent(&incremental)?,
+            join.right().collect_stable(&incremental)?,
+        );
+        let right_keyed = self.keyed(&right, &mut *right_key);
+
+        let mut joiner = join.mapper_mut();
+        let mut mapper = join.left_mapper_mut();
+        if join.mode() == JoinMode::Anti {
+            semi_join_helper(&left_keyed, &[&right_keyed[..]], |k, v, found| {
+                found || self.emit_limited(&mut result, || mapper(k, v))
+            });
+        } else {
+            outer_join_helper(&left_keyed, &right_keyed, |k, v1, v2| {
+                self.emit_limited(&mut result, || match v2 {
+                    Some(v2) => joiner(k, v1, v2),
+                    None => mapper(k, v1),
+                })
+            });
+        }
+
+        self.released(left.len() + right.len());
+        self.check_cancelled()?;
+        Ok(vec![result.into()])
     }
 }
 
@@ -47,28 +416,72 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         Ok(Vec::new().into())
     }
 
+    fn collect_param<T>(&self, _: &Param<T>) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        Ok(Vec::new().into())
+    }
+
     fn collect_relation<T>(&self, relation: &Relation<T>) -> Result<Tuples<T>, Error>
     where
         T: Tuple + 'static,
     {
         let table = self.database.relation_instance(relation)?;
-        Ok(table.recent().clone())
+        let recent = table.recent().clone();
+        self.held(recent.len());
+        Ok(recent)
+    }
+
+    fn collect_relation_ref<T>(&self, relation: &Relation<T>) -> Result<TuplesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let table = self.database.relation_instance(relation)?;
+        Ok(TuplesRef::Borrowed(table.recent()))
     }
 
     fn collect_select<T, E>(&self, select: &Select<T, E>) -> Result<Tuples<T>, Error>
     where
-        T: Tuple,
+        T: Tuple + 'static,
         E: ExpressionExt<T>,
     {
-        let mut result = Vec::new();
-        let recent = select.expression().collect_recent(self)?;
-        let mut predicate = select.predicate_mut();
-        for tuple in &recent[..] {
-            if predicate(tuple) {
-                result.push(tuple.clone());
-            }
-        }
-        Ok(result.into())
+        // a limited selection is not cached since it may stop before selecting every tuple:
+        let key = select.shared_id().filter(|_| self.take.is_none());
+        self.cached(
+            key.map(|id| (id, false)),
+            |tuples: &Tuples<T>| tuples.len(),
+            || {
+                let mut result = Vec::new();
+                let incremental = self.incremental();
+                let recent = select.expression().collect_recent_ref(&incremental)?;
+                if let Some(mut range) = select.range_mut() {
+                    for tuple in range_helper(&recent, &mut *range) {
+                        if !self.emit_limited(&mut result, || Some(tuple.clone())) {
+                            break;
+                        }
+                    }
+                    self.released(recent.owned_len());
+                    self.check_cancelled()?;
+                    return Ok(result.into());
+                }
+
+                let mut predicate = select.predicate_mut();
+                let mut contextual = select.contextual_mut();
+                let mut keep = |t: &T| match contextual.as_mut() {
+                    Some(contextual) => contextual(self.context()?, t).ok_or(Error::MissingContext),
+                    None => Ok(predicate(t)),
+                };
+                for tuple in &recent[..] {
+                    if keep(tuple)? && !self.emit_limited(&mut result, || Some(tuple.clone())) {
+                        break;
+                    }
+                }
+                self.released(recent.owned_len());
+                self.check_cancelled()?;
+                Ok(result.into())
+            },
+        )
     }
 
     fn collect_union<T, L, R>(&self, union: &Union<T, L, R>) -> Result<Tuples<T>, Error>
@@ -89,6 +502,8 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
             result.push(tuple.clone());
         }
 
+        self.produced(result.len());
+        self.released(left_recent.len() + right_recent.len());
         Ok(result.into())
     }
 
@@ -99,7 +514,7 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         R: ExpressionExt<T>,
     {
         let mut result = Vec::new();
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
         let left_recent = intersect.left().collect_recent(self)?;
         let right_recent = intersect.right().collect_recent(self)?;
@@ -108,13 +523,20 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         let right_stable = intersect.right().collect_stable(&incremental)?;
 
         for batch in left_stable.iter() {
-            intersect_helper(&batch, &right_recent, |t| result.push(t.clone()))
+            intersect_helper(batch, &right_recent, |t| result.push(t.clone()))
         }
         for batch in right_stable.iter() {
-            intersect_helper(&left_recent, &batch, |t| result.push(t.clone()))
+            intersect_helper(&left_recent, batch, |t| result.push(t.clone()))
         }
 
         intersect_helper(&left_recent, &right_recent, |t| result.push(t.clone()));
+        self.produced(result.len());
+        self.released(
+            left_recent.len()
+                + right_recent.len()
+                + batches_len(&left_stable)
+                + batches_len(&right_stable),
+        );
         Ok(result.into())
     }
 
@@ -128,7 +550,7 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         R: ExpressionExt<T>,
     {
         let mut result = Vec::new();
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
         let left_recent = difference.left().collect_recent(self)?;
         let left_stable = difference.left().collect_stable(&incremental)?;
@@ -136,12 +558,14 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         let right_stable_slices = right_stable.iter().map(|t| &t[..]).collect::<Vec<_>>();
 
         for batch in left_stable.iter() {
-            diff_helper(&batch, &right_stable_slices, |t| result.push(t.clone()));
+            diff_helper(batch, &right_stable_slices, |t| result.push(t.clone()));
         }
 
         diff_helper(&left_recent, &right_stable_slices, |t| {
             result.push(t.clone())
         });
+        self.produced(result.len());
+        self.released(left_recent.len() + batches_len(&left_stable) + batches_len(&right_stable));
         Ok(result.into())
     }
 
@@ -152,10 +576,76 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         E: ExpressionExt<S>,
     {
         let mut result = Vec::new();
-        let recent = project.expression().collect_recent(self)?;
+        let recent = project.expression().collect_recent_ref(self)?;
         let mut mapper = project.mapper_mut();
 
-        project_helper(&recent, |t| result.push(mapper(t)));
+        if let Some(mut contextual) = project.contextual_mut() {
+            let context = self.context()?;
+            for t in &recent[..] {
+                result.push(contextual(context, t).ok_or(Error::MissingContext)?);
+            }
+        } else {
+            project_helper(&recent, |t| result.push(mapper(t)));
+        }
+        self.produced(result.len());
+        self.released(recent.owned_len());
+        Ok(result.into())
+    }
+
+    fn collect_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let mut result = Vec::new();
+        let recent = filter_map.expression().collect_recent_ref(self)?;
+        let mut mapper = filter_map.mapper_mut();
+
+        project_helper(&recent, |t| self.emit(&mut result, mapper(t)));
+        self.released(recent.owned_len());
+        Ok(result.into())
+    }
+
+    fn collect_flat_project<S, T, E>(
+        &self,
+        flat_project: &FlatProject<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let mut result = Vec::new();
+        let recent = flat_project.expression().collect_recent_ref(self)?;
+        let mut mapper = flat_project.mapper_mut();
+
+        project_helper(&recent, |t| self.extend(&mut result, mapper(t)));
+        self.released(recent.owned_len());
+        Ok(result.into())
+    }
+
+    fn collect_try_project<S, T, E>(
+        &self,
+        try_project: &TryProject<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let recent = try_project.expression().collect_recent_ref(self)?;
+        let mut mapper = try_project.mapper_mut();
+
+        let result = recent
+            .iter()
+            .map(|t| mapper(t).map_err(|message| Error::ProjectionFailed { message }))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.produced(result.len());
+        self.released(recent.owned_len());
         Ok(result.into())
     }
 
@@ -171,27 +661,44 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         Right: ExpressionExt<R>,
     {
         let mut result = Vec::new();
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let left_recent = product.left().collect_recent(self)?;
-        let right_recent = product.right().collect_recent(self)?;
+        let left_recent = product.left().collect_recent(&incremental)?;
+        let right_recent = product.right().collect_recent(&incremental)?;
 
         let left_stable = product.left().collect_stable(&incremental)?;
         let right_stable = product.right().collect_stable(&incremental)?;
 
         let mut mapper = product.mapper_mut();
+        let mut filter = product.filter_mut();
+        let mut mapper = |l: &L, r: &R| match filter.as_mut() {
+            Some(filter) => filter(l, r),
+            None => Some(mapper(l, r)),
+        };
 
         for batch in left_stable.iter() {
-            product_helper(&batch, &right_recent, |v1, v2| result.push(mapper(v1, v2)));
+            product_helper(batch, &right_recent, |v1, v2| {
+                self.emit_limited(&mut result, || mapper(v1, v2))
+            });
         }
         for batch in right_stable.iter() {
-            product_helper(&left_recent, &batch, |v1, v2| result.push(mapper(v1, v2)));
+            product_helper(&left_recent, batch, |v1, v2| {
+                self.emit_limited(&mut result, || mapper(v1, v2))
+            });
+        }
+        if !self.exhausted() {
+            product_helper(&left_recent, &right_recent, |v1, v2| {
+                self.emit_limited(&mut result, || mapper(v1, v2))
+            });
         }
 
-        product_helper(&left_recent, &right_recent, |v1, v2| {
-            result.push(mapper(v1, v2))
-        });
-
+        self.released(
+            left_recent.len()
+                + right_recent.len()
+                + batches_len(&left_stable)
+                + batches_len(&right_stable),
+        );
+        self.check_cancelled()?;
         Ok(result.into())
     }
 
@@ -207,55 +714,131 @@ impl<'d> RecentCollector for IncrementalCollector<'d> {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>,
     {
+        if !join.mode().is_monotone() {
+            // the rows of a non-monotone join are materialized in full as stable tuples
+            // (see `collect_stable`):
+            return Ok(Vec::new().into());
+        }
+
         let mut result = Vec::new();
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
         let mut left_key = join.left_key_mut();
         let mut right_key = join.right_key_mut();
 
-        let left_recent = join.left().collect_recent(self)?;
-        let left_recent: Tuples<(K, &L)> = left_recent.iter().map(|t| (left_key(&t), t)).into();
-        let right_recent = join.right().collect_recent(self)?;
-        let right_recent: Tuples<(K, &R)> = right_recent.iter().map(|t| (right_key(&t), t)).into();
+        let left_recent = join.left().collect_recent(&incremental)?;
+        let left_recent = self.keyed(&left_recent, &mut *left_key);
+        let right_recent = join.right().collect_recent(&incremental)?;
+        let right_recent = self.keyed(&right_recent, &mut *right_key);
 
         let left_stable = join.left().collect_stable(&incremental)?;
-        let left_stable: Vec<Tuples<(K, &L)>> = left_stable
+        let left_stable: Vec<_> = left_stable
             .iter()
-            .map(|batch| batch.iter().map(|t| (left_key(&t), t)).into())
+            .map(|batch| self.keyed(batch, &mut *left_key))
             .collect();
 
         let right_stable = join.right().collect_stable(&incremental)?;
-        let right_stable: Vec<Tuples<(K, &R)>> = right_stable
+        let right_stable: Vec<_> = right_stable
             .iter()
-            .map(|batch| batch.iter().map(|t| (right_key(&t), t)).into())
+            .map(|batch| self.keyed(batch, &mut *right_key))
             .collect();
 
-        let mut joiner = join.mapper_mut();
-
-        for batch in left_stable.iter() {
-            join_helper(&batch, &right_recent, |k, v1, v2| {
-                result.push(joiner(k, v1, v2))
-            });
-        }
-        for batch in right_stable.iter() {
-            join_helper(&left_recent, &batch, |k, v1, v2| {
-                result.push(joiner(k, v1, v2))
+        if join.mode() == JoinMode::Semi {
+            let mut mapper = join.left_mapper_mut();
+            let right = right_stable
+                .iter()
+                .map(|batch| &batch[..])
+                .chain(std::iter::once(&right_recent[..]))
+                .collect::<Vec<_>>();
+
+            // a left tuple is new to a semi-join if it is recent or if it joins with a recent
+            // right tuple:
+            semi_join_helper(&left_recent, &right, |k, v, found| {
+                !found || self.emit_limited(&mut result, || mapper(k, v))
             });
+            for batch in left_stable.iter() {
+                semi_join_helper(batch, &[&right_recent[..]], |k, v, found| {
+                    !found || self.emit_limited(&mut result, || mapper(k, v))
+                });
+            }
+        } else {
+            let mut joiner = join.mapper_mut();
+
+            for batch in left_stable.iter() {
+                join_helper(batch, &right_recent, |k, v1, v2| {
+                    self.emit_limited(&mut result, || joiner(k, v1, v2))
+                });
+            }
+            for batch in right_stable.iter() {
+                join_helper(&left_recent, batch, |k, v1, v2| {
+                    self.emit_limited(&mut result, || joiner(k, v1, v2))
+                });
+            }
+            if !self.exhausted() {
+                join_helper(&left_recent, &right_recent, |k, v1, v2| {
+                    self.emit_limited(&mut result, || joiner(k, v1, v2))
+                });
+            }
         }
-        join_helper(&left_recent, &right_recent, |k, v1, v2| {
-            result.push(joiner(k, v1, v2))
-        });
 
+        self.released(
+            left_recent.len()
+                + right_recent.len()
+                + left_stable.iter().map(|batch| batch.len()).sum::<usize>()
+                + right_stable.iter().map(|batch| batch.len()).sum::<usize>(),
+        );
+        self.check_cancelled()?;
         Ok(result.into())
     }
 
+    fn collect_enumerate<T, E>(&self, _: &Enumerate<T, E>) -> Result<Tuples<(usize, T)>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        // `Enumerate` is materialized in full as stable tuples (see `collect_stable`).
+        Ok(Vec::new().into())
+    }
+
+    fn collect_group_by<S, K, A, E>(&self, _: &GroupBy<S, K, A, E>) -> Result<Tuples<(K, A)>, Error>
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: ExpressionExt<S>,
+    {
+        // `GroupBy` is materialized in full as stable tuples (see `collect_stable`).
+        Ok(Vec::new().into())
+    }
+
+    fn collect_scan<T, A, E>(&self, _: &Scan<T, A, E>) -> Result<Tuples<(T, A)>, Error>
+    where
+        T: Tuple,
+        A: Tuple,
+        E: ExpressionExt<T>,
+    {
+        // `Scan` is materialized in full as stable tuples (see `collect_stable`).
+        Ok(Vec::new().into())
+    }
+
     fn collect_view<T, E>(&self, view: &View<T, E>) -> Result<Tuples<T>, Error>
     where
         T: Tuple + 'static,
         E: ExpressionExt<T> + 'static,
     {
         let table = self.database.view_instance(view)?;
-        Ok(table.recent().clone())
+        let recent = table.recent().clone();
+        self.held(recent.len());
+        Ok(recent)
+    }
+
+    fn collect_view_ref<T, E>(&self, view: &View<T, E>) -> Result<TuplesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+    {
+        let table = self.database.view_instance(view)?;
+        Ok(TuplesRef::Borrowed(table.recent()))
     }
 }
 
@@ -282,39 +865,110 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
     where
         T: Tuple,
     {
+        self.held(1);
         Ok(vec![vec![singleton.tuple().clone()].into()])
     }
 
+    fn collect_param<T>(&self, param: &Param<T>) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let tuple = bound(self.params, param)?;
+        self.held(1);
+        Ok(vec![vec![tuple].into()])
+    }
+
     fn collect_relation<T>(&self, relation: &Relation<T>) -> Result<Vec<Tuples<T>>, Error>
     where
         T: Tuple + 'static,
     {
-        let mut result = Vec::<Tuples<T>>::new();
-        let table = self.database.relation_instance(&relation)?;
-        for batch in table.stable().iter() {
-            result.push(batch.clone());
-        }
-        Ok(result)
+        let table = self.database.relation_instance(relation)?;
+        let stable = table.stable_batches()?;
+        self.held(batches_len(&stable));
+        Ok(stable)
+    }
+
+    fn collect_relation_ref<T>(&self, relation: &Relation<T>) -> Result<BatchesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        let stable = self.database.relation_instance(relation)?.stable_ref()?;
+        self.held(stable.owned_len());
+        Ok(stable)
     }
 
     fn collect_select<T, E>(&self, select: &Select<T, E>) -> Result<Vec<Tuples<T>>, Error>
     where
-        T: Tuple,
+        T: Tuple + 'static,
         E: ExpressionExt<T>,
     {
-        let mut result = Vec::<Tuples<T>>::new();
-        let stable = select.expression().collect_stable(self)?;
+        let key = select.shared_id().filter(|_| self.take.is_none());
+        self.cached(
+            key.map(|id| (id, true)),
+            |batches: &Vec<_>| batches_len(batches),
+            || {
+                let mut result = Vec::new();
+                self.stream_select(select, &mut |batch| {
+                    result.push(batch);
+                    true
+                })?;
+                Ok(result)
+            },
+        )
+    }
+
+    fn stream_select<T, E>(
+        &self,
+        select: &Select<T, E>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T>,
+    {
+        let incremental = self.incremental();
+        let stable = select.expression().collect_stable_ref(&incremental)?;
+        if let Some(mut range) = select.range_mut() {
+            for batch in stable.iter() {
+                if self.exhausted() {
+                    break;
+                }
+                let mut tuples = Vec::new();
+                for tuple in range_helper(batch, &mut *range) {
+                    if !self.emit_limited(&mut tuples, || Some(tuple.clone())) {
+                        break;
+                    }
+                }
+                if !sink(tuples.into()) {
+                    break;
+                }
+            }
+            self.released(stable.owned_len());
+            return self.check_cancelled();
+        }
+
         let mut predicate = select.predicate_mut();
+        let mut contextual = select.contextual_mut();
+        let mut keep = |t: &T| match contextual.as_mut() {
+            Some(contextual) => contextual(self.context()?, t).ok_or(Error::MissingContext),
+            None => Ok(predicate(t)),
+        };
         for batch in stable.iter() {
+            if self.exhausted() {
+                break;
+            }
             let mut tuples = Vec::new();
             for tuple in &batch[..] {
-                if predicate(tuple) {
-                    tuples.push(tuple.clone());
+                if keep(tuple)? && !self.emit_limited(&mut tuples, || Some(tuple.clone())) {
+                    break;
                 }
             }
-            result.push(tuples.into());
+            if !sink(tuples.into()) {
+                break;
+            }
         }
-        Ok(result)
+        self.released(stable.owned_len());
+        self.check_cancelled()
     }
 
     fn collect_union<T, L, R>(&self, union: &Union<T, L, R>) -> Result<Vec<Tuples<T>>, Error>
@@ -323,22 +977,38 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
         L: ExpressionExt<T>,
         R: ExpressionExt<T>,
     {
-        let mut result = Vec::<Tuples<T>>::new();
+        let mut result = Vec::new();
+        self.stream_union(union, &mut |batch| {
+            result.push(batch);
+            true
+        })?;
+        Ok(result)
+    }
+
+    fn stream_union<T, L, R>(
+        &self,
+        union: &Union<T, L, R>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        L: ExpressionExt<T>,
+        R: ExpressionExt<T>,
+    {
         let left_stable = union.left().collect_stable(self)?;
         let right_stable = union.right().collect_stable(self)?;
 
-        for batch in left_stable.iter() {
+        for batch in left_stable.iter().chain(right_stable.iter()) {
             let mut tuples = Vec::new();
-            project_helper(&batch, |t| tuples.push(t.clone()));
-            result.push(tuples.into());
-        }
-        for batch in right_stable.iter() {
-            let mut tuples = Vec::new();
-            project_helper(&batch, |t| tuples.push(t.clone()));
-            result.push(tuples.into());
+            project_helper(batch, |t| tuples.push(t.clone()));
+            self.produced(tuples.len());
+            if !sink(tuples.into()) {
+                break;
+            }
         }
 
-        Ok(result)
+        self.released(batches_len(&left_stable) + batches_len(&right_stable));
+        Ok(())
     }
 
     fn collect_intersect<T, L, R>(
@@ -357,10 +1027,12 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
         for left_batch in left.iter() {
             let mut tuples = Vec::new();
             for right_batch in right.iter() {
-                intersect_helper(&left_batch, &right_batch, |t| tuples.push(t.clone()));
+                intersect_helper(left_batch, right_batch, |t| tuples.push(t.clone()));
             }
+            self.produced(tuples.len());
             result.push(tuples.into());
         }
+        self.released(batches_len(&left) + batches_len(&right));
         Ok(result)
     }
 
@@ -380,9 +1052,11 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
 
         for batch in left.iter() {
             let mut tuples = Vec::new();
-            diff_helper(&batch, &right_slices, |t| tuples.push(t.clone()));
+            diff_helper(batch, &right_slices, |t| tuples.push(t.clone()));
+            self.produced(tuples.len());
             result.push(tuples.into());
         }
+        self.released(batches_len(&left) + batches_len(&right));
         Ok(result)
     }
 
@@ -392,14 +1066,128 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
         S: Tuple,
         E: ExpressionExt<S>,
     {
-        let mut result = Vec::<Tuples<T>>::new();
-        let stable = project.expression().collect_stable(self)?;
+        let mut result = Vec::new();
+        self.stream_project(project, &mut |batch| {
+            result.push(batch);
+            true
+        })?;
+        Ok(result)
+    }
+
+    fn stream_project<S, T, E>(
+        &self,
+        project: &Project<S, T, E>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let stable = project.expression().collect_stable_ref(self)?;
         let mut mapper = project.mapper_mut();
+        let mut contextual = project.contextual_mut();
         for batch in stable.iter() {
             let mut tuples = Vec::new();
-            project_helper(&batch, |t| tuples.push(mapper(t)));
+            if let Some(contextual) = contextual.as_mut() {
+                let context = self.context()?;
+                for t in &batch[..] {
+                    tuples.push(contextual(context, t).ok_or(Error::MissingContext)?);
+                }
+            } else {
+                project_helper(batch, |t| tuples.push(mapper(t)));
+            }
+            self.produced(tuples.len());
+            if !sink(tuples.into()) {
+                break;
+            }
+        }
+        self.released(stable.owned_len());
+        Ok(())
+    }
+
+    fn collect_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let mut result = Vec::new();
+        self.stream_filter_map(filter_map, &mut |batch| {
+            result.push(batch);
+            true
+        })?;
+        Ok(result)
+    }
+
+    fn stream_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let stable = filter_map.expression().collect_stable_ref(self)?;
+        let mut mapper = filter_map.mapper_mut();
+        for batch in stable.iter() {
+            let mut tuples = Vec::new();
+            project_helper(batch, |t| self.emit(&mut tuples, mapper(t)));
+            if !sink(tuples.into()) {
+                break;
+            }
+        }
+        self.released(stable.owned_len());
+        Ok(())
+    }
+
+    fn collect_flat_project<S, T, E>(
+        &self,
+        flat_project: &FlatProject<S, T, E>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let mut result = Vec::<Tuples<T>>::new();
+        let stable = flat_project.expression().collect_stable_ref(self)?;
+        let mut mapper = flat_project.mapper_mut();
+        for batch in stable.iter() {
+            let mut tuples = Vec::new();
+            project_helper(batch, |t| self.extend(&mut tuples, mapper(t)));
+            result.push(tuples.into());
+        }
+        self.released(stable.owned_len());
+        Ok(result)
+    }
+
+    fn collect_try_project<S, T, E>(
+        &self,
+        try_project: &TryProject<S, T, E>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let mut result = Vec::<Tuples<T>>::new();
+        let stable = try_project.expression().collect_stable_ref(self)?;
+        let mut mapper = try_project.mapper_mut();
+        for batch in stable.iter() {
+            let tuples = batch
+                .iter()
+                .map(|t| mapper(t).map_err(|message| Error::ProjectionFailed { message }))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.produced(tuples.len());
             result.push(tuples.into());
         }
+        self.released(stable.owned_len());
         Ok(result)
     }
 
@@ -414,21 +1202,52 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>,
     {
-        let mut result = Vec::<Tuples<T>>::new();
-        let left = product.left().collect_stable(self)?;
-        let right = product.right().collect_stable(self)?;
+        let mut result = Vec::new();
+        self.stream_product(product, &mut |batch| {
+            result.push(batch);
+            true
+        })?;
+        Ok(result)
+    }
+
+    fn stream_product<L, R, Left, Right, T>(
+        &self,
+        product: &Product<L, R, Left, Right, T>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: ExpressionExt<L>,
+        Right: ExpressionExt<R>,
+    {
+        let incremental = self.incremental();
+        let left = product.left().collect_stable(&incremental)?;
+        let right = product.right().collect_stable(&incremental)?;
 
         let mut mapper = product.mapper_mut();
+        let mut filter = product.filter_mut();
+        let mut mapper = |l: &L, r: &R| match filter.as_mut() {
+            Some(filter) => filter(l, r),
+            None => Some(mapper(l, r)),
+        };
         for left_batch in left.iter() {
+            if self.exhausted() || self.cancelled() {
+                break;
+            }
             let mut tuples = Vec::new();
             for right_batch in right.iter() {
-                product_helper(&left_batch, &right_batch, |v1, v2| {
-                    tuples.push(mapper(v1, v2))
+                product_helper(left_batch, right_batch, |v1, v2| {
+                    self.emit_limited(&mut tuples, || mapper(v1, v2))
                 });
             }
-            result.push(tuples.into());
+            if !sink(tuples.into()) {
+                break;
+            }
         }
-        Ok(result)
+        self.released(batches_len(&left) + batches_len(&right));
+        self.check_cancelled()
     }
 
     fn collect_join<K, L, R, Left, Right, T>(
@@ -443,33 +1262,159 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>,
     {
-        let mut result = Vec::<Tuples<T>>::new();
+        let mut result = Vec::new();
+        self.stream_join(join, &mut |batch| {
+            result.push(batch);
+            true
+        })?;
+        Ok(result)
+    }
+
+    fn stream_join<K, L, R, Left, Right, T>(
+        &self,
+        join: &Join<K, L, R, Left, Right, T>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        K: Tuple,
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: ExpressionExt<L>,
+        Right: ExpressionExt<R>,
+    {
+        if !join.mode().is_monotone() {
+            for batch in self.collect_outer_join(join)? {
+                sink(batch);
+            }
+            return Ok(());
+        }
+
+        let incremental = self.incremental();
         let mut left_key = join.left_key_mut();
         let mut right_key = join.right_key_mut();
 
-        let left = join.left().collect_stable(self)?;
-        let left: Vec<Tuples<(K, &L)>> = left
+        let left = join.left().collect_stable(&incremental)?;
+        let left: Vec<_> = left
             .iter()
-            .map(|batch| batch.iter().map(|t| (left_key(&t), t)).into())
+            .map(|batch| self.keyed(batch, &mut *left_key))
             .collect();
 
-        let right = join.right().collect_stable(self)?;
-        let right: Vec<Tuples<(K, &R)>> = right
+        let right = join.right().collect_stable(&incremental)?;
+        let right: Vec<_> = right
             .iter()
-            .map(|batch| batch.iter().map(|t| (right_key(&t), t)).into())
+            .map(|batch| self.keyed(batch, &mut *right_key))
             .collect();
 
         let mut joiner = join.mapper_mut();
+        let mut mapper = join.left_mapper_mut();
+        let right_slices = right.iter().map(|batch| &batch[..]).collect::<Vec<_>>();
         for left_batch in left.iter() {
+            if self.exhausted() || self.cancelled() {
+                break;
+            }
             let mut tuples = Vec::new();
-            for right_batch in right.iter() {
-                join_helper(&left_batch, &right_batch, |k, v1, v2| {
-                    tuples.push(joiner(k, v1, v2))
+            if join.mode() == JoinMode::Semi {
+                semi_join_helper(left_batch, &right_slices, |k, v, found| {
+                    !found || self.emit_limited(&mut tuples, || mapper(k, v))
                 });
+            } else {
+                for right_batch in right.iter() {
+                    join_helper(left_batch, right_batch, |k, v1, v2| {
+                        self.emit_limited(&mut tuples, || joiner(k, v1, v2))
+                    });
+                }
+            }
+            if !sink(tuples.into()) {
+                break;
             }
-            result.push(tuples.into());
         }
-        Ok(result)
+        self.released(
+            left.iter().map(|batch| batch.len()).sum::<usize>()
+                + right.iter().map(|batch| batch.len()).sum::<usize>(),
+        );
+        self.check_cancelled()
+    }
+
+    fn collect_enumerate<T, E>(
+        &self,
+        enumerate: &Enumerate<T, E>,
+    ) -> Result<Vec<Tuples<(usize, T)>>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        // the indices are global over the sorted tuples of the sub-expression, so the
+        // sub-expression has to be materialized before the indices are assigned:
+        let recent = enumerate.expression().collect_recent(self)?;
+        let stable = enumerate.expression().collect_stable(self)?;
+        let inputs = recent.len() + batches_len(&stable);
+        let tuples = Tuples::merge_all(std::iter::once(recent).chain(stable));
+
+        let tuples: Vec<_> = tuples.into_tuples().into_iter().enumerate().collect();
+        self.produced(tuples.len());
+        self.released(inputs);
+        Ok(vec![tuples.into()])
+    }
+
+    fn collect_group_by<S, K, A, E>(
+        &self,
+        group_by: &GroupBy<S, K, A, E>,
+    ) -> Result<Vec<Tuples<(K, A)>>, Error>
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: ExpressionExt<S>,
+    {
+        // an aggregate depends on all tuples of its group, so the sub-expression has to be
+        // materialized before the groups are folded:
+        let recent = group_by.expression().collect_recent(self)?;
+        let stable = group_by.expression().collect_stable(self)?;
+        let inputs = recent.len() + batches_len(&stable);
+        let tuples = Tuples::merge_all(std::iter::once(recent).chain(stable));
+
+        let mut key = group_by.key_mut();
+        let mut fold = group_by.fold_mut();
+        let mut groups = BTreeMap::<K, A>::new();
+        for tuple in tuples.iter() {
+            let key = key(tuple);
+            let aggregate = groups
+                .remove(&key)
+                .unwrap_or_else(|| group_by.init().clone());
+            groups.insert(key, fold(aggregate, tuple));
+        }
+
+        self.produced(groups.len());
+        self.released(inputs);
+        Ok(vec![groups.into_iter().collect()])
+    }
+
+    fn collect_scan<T, A, E>(&self, scan: &Scan<T, A, E>) -> Result<Vec<Tuples<(T, A)>>, Error>
+    where
+        T: Tuple,
+        A: Tuple,
+        E: ExpressionExt<T>,
+    {
+        // the accumulated values are global over the sorted tuples of the sub-expression,
+        // so the sub-expression has to be materialized before the tuples are accumulated:
+        let recent = scan.expression().collect_recent(self)?;
+        let stable = scan.expression().collect_stable(self)?;
+        let inputs = recent.len() + batches_len(&stable);
+        let tuples = Tuples::merge_all(std::iter::once(recent).chain(stable));
+
+        let mut accumulator = scan.accumulator_mut();
+        let mut value = scan.init().clone();
+        let mut result = Vec::with_capacity(tuples.len());
+        for tuple in tuples.into_tuples() {
+            value = accumulator(&value, &tuple);
+            result.push((tuple, value.clone()));
+        }
+
+        self.produced(result.len());
+        self.released(inputs);
+        // the tuples are already sorted by their first component, which is unique:
+        Ok(vec![Tuples::from_sorted_unchecked(result)])
     }
 
     fn collect_view<T, E>(&self, view: &View<T, E>) -> Result<Vec<Tuples<T>>, Error>
@@ -477,12 +1422,20 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
         T: Tuple + 'static,
         E: ExpressionExt<T> + 'static,
     {
-        let mut result = Vec::<Tuples<T>>::new();
-        let table = self.database.view_instance(&view)?;
-        for batch in table.stable().iter() {
-            result.push(batch.clone());
-        }
-        Ok(result)
+        let table = self.database.view_instance(view)?;
+        let stable = table.stable_batches()?;
+        self.held(batches_len(&stable));
+        Ok(stable)
+    }
+
+    fn collect_view_ref<T, E>(&self, view: &View<T, E>) -> Result<BatchesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+    {
+        let stable = self.database.view_instance(view)?.stable_ref()?;
+        self.held(stable.owned_len());
+        Ok(stable)
     }
 }
 
@@ -491,12 +1444,219 @@ impl<'d> StableCollector for IncrementalCollector<'d> {
 pub(super) struct Evaluator<'d> {
     /// Is the database in which the visited expression is evaluated.
     database: &'d Database,
+
+    /// Is the (optional) monitor that observes the tuples produced during evaluation.
+    monitor: Option<&'d Monitor<'d>>,
+
+    /// Is the (optional) number of tuples that a selection, a product or a join at the root
+    /// of the evaluated expression may produce.
+    take: Option<&'d Cell<usize>>,
+
+    /// Is the (optional) bindings of the parameters of the evaluated expression.
+    params: Option<&'d Params>,
+
+    /// Is the cache of the tuples of the shared sub-expressions of the evaluated expression,
+    /// which lives as long as the evaluation.
+    cache: Rc<Cache>,
+
+    /// Is true if the result of the evaluated expression is returned without sorting and
+    /// deduplicating its tuples.
+    unordered: bool,
+
+    /// Is the (optional) flag that cancels the evaluation when it is set.
+    cancel: Option<&'d AtomicBool>,
+
+    /// Is the (optional) context that is read by the contextual closures of the evaluated
+    /// expression.
+    context: Option<&'d dyn Any>,
+
+    /// Is the (optional) arena in which scratch memory is allocated during evaluation.
+    #[cfg(feature = "arena")]
+    arena: Option<&'d Arena>,
 }
 
 impl<'d> Evaluator<'d> {
     /// Creates a new [`Evaluator`].
     pub fn new(database: &'d Database) -> Self {
-        Self { database }
+        Self {
+            database,
+            monitor: None,
+            take: None,
+            params: None,
+            cache: Rc::new(RefCell::new(HashMap::new())),
+            unordered: false,
+            cancel: None,
+            context: None,
+            #[cfg(feature = "arena")]
+            arena: None,
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that returns the tuples of the evaluated expression in no
+    /// particular order and possibly with duplicates.
+    ///
+    /// **Note**: the result of such an evaluator does not satisfy the invariant of
+    /// [`Tuples`] and must only be consumed as a vector (see [`Tuples::unordered`]).
+    pub fn unordered(database: &'d Database) -> Self {
+        Self {
+            unordered: true,
+            ..Self::new(database)
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that stops a selection, a product or a join at the root
+    /// of the evaluated expression once it produces as many tuples as `take` holds.
+    pub fn with_limit(database: &'d Database, take: &'d Cell<usize>) -> Self {
+        Self {
+            take: Some(take),
+            ..Self::new(database)
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that binds the parameters of the evaluated expression to
+    /// the values of `params`.
+    pub fn with_params(database: &'d Database, params: &'d Params) -> Self {
+        Self {
+            params: Some(params),
+            ..Self::new(database)
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that reports the tuples it produces to `monitor`.
+    pub fn with_monitor(database: &'d Database, monitor: &'d Monitor<'d>) -> Self {
+        Self {
+            monitor: Some(monitor),
+            ..Self::new(database)
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that fails with [`Error::Cancelled`] once `cancel` is set.
+    pub fn with_cancel(database: &'d Database, cancel: &'d AtomicBool) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..Self::new(database)
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that supplies `context` to the contextual closures of the
+    /// evaluated expression (see [`Select::with_context`] and [`Project::with_context`]).
+    pub fn with_context(database: &'d Database, context: &'d dyn Any) -> Self {
+        Self {
+            context: Some(context),
+            ..Self::new(database)
+        }
+    }
+
+    /// Creates a new [`Evaluator`] that allocates its scratch memory in `arena`.
+    #[cfg(feature = "arena")]
+    pub fn with_arena(database: &'d Database, arena: &'d Arena) -> Self {
+        Self {
+            arena: Some(arena),
+            ..Self::new(database)
+        }
+    }
+
+    /// Returns a new incremental collector over the database of the receiver, sharing its
+    /// monitor, parameters, cache, cancellation flag, context and arena.
+    fn incremental(&self) -> IncrementalCollector<'d> {
+        IncrementalCollector {
+            database: self.database,
+            monitor: self.monitor,
+            take: None,
+            params: self.params,
+            cache: Some(self.cache.clone()),
+            cancel: self.cancel,
+            context: self.context,
+            #[cfg(feature = "arena")]
+            arena: self.arena,
+        }
+    }
+
+    /// Returns a new incremental collector like [`incremental`] that also shares the limit
+    /// of the receiver.
+    ///
+    /// [`incremental`]: Evaluator::incremental
+    fn limited(&self) -> IncrementalCollector<'d> {
+        IncrementalCollector {
+            take: self.take,
+            ..self.incremental()
+        }
+    }
+
+    /// Merges the `recent` and `stable` tuples of the evaluated expression into its result
+    /// using `incremental`. If the receiver is unordered, the tuples are concatenated
+    /// without sorting and deduplicating them.
+    fn merge<T: Tuple>(
+        &self,
+        incremental: &IncrementalCollector<'d>,
+        recent: Tuples<T>,
+        stable: Vec<Tuples<T>>,
+    ) -> Tuples<T> {
+        if !self.unordered {
+            let result = incremental.merge(recent, stable);
+            result.debug_assert_sorted();
+            return result;
+        }
+
+        let mut result = recent.into_tuples();
+        result.reserve(batches_len(&stable));
+        for batch in stable {
+            result.extend(batch.into_tuples());
+        }
+        Tuples::unordered(result)
+    }
+
+    /// Stabilizes the relations and the views on which `expression` depends and enters the
+    /// gate of the database for reading them. The dependencies are stabilized again if
+    /// another thread inserts tuples into them before the gate is entered for reading.
+    fn stabilize<T, E>(&self, expression: &E) -> Result<Reader<'d>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let database = self.database;
+        loop {
+            {
+                let reader = database.gate.read();
+                if database.is_stable(
+                    expression.relation_dependencies(),
+                    expression.view_dependencies(),
+                ) {
+                    return Ok(reader);
+                }
+            }
+
+            let _writer = database.gate.write();
+            for r in expression.relation_dependencies() {
+                database.stabilize_relation(r)?;
+            }
+            for r in expression.view_dependencies() {
+                database.stabilize_view(r)?;
+            }
+        }
+    }
+
+    /// Evaluates `expression` like [`ExpressionExt::collect_recent`] on the receiver but
+    /// hands the recent tuples and then every batch of the stable tuples of `expression` to
+    /// `sink` as soon as they are collected instead of merging them, until `sink` returns
+    /// `false`. The tuples of every batch are sorted, but a tuple may appear in more than
+    /// one batch.
+    pub fn stream<T, E>(
+        &self,
+        expression: &E,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let _reader = self.stabilize(expression)?;
+
+        let incremental = self.incremental();
+        if sink(expression.collect_recent(&incremental)?) {
+            expression.stream_stable(&incremental, sink)?;
+        }
+        Ok(())
     }
 }
 
@@ -525,46 +1685,46 @@ impl<'d> RecentCollector for Evaluator<'d> {
         Ok(vec![singleton.tuple().clone()].into())
     }
 
+    fn collect_param<T>(&self, param: &Param<T>) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        Ok(vec![bound(self.params, param)?].into())
+    }
+
     fn collect_relation<T>(&self, relation: &Relation<T>) -> Result<Tuples<T>, Error>
     where
         T: Tuple + 'static,
     {
         // stabilize the instance corresponding to this relation before evaluating the relation:
-        self.database.stabilize_relation(relation.name())?;
-        let table = self.database.relation_instance(&relation)?;
+        let _reader = self.stabilize(relation)?;
+        let table = self.database.relation_instance(relation)?;
 
         assert!(table.recent().is_empty());
         assert!(table.to_add().is_empty());
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let mut result = relation.collect_recent(&incremental)?;
-        for batch in relation.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = relation.collect_recent(&incremental)?;
+        let stable = relation.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
 
         Ok(result)
     }
 
     fn collect_select<T, E>(&self, select: &Select<T, E>) -> Result<Tuples<T>, Error>
     where
-        T: Tuple,
+        T: Tuple + 'static,
         E: ExpressionExt<T>,
     {
         // stabilize the dependencies of the expression before evaluating it:
-        for r in select.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in select.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(select)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.limited();
 
-        let mut result = select.collect_recent(&incremental)?;
-        for batch in select.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = select.collect_recent(&incremental)?;
+        let stable = select.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
         Ok(result)
     }
 
@@ -574,19 +1734,13 @@ impl<'d> RecentCollector for Evaluator<'d> {
         L: ExpressionExt<T>,
         R: ExpressionExt<T>,
     {
-        for r in union.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in union.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(union)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let mut result = union.collect_recent(&incremental)?;
-        for batch in union.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = union.collect_recent(&incremental)?;
+        let stable = union.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
         Ok(result)
     }
 
@@ -596,19 +1750,13 @@ impl<'d> RecentCollector for Evaluator<'d> {
         L: ExpressionExt<T>,
         R: ExpressionExt<T>,
     {
-        for r in intersect.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in intersect.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(intersect)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let mut result = intersect.collect_recent(&incremental)?;
-        for batch in intersect.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = intersect.collect_recent(&incremental)?;
+        let stable = intersect.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
 
         Ok(result)
     }
@@ -622,19 +1770,13 @@ impl<'d> RecentCollector for Evaluator<'d> {
         L: ExpressionExt<T>,
         R: ExpressionExt<T>,
     {
-        for r in difference.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in difference.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(difference)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let mut result = difference.collect_recent(&incremental)?;
-        for batch in difference.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = difference.collect_recent(&incremental)?;
+        let stable = difference.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
 
         Ok(result)
     }
@@ -645,19 +1787,70 @@ impl<'d> RecentCollector for Evaluator<'d> {
         S: Tuple,
         E: ExpressionExt<S>,
     {
-        for r in project.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in project.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(project)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let mut result = project.collect_recent(&incremental)?;
-        for batch in project.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = project.collect_recent(&incremental)?;
+        let stable = project.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
+        Ok(result)
+    }
+
+    fn collect_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let _reader = self.stabilize(filter_map)?;
+
+        let incremental = self.incremental();
+
+        let recent = filter_map.collect_recent(&incremental)?;
+        let stable = filter_map.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
+        Ok(result)
+    }
+
+    fn collect_flat_project<S, T, E>(
+        &self,
+        flat_project: &FlatProject<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let _reader = self.stabilize(flat_project)?;
+
+        let incremental = self.incremental();
+
+        let recent = flat_project.collect_recent(&incremental)?;
+        let stable = flat_project.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
+        Ok(result)
+    }
+
+    fn collect_try_project<S, T, E>(
+        &self,
+        try_project: &TryProject<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let _reader = self.stabilize(try_project)?;
+
+        let incremental = self.incremental();
+
+        let recent = try_project.collect_recent(&incremental)?;
+        let stable = try_project.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
         Ok(result)
     }
 
@@ -672,19 +1865,13 @@ impl<'d> RecentCollector for Evaluator<'d> {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>,
     {
-        for r in product.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in product.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(product)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.limited();
 
-        let mut result = product.collect_recent(&incremental)?;
-        for batch in product.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = product.collect_recent(&incremental)?;
+        let stable = product.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
 
         Ok(result)
     }
@@ -701,20 +1888,68 @@ impl<'d> RecentCollector for Evaluator<'d> {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>,
     {
-        for r in join.relation_dependencies() {
-            self.database.stabilize_relation(&r)?;
-        }
-        for r in join.view_dependencies() {
-            self.database.stabilize_view(&r)?;
-        }
+        let _reader = self.stabilize(join)?;
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.limited();
 
-        let mut result = join.collect_recent(&incremental)?;
-        for batch in join.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = join.collect_recent(&incremental)?;
+        let stable = join.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
+
+        Ok(result)
+    }
+
+    fn collect_enumerate<T, E>(
+        &self,
+        enumerate: &Enumerate<T, E>,
+    ) -> Result<Tuples<(usize, T)>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let _reader = self.stabilize(enumerate)?;
+
+        let incremental = self.incremental();
+
+        let recent = enumerate.collect_recent(&incremental)?;
+        let stable = enumerate.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
+        Ok(result)
+    }
+
+    fn collect_group_by<S, K, A, E>(
+        &self,
+        group_by: &GroupBy<S, K, A, E>,
+    ) -> Result<Tuples<(K, A)>, Error>
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: ExpressionExt<S>,
+    {
+        let _reader = self.stabilize(group_by)?;
+
+        let incremental = self.incremental();
+
+        let recent = group_by.collect_recent(&incremental)?;
+        let stable = group_by.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
+        Ok(result)
+    }
+
+    fn collect_scan<T, A, E>(&self, scan: &Scan<T, A, E>) -> Result<Tuples<(T, A)>, Error>
+    where
+        T: Tuple,
+        A: Tuple,
+        E: ExpressionExt<T>,
+    {
+        let _reader = self.stabilize(scan)?;
 
+        let incremental = self.incremental();
+
+        let recent = scan.collect_recent(&incremental)?;
+        let stable = scan.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
         Ok(result)
     }
 
@@ -723,17 +1958,16 @@ impl<'d> RecentCollector for Evaluator<'d> {
         T: Tuple + 'static,
         E: ExpressionExt<T> + 'static,
     {
-        self.database.stabilize_view(view.reference())?;
+        let _reader = self.stabilize(view)?;
         let table = self.database.view_instance(view)?;
         assert!(table.recent().is_empty());
         assert!(table.to_add().is_empty());
 
-        let incremental = IncrementalCollector::new(self.database);
+        let incremental = self.incremental();
 
-        let mut result = view.collect_recent(&incremental)?;
-        for batch in view.collect_stable(&incremental)? {
-            result = result.merge(batch);
-        }
+        let recent = view.collect_recent(&incremental)?;
+        let stable = view.collect_stable(&incremental)?;
+        let result = self.merge(&incremental, recent, stable);
 
         Ok(result)
     }
@@ -742,6 +1976,7 @@ impl<'d> RecentCollector for Evaluator<'d> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_evaluate_full() {
@@ -768,24 +2003,76 @@ mod tests {
             let result = database.evaluate(&s).unwrap();
             assert_eq!(Tuples::from(vec![42]), result);
         }
-    }
-    #[test]
-    fn test_evaluate_relation() {
+    }
+    #[test]
+    fn test_evaluate_relation() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            database.insert(&r, vec![1, 2, 3].into()).unwrap();
+            let result = database.evaluate(&r).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![1, 2, 3]), result);
+        }
+        {
+            let database = Database::new();
+            let mut dummy = Database::new();
+            let r = dummy.add_relation::<i32>("r").unwrap();
+
+            assert!(database.evaluate(&r).is_err());
+        }
+    }
+    #[test]
+    fn test_evaluate_arrays() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<[i32; 3]>("r").unwrap();
+            database
+                .insert(&r, vec![[3, 1, 2], [1, 2, 3], [3, 1, 2], [1, 2, 0]].into())
+                .unwrap();
+
+            let result = database.evaluate(&r).unwrap();
+            assert_eq!(
+                Tuples::<[i32; 3]>::from(vec![[1, 2, 0], [1, 2, 3], [3, 1, 2]]),
+                result
+            );
+            assert_eq!(vec![[1, 2, 0], [1, 2, 3], [3, 1, 2]], result.into_tuples());
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<(i32, i32, i32)>("r").unwrap();
+            let s = database.add_relation::<[i32; 3]>("s").unwrap();
+            database
+                .insert(&r, vec![(1, 2, 3), (4, 5, 6), (1, 2, 3)].into())
+                .unwrap();
+            database
+                .insert(&s, vec![[1, 0, 0], [4, 0, 0], [7, 0, 0]].into())
+                .unwrap();
+
+            let join = r
+                .builder()
+                .project_array(|&(x, y, z)| [x, y, z])
+                .with_key(|a| a[0])
+                .join(s.builder().with_key(|a| a[0]))
+                .on(|_, l, r| [l[0], l[1], l[2], r[1], r[2]])
+                .build();
+            assert_eq!(
+                Tuples::<[i32; 5]>::from(vec![[1, 2, 3, 0, 0], [4, 5, 6, 0, 0]]),
+                database.evaluate(&join).unwrap()
+            );
+        }
         {
             let mut database = Database::new();
             let r = database.add_relation::<i32>("r").unwrap();
-            database.insert(&r, vec![1, 2, 3].into()).unwrap();
-            let result = database.evaluate(&r).unwrap();
-            assert_eq!(Tuples::<i32>::from(vec![1, 2, 3]), result);
-        }
-        {
-            let database = Database::new();
-            let mut dummy = Database::new();
-            let r = dummy.add_relation::<i32>("r").unwrap();
+            database.insert(&r, vec![1, 2].into()).unwrap();
 
-            assert!(database.evaluate(&r).is_err());
+            let wide = r.builder().project_array(|&t| [t; 64]).build();
+            assert_eq!(
+                Tuples::<[i32; 64]>::from(vec![[1; 64], [2; 64]]),
+                database.evaluate(&wide).unwrap()
+            );
         }
     }
+
     #[test]
     fn test_evaluate_project() {
         {
@@ -824,6 +2111,273 @@ mod tests {
             assert!(database.evaluate(&project).is_err());
         }
     }
+    #[test]
+    fn test_evaluate_project_memoized() {
+        use std::sync::{atomic::AtomicUsize, Arc};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let project = {
+            let calls = calls.clone();
+            r.builder()
+                .project_memoized(move |t| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    t * 10
+                })
+                .build()
+        };
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        let result = database.evaluate(&project).unwrap();
+        assert_eq!(Tuples::<i32>::from(vec![10, 20, 30, 40]), result);
+        assert_eq!(4, calls.load(Ordering::Relaxed));
+
+        database.insert(&r, vec![4, 5].into()).unwrap();
+        let result = database.evaluate(&project).unwrap();
+        assert_eq!(Tuples::<i32>::from(vec![10, 20, 30, 40, 50]), result);
+        assert_eq!(5, calls.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_evaluate_flat_project() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<Vec<i32>>("r").unwrap();
+            let flatten = r.builder().flatten().build();
+
+            let result = database.evaluate(&flatten).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![]), result);
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<Vec<i32>>("r").unwrap();
+            let flatten = r.builder().flatten().build();
+            database
+                .insert(&r, vec![vec![1, 2], vec![3]].into())
+                .unwrap();
+
+            let result = database.evaluate(&flatten).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![1, 2, 3]), result);
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<Vec<i32>>("r").unwrap();
+            let v = database.store_view(r.builder().flatten()).unwrap();
+            database
+                .insert(&r, vec![vec![3, 1], vec![2]].into())
+                .unwrap();
+            database.evaluate(&v).unwrap();
+            database
+                .insert(&r, vec![vec![2, 4], vec![]].into())
+                .unwrap();
+
+            let result = database.evaluate(&v).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![1, 2, 3, 4]), result);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_shared_select() {
+        use std::sync::{atomic::AtomicUsize, Arc};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let s = {
+            let calls = calls.clone();
+            r.builder()
+                .select(move |t| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    t % 2 == 0
+                })
+                .build()
+        };
+        let intersect = s.builder().intersect(s.clone()).build();
+        database
+            .insert(&r, (0..10).collect::<Vec<_>>().into())
+            .unwrap();
+        database.evaluate(&r).unwrap();
+        database
+            .insert(&r, (10..20).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let expected: Tuples<i32> = (0..20).filter(|t| t % 2 == 0).collect::<Vec<_>>().into();
+        assert_eq!(expected, database.evaluate(&intersect).unwrap());
+        assert_eq!(20, calls.load(Ordering::Relaxed));
+
+        // the cache does not outlive an evaluation:
+        assert_eq!(expected, database.evaluate(&intersect).unwrap());
+        assert_eq!(40, calls.load(Ordering::Relaxed));
+
+        // unshared selects are not cached:
+        let union = r
+            .builder()
+            .select(|t| t % 2 == 0)
+            .union(r.builder().select(|t| t % 2 == 1))
+            .build();
+        assert_eq!(20, database.evaluate(&union).unwrap().len());
+    }
+
+    #[test]
+    fn test_evaluate_shared_predicate() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database
+            .insert(&r, (0..10).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(&s, (10..20).collect::<Vec<_>>().into())
+            .unwrap();
+
+        // two selects that share their predicate over different inputs are not the same
+        // sub-expression:
+        let r_evens: Mono<i32> = Select::new(Mono::from(r), |t| t % 2 == 0).into();
+        let mut s_evens = r_evens.clone();
+        if let Mono::Select(select) = &mut s_evens {
+            *select.expression_mut() = Mono::from(s);
+        }
+        let union = Union::new(r_evens, s_evens);
+
+        let expected: Tuples<i32> = (0..20).filter(|t| t % 2 == 0).collect::<Vec<_>>().into();
+        assert_eq!(expected, database.evaluate(&union).unwrap());
+    }
+
+    #[test]
+    fn test_evaluate_try_project() {
+        let parse = |t: &String| t.parse::<i32>().map_err(|e| format!("{:?}: {}", t, e));
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<String>("r").unwrap();
+            let numbers = r.builder().try_project(parse).build();
+            database
+                .insert(&r, vec!["3".to_string(), "1".into(), "2".into()].into())
+                .unwrap();
+
+            let result = database.evaluate(&numbers).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![1, 2, 3]), result);
+
+            database.insert(&r, vec!["x".to_string()].into()).unwrap();
+            match database.evaluate(&numbers) {
+                Err(Error::ProjectionFailed { message }) => assert!(message.starts_with("\"x\"")),
+                result => panic!("expecting a projection failure, found {:?}", result),
+            }
+        }
+        {
+            // a view cannot be maintained past a failing projection:
+            let mut database = Database::new();
+            let r = database.add_relation::<String>("r").unwrap();
+            assert!(matches!(
+                database.store_view(r.builder().try_project(parse)),
+                Err(Error::UnsupportedExpression { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_filter_map() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let even_halves = r
+            .builder()
+            .filter_map(|&t| if t % 2 == 0 { Some(t / 2) } else { None })
+            .build();
+        let view = database.store_view(even_halves.clone()).unwrap();
+
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+        assert_eq!(
+            vec![1, 2],
+            database.evaluate(&even_halves).unwrap().into_tuples()
+        );
+        assert_eq!(vec![1, 2], database.evaluate(&view).unwrap().into_tuples());
+
+        database.insert(&r, vec![5, 6].into()).unwrap();
+        assert_eq!(
+            vec![1, 2, 3],
+            database.evaluate(&view).unwrap().into_tuples()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_lookup() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32,)>("r").unwrap();
+        database.insert(&r, vec![(1,), (2,), (3,)].into()).unwrap();
+
+        let names: HashMap<i32, String> = vec![(1, "one".to_string()), (2, "two".into())]
+            .into_iter()
+            .collect();
+        let named = r
+            .builder()
+            .lookup(|t| t.0, names, |t, name| (t.0, name.clone()))
+            .build();
+        assert_eq!(
+            vec![(1, "one".to_string()), (2, "two".into())],
+            database.evaluate(&named).unwrap().into_tuples()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_split_either() {
+        use either::Either;
+
+        let mut database = Database::new();
+        let r = database.add_relation::<Either<i32, String>>("r").unwrap();
+        let (numbers, words) = r.builder().split_either();
+        let numbers = database.store_view(numbers.build()).unwrap();
+        let words = words.build();
+
+        database
+            .insert(
+                &r,
+                vec![
+                    Either::Left(2),
+                    Either::Right("b".to_string()),
+                    Either::Left(1),
+                    Either::Right("a".into()),
+                ]
+                .into(),
+            )
+            .unwrap();
+        assert_eq!(
+            vec![1, 2],
+            database.evaluate(&numbers).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec!["a".to_string(), "b".into()],
+            database.evaluate(&words).unwrap().into_tuples()
+        );
+
+        database.insert(&r, vec![Either::Left(0)].into()).unwrap();
+        assert_eq!(
+            vec![0, 1, 2],
+            database.evaluate(&numbers).unwrap().into_tuples()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_when() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4, 5].into()).unwrap();
+
+        let query = |odds_only: bool| {
+            r.builder()
+                .select(|_| true)
+                .when(odds_only, |_| r.builder().select(|t| t % 2 == 1))
+                .build()
+        };
+        assert_eq!(
+            vec![1, 3, 5],
+            database.evaluate(&query(true)).unwrap().into_tuples()
+        );
+        assert_eq!(
+            vec![1, 2, 3, 4, 5],
+            database.evaluate(&query(false)).unwrap().into_tuples()
+        );
+    }
+
     #[test]
     fn test_evaluate_select() {
         {
@@ -870,6 +2424,141 @@ mod tests {
             assert!(database.evaluate(&select).is_err());
         }
     }
+
+    #[test]
+    fn test_evaluate_select3() {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Musician {
+            name: String,
+            band: Option<String>,
+        }
+
+        let mut database = Database::new();
+        let musician = database.add_relation::<Musician>("musician").unwrap();
+        database
+            .insert(
+                &musician,
+                vec![
+                    Musician {
+                        name: "John Petrucci".into(),
+                        band: Some("Dream Theater".into()),
+                    },
+                    Musician {
+                        name: "Taylor Swift".into(),
+                        band: None,
+                    },
+                    Musician {
+                        name: "Conor Mason".into(),
+                        band: Some("Nothing But Thieves".into()),
+                    },
+                ]
+                .into(),
+            )
+            .unwrap();
+
+        let in_band = musician
+            .builder()
+            .select3(|m| m.band.as_ref().map(|_| true))
+            .project(|m| m.name.clone())
+            .build();
+        assert_eq!(
+            vec!["Conor Mason".to_string(), "John Petrucci".into()],
+            database.evaluate(&in_band).unwrap().into_tuples()
+        );
+
+        // both false and unknown drop the tuple:
+        let not_dream_theater = musician
+            .builder()
+            .select3(|m| m.band.as_ref().map(|b| b != "Dream Theater"))
+            .project(|m| m.name.clone())
+            .build();
+        assert_eq!(
+            vec!["Conor Mason".to_string()],
+            database.evaluate(&not_dream_theater).unwrap().into_tuples()
+        );
+
+        let view = database
+            .store_view(
+                musician
+                    .builder()
+                    .select3(|m| m.band.as_ref().map(|_| false)),
+            )
+            .unwrap();
+        assert!(database.evaluate(&view).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_select_sorted() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let sorted = r.builder().select_sorted(|&t| t, 500).build();
+            let naive = r.builder().select(|&t| t < 500).build();
+
+            database
+                .insert(&r, (0..1000).step_by(3).collect::<Vec<_>>().into())
+                .unwrap();
+            database.evaluate(&r).unwrap();
+            database
+                .insert(&r, (0..1000).step_by(7).collect::<Vec<_>>().into())
+                .unwrap();
+
+            assert_eq!(
+                database.evaluate(&naive).unwrap(),
+                database.evaluate(&sorted).unwrap()
+            );
+        }
+        {
+            let database = Database::new();
+            let s = Singleton::new(42);
+            let select = s.builder().select_sorted(|&t| t, 42).build();
+
+            let result = database.evaluate(&select).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![]), result);
+        }
+    }
+    #[test]
+    fn test_evaluate_find() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, i32)>("r").unwrap();
+        database
+            .insert(
+                &r,
+                (0..10_000).map(|i| (i, i % 7)).collect::<Vec<_>>().into(),
+            )
+            .unwrap();
+        database.evaluate(&r).unwrap();
+        database
+            .insert(
+                &r,
+                (10_000..10_100)
+                    .map(|i| (i, i % 7))
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+            .unwrap();
+
+        for target in [
+            (0, 0),
+            (4_242, 4_242 % 7),
+            (9_999, 9_999 % 7),
+            (10_050, 10_050 % 7),
+        ] {
+            let find = r.builder().find(target).build();
+            let naive = r.builder().select(move |&t| t == target).build();
+            assert_eq!(
+                vec![target],
+                database.evaluate(&find).unwrap().into_tuples()
+            );
+            assert_eq!(
+                database.evaluate(&naive).unwrap(),
+                database.evaluate(&find).unwrap()
+            );
+        }
+
+        let find = r.builder().find((4_242, 1)).build();
+        assert!(database.evaluate(&find).unwrap().is_empty());
+    }
     #[test]
     fn test_evaluate_product() {
         {
@@ -957,6 +2646,253 @@ mod tests {
             assert!(database.evaluate(&u).is_err());
         }
     }
+
+    #[test]
+    fn test_evaluate_join_modes() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, i32)>("r").unwrap();
+        let s = database.add_relation::<(i32, i32)>("s").unwrap();
+        let join = |mode| {
+            r.builder()
+                .with_key(|t| t.0)
+                .join(s.builder().with_key(|t| t.0))
+                .mode(mode)
+        };
+
+        let inner = join(JoinMode::Inner)
+            .on(|_, &l, r| (l.1, r.unwrap().1))
+            .build();
+        let semi = database.store_view(join(JoinMode::Semi).left()).unwrap();
+        let anti = database
+            .store_view_recompute(join(JoinMode::Anti).left())
+            .unwrap();
+        let outer = join(JoinMode::LeftOuter)
+            .on(|_, &l, r| (l.1, r.map(|r| r.1)))
+            .build();
+        assert!(matches!(
+            database.store_view(join(JoinMode::Anti).left()),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+        assert!(matches!(
+            database.store_view(outer.clone()),
+            Err(Error::UnsupportedExpression { .. })
+        ));
+
+        database
+            .insert(&r, vec![(1, 10), (2, 20), (3, 30)].into())
+            .unwrap();
+        database
+            .insert(&s, vec![(1, 100), (1, 101), (3, 300), (4, 400)].into())
+            .unwrap();
+
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(10, 100), (10, 101), (30, 300)]),
+            database.evaluate(&inner).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(1, 10), (3, 30)]),
+            database.evaluate(&semi).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(2, 20)]),
+            database.evaluate(&anti).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, Option<i32>)>::from(vec![
+                (10, Some(100)),
+                (10, Some(101)),
+                (20, None),
+                (30, Some(300)),
+            ]),
+            database.evaluate(&outer).unwrap()
+        );
+
+        database.insert(&r, vec![(4, 40), (5, 50)].into()).unwrap();
+        database.insert(&s, vec![(2, 200)].into()).unwrap();
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(10, 100), (10, 101), (20, 200), (30, 300), (40, 400)]),
+            database.evaluate(&inner).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(1, 10), (2, 20), (3, 30), (4, 40)]),
+            database.evaluate(&semi).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(5, 50)]),
+            database.evaluate(&anti).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, Option<i32>)>::from(vec![
+                (10, Some(100)),
+                (10, Some(101)),
+                (20, Some(200)),
+                (30, Some(300)),
+                (40, Some(400)),
+                (50, None),
+            ]),
+            database.evaluate(&outer).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_left_outer_single_pass() {
+        use std::sync::{atomic::AtomicUsize, Arc};
+
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        database.insert(&s, vec![2].into()).unwrap();
+
+        let keys = Arc::new(AtomicUsize::new(0));
+        let rows = Arc::new(AtomicUsize::new(0));
+        let outer = {
+            let keys = keys.clone();
+            let rows = rows.clone();
+            r.builder()
+                .with_key(move |&t| {
+                    keys.fetch_add(1, Ordering::Relaxed);
+                    t
+                })
+                .join(s.builder().with_key(|&t| t))
+                .mode(JoinMode::LeftOuter)
+                .on(move |_, &l, r| {
+                    rows.fetch_add(1, Ordering::Relaxed);
+                    (l, r.copied())
+                })
+                .build()
+        };
+
+        assert_eq!(
+            Tuples::from(vec![(1, None), (2, Some(2)), (3, None)]),
+            database.evaluate(&outer).unwrap()
+        );
+        assert_eq!(3, keys.load(Ordering::Relaxed));
+        assert_eq!(3, rows.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_evaluate_left_outer_view() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, char)>("r").unwrap();
+        let s = database.add_relation::<(i32, char)>("s").unwrap();
+        let outer = r
+            .builder()
+            .with_key(|t| t.0)
+            .join(s.builder().with_key(|t| t.0))
+            .mode(JoinMode::LeftOuter)
+            .on(|&k, l, r| (k, l.1, r.map(|r| r.1)));
+        let v = database.store_view_recompute(outer).unwrap();
+
+        database
+            .insert(&r, vec![(1, 'a'), (2, 'b')].into())
+            .unwrap();
+        assert_eq!(
+            Tuples::from(vec![(1, 'a', None), (2, 'b', None)]),
+            database.evaluate(&v).unwrap()
+        );
+
+        database
+            .insert(&s, vec![(1, 'x'), (1, 'y')].into())
+            .unwrap();
+        assert_eq!(
+            Tuples::from(vec![
+                (1, 'a', Some('x')),
+                (1, 'a', Some('y')),
+                (2, 'b', None)
+            ]),
+            database.evaluate(&v).unwrap()
+        );
+
+        database.insert(&r, vec![(3, 'c')].into()).unwrap();
+        database.insert(&s, vec![(2, 'z')].into()).unwrap();
+        assert_eq!(
+            Tuples::from(vec![
+                (1, 'a', Some('x')),
+                (1, 'a', Some('y')),
+                (2, 'b', Some('z')),
+                (3, 'c', None),
+            ]),
+            database.evaluate(&v).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_join_counted() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(i32, char)>("r").unwrap();
+        let s = database.add_relation::<(i32, char)>("s").unwrap();
+        let counted = r
+            .builder()
+            .with_key(|t| t.0)
+            .join(s.builder().with_key(|t| t.0))
+            .on_counted(|&k, _, _| k)
+            .build();
+        database
+            .insert(&r, vec![(1, 'a'), (1, 'b'), (2, 'c')].into())
+            .unwrap();
+        database
+            .insert(&s, vec![(1, 'x'), (1, 'y'), (2, 'z')].into())
+            .unwrap();
+
+        assert_eq!(
+            Tuples::from(vec![(1, 4), (2, 1)]),
+            database.evaluate(&counted).unwrap()
+        );
+        assert!(matches!(
+            database.store_view(counted.clone()),
+            Err(Error::UnsupportedExpression { name, .. }) if name == "GroupBy"
+        ));
+
+        let view = database.store_view_recompute(counted).unwrap();
+        database.insert(&s, vec![(2, 'w')].into()).unwrap();
+        assert_eq!(
+            Tuples::from(vec![(1, 4), (2, 2)]),
+            database.evaluate(&view).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_product_flat() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<char>("s").unwrap();
+        let t = database.add_relation::<String>("t").unwrap();
+        database.insert(&r, vec![1, 2].into()).unwrap();
+        database.insert(&s, vec!['a', 'b'].into()).unwrap();
+        database
+            .insert(&t, vec!["x".to_string(), "y".into()].into())
+            .unwrap();
+
+        let pairs = r.builder().product(&s).flat().build();
+        let manual = r.builder().product(&s).on(|&l, &r| (l, r)).build();
+        assert_eq!(
+            database.evaluate(&manual).unwrap(),
+            database.evaluate(&pairs).unwrap()
+        );
+
+        let triples = r
+            .builder()
+            .product(s.clone())
+            .flat()
+            .product(t.clone())
+            .flat3()
+            .build();
+        let manual = r
+            .builder()
+            .product(&s)
+            .on(|&l, &r| (l, r))
+            .product(&t)
+            .on(|&(a, b), c| (a, b, c.clone()));
+        let result = database.evaluate(&triples).unwrap();
+        assert_eq!(8, result.len());
+        assert_eq!(database.evaluate(&manual.build()).unwrap(), result);
+
+        let view = database.store_view(triples).unwrap();
+        database.insert(&r, vec![3].into()).unwrap();
+        assert_eq!(12, database.evaluate(&view).unwrap().len());
+    }
+
     #[test]
     fn test_evaluate_join() {
         {
@@ -1100,6 +3036,71 @@ mod tests {
             assert!(database.evaluate(&join).is_err());
         }
     }
+    #[test]
+    fn test_evaluate_on_filter() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<(i32, i32)>("r").unwrap();
+            let s = database.add_relation::<(i32, i32)>("s").unwrap();
+            let j = r
+                .builder()
+                .with_key(|t| t.0)
+                .join(s.builder().with_key(|t| t.0))
+                .on_filter(|_, l, r| {
+                    let v = l.1 + r.1;
+                    if v < 0 {
+                        None
+                    } else {
+                        Some(v)
+                    }
+                })
+                .build();
+
+            database
+                .insert(&r, vec![(1, 4), (2, -5), (3, 1)].into())
+                .unwrap();
+            database.evaluate(&j).unwrap();
+            database
+                .insert(&s, vec![(1, -1), (1, -10), (2, 3), (3, 2)].into())
+                .unwrap();
+
+            let result = database.evaluate(&j).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![3]), result);
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let s = database.add_relation::<i32>("s").unwrap();
+            let p = r
+                .builder()
+                .product(&s)
+                .on_filter(|&l, &r| if l - r < 0 { None } else { Some(l - r) })
+                .build();
+
+            database.insert(&r, vec![1, 5].into()).unwrap();
+            database.insert(&s, vec![2, 3].into()).unwrap();
+            database.evaluate(&p).unwrap();
+            database.insert(&s, vec![4, 6].into()).unwrap();
+
+            let result = database.evaluate(&p).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![1, 2, 3]), result);
+        }
+    }
+    #[test]
+    fn test_evaluate_union_tuple() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let v = database.store_view(r.builder().union(0)).unwrap();
+        database.insert(&r, vec![1, 2].into()).unwrap();
+
+        let union = r.builder().union(42).union(1).build();
+        assert_eq!(
+            vec![1, 2, 42],
+            database.evaluate(&union).unwrap().into_tuples()
+        );
+        assert_eq!(vec![0, 1, 2], database.evaluate(&v).unwrap().into_tuples());
+    }
+
     #[test]
     fn test_evaluate_union() {
         {
@@ -1360,6 +3361,195 @@ mod tests {
         }
     }
     #[test]
+    fn test_evaluate_enumerate() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let e = r.builder().enumerate().build();
+
+            let result = database.evaluate(&e).unwrap();
+            assert_eq!(Tuples::<(usize, i32)>::from(vec![]), result);
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let e = r.builder().enumerate().build();
+
+            database.insert(&r, vec![50, 20].into()).unwrap();
+            database.evaluate(&r).unwrap();
+            database.insert(&r, vec![40, 10, 30].into()).unwrap();
+
+            let result = database.evaluate(&e).unwrap();
+            assert_eq!(
+                Tuples::<(usize, i32)>::from(vec![(0, 10), (1, 20), (2, 30), (3, 40), (4, 50)]),
+                result
+            );
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let e = r
+                .builder()
+                .enumerate()
+                .select(|&(i, _)| i % 2 == 0)
+                .project(|&(_, t)| t)
+                .build();
+
+            database.insert(&r, vec![5, 4, 3, 2, 1].into()).unwrap();
+
+            let result = database.evaluate(&e).unwrap();
+            assert_eq!(Tuples::<i32>::from(vec![1, 3, 5]), result);
+        }
+        {
+            let database = Database::new();
+            let mut dummy = Database::new();
+            let r = dummy.add_relation::<i32>("r").unwrap();
+            let e = r.builder().enumerate().build();
+            assert!(database.evaluate(&e).is_err());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_group_by() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let g = r.builder().group_by(|t| t % 3, 0, |sum, t| sum + t).build();
+
+            let result = database.evaluate(&g).unwrap();
+            assert_eq!(Tuples::<(i32, i32)>::from(vec![]), result);
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let g = r.builder().group_by(|t| t % 3, 0, |sum, t| sum + t).build();
+
+            database.insert(&r, vec![1, 2, 3].into()).unwrap();
+            database.evaluate(&r).unwrap();
+            database.insert(&r, vec![4, 5, 6].into()).unwrap();
+
+            let result = database.evaluate(&g).unwrap();
+            assert_eq!(
+                Tuples::<(i32, i32)>::from(vec![(0, 9), (1, 5), (2, 7)]),
+                result
+            );
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let g = r.builder().group_by(|t| t % 3, 0, |sum, t| sum + t).build();
+            assert!(database.store_view(g.clone()).is_err());
+
+            let v = database.store_view_recompute(g).unwrap();
+            database.insert(&r, vec![1, 2, 3].into()).unwrap();
+            assert_eq!(
+                Tuples::<(i32, i32)>::from(vec![(0, 3), (1, 1), (2, 2)]),
+                database.evaluate(&v).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_having() {
+        let mut database = Database::new();
+        let r = database.add_relation::<(char, i32)>("r").unwrap();
+        let g = r
+            .builder()
+            .group_by(|t| t.0, 0, |count, _| count + 1)
+            .having(|&count| count >= 2)
+            .build();
+        assert_eq!(
+            Tuples::<(char, i32)>::from(vec![]),
+            database.evaluate(&g).unwrap()
+        );
+
+        database
+            .insert(&r, vec![('a', 1), ('a', 2), ('b', 1), ('c', 1)].into())
+            .unwrap();
+        database.evaluate(&r).unwrap();
+        database
+            .insert(&r, vec![('b', 2), ('c', 1), ('d', 1)].into())
+            .unwrap();
+        assert_eq!(
+            Tuples::<(char, i32)>::from(vec![('a', 2), ('b', 2)]),
+            database.evaluate(&g).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_scan() {
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let s = r.builder().scan(0, |sum, t| sum + t).build();
+
+            let result = database.evaluate(&s).unwrap();
+            assert_eq!(Tuples::<(i32, i32)>::from(vec![]), result);
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let s = r.builder().scan(0, |sum, t| sum + t).build();
+
+            database.insert(&r, vec![4, 2].into()).unwrap();
+            database.evaluate(&r).unwrap();
+            database.insert(&r, vec![5, 1, 3].into()).unwrap();
+
+            let result = database.evaluate(&s).unwrap();
+            assert_eq!(
+                vec![(1, 1), (2, 3), (3, 6), (4, 10), (5, 15)],
+                result.into_tuples()
+            );
+        }
+        {
+            let mut database = Database::new();
+            let r = database.add_relation::<i32>("r").unwrap();
+            let s = r.builder().scan(0, |sum, t| sum + t).build();
+            assert!(database.store_view(s.clone()).is_err());
+
+            let v = database.store_view_recompute(s).unwrap();
+            database.insert(&r, vec![1, 2].into()).unwrap();
+            assert_eq!(
+                Tuples::<(i32, i32)>::from(vec![(1, 1), (2, 3)]),
+                database.evaluate(&v).unwrap()
+            );
+        }
+    }
+    #[test]
+    fn test_evaluate_cache() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        let plain = r
+            .builder()
+            .select(|t| t % 2 == 0)
+            .project(|t| t * 10)
+            .build();
+        let cached = r
+            .builder()
+            .select(|t| t % 2 == 0)
+            .cache(&mut database)
+            .unwrap()
+            .project(|t| t * 10)
+            .build();
+
+        assert_eq!(
+            database.evaluate(&plain).unwrap(),
+            database.evaluate(&cached).unwrap()
+        );
+
+        database.insert(&r, vec![5, 6, 7, 8].into()).unwrap();
+        assert_eq!(
+            Tuples::<i32>::from(vec![20, 40, 60, 80]),
+            database.evaluate(&cached).unwrap()
+        );
+        assert_eq!(
+            database.evaluate(&plain).unwrap(),
+            database.evaluate(&cached).unwrap()
+        );
+    }
+    #[test]
     fn test_evaluate_view() {
         {
             let mut database = Database::new();
@@ -1601,4 +3791,67 @@ mod tests {
             assert_eq!(vec![1, 2], database.evaluate(&v3).unwrap().into_tuples());
         }
     }
+
+    #[test]
+    fn test_evaluate_borrowed() {
+        thread_local! {
+            static CLONES: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+        struct Counted(i32);
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.with(|c| c.set(c.get() + 1));
+                Counted(self.0)
+            }
+        }
+
+        let clones = || CLONES.with(|c| c.replace(0));
+
+        let mut database = Database::new();
+        let r = database.add_relation::<Counted>("r").unwrap();
+        database
+            .insert(&r, (0..100).map(Counted).collect::<Vec<_>>().into())
+            .unwrap();
+        let v = database.store_view(r.clone()).unwrap();
+
+        let select = r.builder().select(|t| t.0 % 10 == 0).build();
+        let project = r.builder().project(|t| t.0 * 2).build();
+        let view_select = v.builder().select(|t| t.0 < 5).build();
+
+        // stabilize the database before counting the clones:
+        database.evaluate(&select).unwrap();
+        database.evaluate(&view_select).unwrap();
+        clones();
+
+        assert_eq!(
+            (0..10).map(|i| Counted(i * 10)).collect::<Vec<_>>(),
+            database.evaluate(&select).unwrap().into_tuples()
+        );
+        assert_eq!(10, clones());
+
+        assert_eq!(
+            (0..100).map(|i| i * 2).collect::<Vec<_>>(),
+            database.evaluate(&project).unwrap().into_tuples()
+        );
+        assert_eq!(0, clones());
+
+        assert_eq!(
+            (0..5).map(Counted).collect::<Vec<_>>(),
+            database.evaluate(&view_select).unwrap().into_tuples()
+        );
+        assert_eq!(5, clones());
+
+        // recent tuples are borrowed as well:
+        database
+            .insert(&r, (100..110).map(Counted).collect::<Vec<_>>().into())
+            .unwrap();
+        assert_eq!(
+            (0..11).map(|i| Counted(i * 10)).collect::<Vec<_>>(),
+            database.evaluate(&select).unwrap().into_tuples()
+        );
+        assert!(clones() < 100);
+    }
 }
@@ -1,3 +1,4 @@
+use super::instance::{BatchesRef, TuplesRef};
 use crate::{
     expression::{view::ViewRef, *},
     Error, Tuple, Tuples,
@@ -25,6 +26,39 @@ pub trait ExpressionExt<T: Tuple>: Expression<T> {
     where
         C: StableCollector;
 
+    /// Visits this node by a [`StableCollector`] like [`ExpressionExt::collect_stable`] but
+    /// hands every batch of stable tuples to `sink` as soon as it is collected, until
+    /// `sink` returns `false`.
+    fn stream_stable<C>(
+        &self,
+        collector: &C,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        C: StableCollector,
+    {
+        self.collect_stable(collector)
+            .map(|batches| stream(batches, sink))
+    }
+
+    /// Collects the recent tuples of this node like [`ExpressionExt::collect_recent`] but
+    /// borrows them from the database when the node is a relation or a view.
+    fn collect_recent_ref<'c, C>(&self, collector: &'c C) -> Result<TuplesRef<'c, T>, Error>
+    where
+        C: RecentCollector,
+    {
+        self.collect_recent(collector).map(TuplesRef::Owned)
+    }
+
+    /// Collects the stable tuples of this node like [`ExpressionExt::collect_stable`] but
+    /// borrows them from the database when the node is a relation or a view.
+    fn collect_stable_ref<'c, C>(&self, collector: &'c C) -> Result<BatchesRef<'c, T>, Error>
+    where
+        C: StableCollector,
+    {
+        self.collect_stable(collector).map(BatchesRef::Owned)
+    }
+
     /// Returns an iterator over the relation dependencies of this expression. These are
     /// the name of relations that show up in the receiver expression.
     fn relation_dependencies(&self) -> &[String];
@@ -53,6 +87,31 @@ where
         (*self).collect_stable(collector)
     }
 
+    fn stream_stable<C>(
+        &self,
+        collector: &C,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        C: StableCollector,
+    {
+        (*self).stream_stable(collector, sink)
+    }
+
+    fn collect_recent_ref<'c, C>(&self, collector: &'c C) -> Result<TuplesRef<'c, T>, Error>
+    where
+        C: RecentCollector,
+    {
+        (*self).collect_recent_ref(collector)
+    }
+
+    fn collect_stable_ref<'c, C>(&self, collector: &'c C) -> Result<BatchesRef<'c, T>, Error>
+    where
+        C: StableCollector,
+    {
+        (*self).collect_stable_ref(collector)
+    }
+
     fn relation_dependencies(&self) -> &[String] {
         (*self).relation_dependencies()
     }
@@ -81,6 +140,31 @@ where
         (**self).collect_stable(collector)
     }
 
+    fn stream_stable<C>(
+        &self,
+        collector: &C,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        C: StableCollector,
+    {
+        (**self).stream_stable(collector, sink)
+    }
+
+    fn collect_recent_ref<'c, C>(&self, collector: &'c C) -> Result<TuplesRef<'c, T>, Error>
+    where
+        C: RecentCollector,
+    {
+        (**self).collect_recent_ref(collector)
+    }
+
+    fn collect_stable_ref<'c, C>(&self, collector: &'c C) -> Result<BatchesRef<'c, T>, Error>
+    where
+        C: StableCollector,
+    {
+        (**self).collect_stable_ref(collector)
+    }
+
     fn relation_dependencies(&self) -> &[String] {
         (**self).relation_dependencies()
     }
@@ -108,15 +192,29 @@ pub trait RecentCollector {
     where
         T: Tuple;
 
+    /// Collects the recent tuples for a [`Param`] expression.
+    fn collect_param<T>(&self, param: &Param<T>) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple + 'static;
+
     /// Collects the recent tuples for a [`Relation`] expression.
     fn collect_relation<T>(&self, relation: &Relation<T>) -> Result<Tuples<T>, Error>
     where
         T: Tuple + 'static;
 
+    /// Collects the recent tuples for a [`Relation`] expression like
+    /// [`RecentCollector::collect_relation`] but may borrow them from the database.
+    fn collect_relation_ref<T>(&self, relation: &Relation<T>) -> Result<TuplesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        self.collect_relation(relation).map(TuplesRef::Owned)
+    }
+
     /// Collects the recent tuples for a [`Select`] expression.
     fn collect_select<T, E>(&self, select: &Select<T, E>) -> Result<Tuples<T>, Error>
     where
-        T: Tuple,
+        T: Tuple + 'static,
         E: ExpressionExt<T>;
 
     /// Collects the recent tuples for a [`Union`] expression.    
@@ -153,6 +251,36 @@ pub trait RecentCollector {
         S: Tuple,
         E: ExpressionExt<S>;
 
+    /// Collects the recent tuples for a [`FilterMap`] expression.
+    fn collect_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>;
+
+    /// Collects the recent tuples for a [`FlatProject`] expression.
+    fn collect_flat_project<S, T, E>(
+        &self,
+        flat_project: &FlatProject<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>;
+
+    /// Collects the recent tuples for a [`TryProject`] expression.
+    fn collect_try_project<S, T, E>(
+        &self,
+        try_project: &TryProject<S, T, E>,
+    ) -> Result<Tuples<T>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>;
+
     /// Collects the recent tuples for a [`Product`] expression.    
     fn collect_product<L, R, Left, Right, T>(
         &self,
@@ -178,11 +306,48 @@ pub trait RecentCollector {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>;
 
+    /// Collects the recent tuples for an [`Enumerate`] expression.
+    fn collect_enumerate<T, E>(
+        &self,
+        enumerate: &Enumerate<T, E>,
+    ) -> Result<Tuples<(usize, T)>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>;
+
+    /// Collects the recent tuples for a [`GroupBy`] expression.
+    fn collect_group_by<S, K, A, E>(
+        &self,
+        group_by: &GroupBy<S, K, A, E>,
+    ) -> Result<Tuples<(K, A)>, Error>
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: ExpressionExt<S>;
+
+    /// Collects the recent tuples for a [`Scan`] expression.
+    fn collect_scan<T, A, E>(&self, scan: &Scan<T, A, E>) -> Result<Tuples<(T, A)>, Error>
+    where
+        T: Tuple,
+        A: Tuple,
+        E: ExpressionExt<T>;
+
     /// Collects the recent tuples for a [`View`] expression.
     fn collect_view<T, E>(&self, view: &View<T, E>) -> Result<Tuples<T>, Error>
     where
         T: Tuple + 'static,
         E: ExpressionExt<T> + 'static;
+
+    /// Collects the recent tuples for a [`View`] expression like
+    /// [`RecentCollector::collect_view`] but may borrow them from the database.
+    fn collect_view_ref<T, E>(&self, view: &View<T, E>) -> Result<TuplesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+    {
+        self.collect_view(view).map(TuplesRef::Owned)
+    }
 }
 
 /// Is the trait of objects that implement the logic for collecting the stable tuples of
@@ -203,17 +368,47 @@ pub trait StableCollector {
     where
         T: Tuple;
 
+    /// Collects the stable tuples for a [`Param`] expression.
+    fn collect_param<T>(&self, param: &Param<T>) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple + 'static;
+
     /// Collects the stable tuples for a [`Relation`] expression.            
     fn collect_relation<T>(&self, relation: &Relation<T>) -> Result<Vec<Tuples<T>>, Error>
     where
         T: Tuple + 'static;
 
+    /// Collects the stable tuples for a [`Relation`] expression like
+    /// [`StableCollector::collect_relation`] but may borrow them from the database.
+    fn collect_relation_ref<T>(&self, relation: &Relation<T>) -> Result<BatchesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        self.collect_relation(relation).map(BatchesRef::Owned)
+    }
+
     /// Collects the stable tuples for a [`Select`] expression.            
     fn collect_select<T, E>(&self, select: &Select<T, E>) -> Result<Vec<Tuples<T>>, Error>
     where
-        T: Tuple,
+        T: Tuple + 'static,
         E: ExpressionExt<T>;
 
+    /// Collects the stable tuples for a [`Select`] expression like
+    /// [`StableCollector::collect_select`] but hands every batch to `sink` as soon as it is
+    /// collected, until `sink` returns `false`.
+    fn stream_select<T, E>(
+        &self,
+        select: &Select<T, E>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T>,
+    {
+        self.collect_select(select)
+            .map(|batches| stream(batches, sink))
+    }
+
     /// Collects the stable tuples for a [`Union`] expression.            
     fn collect_union<T, L, R>(&self, union: &Union<T, L, R>) -> Result<Vec<Tuples<T>>, Error>
     where
@@ -221,6 +416,23 @@ pub trait StableCollector {
         L: ExpressionExt<T>,
         R: ExpressionExt<T>;
 
+    /// Collects the stable tuples for a [`Union`] expression like
+    /// [`StableCollector::collect_union`] but hands every batch to `sink` as soon as it is
+    /// collected, until `sink` returns `false`.
+    fn stream_union<T, L, R>(
+        &self,
+        union: &Union<T, L, R>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        L: ExpressionExt<T>,
+        R: ExpressionExt<T>,
+    {
+        self.collect_union(union)
+            .map(|batches| stream(batches, sink))
+    }
+
     /// Collects the stable tuples for an [`Intersect`] expression.            
     fn collect_intersect<T, L, R>(
         &self,
@@ -248,6 +460,70 @@ pub trait StableCollector {
         S: Tuple,
         E: ExpressionExt<S>;
 
+    /// Collects the stable tuples for a [`Project`] expression like
+    /// [`StableCollector::collect_project`] but hands every batch to `sink` as soon as it is
+    /// collected, until `sink` returns `false`.
+    fn stream_project<S, T, E>(
+        &self,
+        project: &Project<S, T, E>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        self.collect_project(project)
+            .map(|batches| stream(batches, sink))
+    }
+
+    /// Collects the stable tuples for a [`FilterMap`] expression.
+    fn collect_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>;
+
+    /// Collects the stable tuples for a [`FilterMap`] expression like
+    /// [`StableCollector::collect_filter_map`] but hands every batch to `sink` as soon as it
+    /// is collected, until `sink` returns `false`.
+    fn stream_filter_map<S, T, E>(
+        &self,
+        filter_map: &FilterMap<S, T, E>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>,
+    {
+        self.collect_filter_map(filter_map)
+            .map(|batches| stream(batches, sink))
+    }
+
+    /// Collects the stable tuples for a [`FlatProject`] expression.
+    fn collect_flat_project<S, T, E>(
+        &self,
+        flat_project: &FlatProject<S, T, E>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>;
+
+    /// Collects the stable tuples for a [`TryProject`] expression.
+    fn collect_try_project<S, T, E>(
+        &self,
+        try_project: &TryProject<S, T, E>,
+    ) -> Result<Vec<Tuples<T>>, Error>
+    where
+        T: Tuple,
+        S: Tuple,
+        E: ExpressionExt<S>;
+
     /// Collects the stable tuples for a [`Product`] expression.            
     fn collect_product<L, R, Left, Right, T>(
         &self,
@@ -260,6 +536,25 @@ pub trait StableCollector {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>;
 
+    /// Collects the stable tuples for a [`Product`] expression like
+    /// [`StableCollector::collect_product`] but hands every batch to `sink` as soon as it is
+    /// collected, until `sink` returns `false`.
+    fn stream_product<L, R, Left, Right, T>(
+        &self,
+        product: &Product<L, R, Left, Right, T>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: ExpressionExt<L>,
+        Right: ExpressionExt<R>,
+    {
+        self.collect_product(product)
+            .map(|batches| stream(batches, sink))
+    }
+
     /// Collects the stable tuples for a [`Join`] expression.            
     fn collect_join<K, L, R, Left, Right, T>(
         &self,
@@ -273,15 +568,80 @@ pub trait StableCollector {
         Left: ExpressionExt<L>,
         Right: ExpressionExt<R>;
 
+    /// Collects the stable tuples for a [`Join`] expression like
+    /// [`StableCollector::collect_join`] but hands every batch to `sink` as soon as it is
+    /// collected, until `sink` returns `false`.
+    fn stream_join<K, L, R, Left, Right, T>(
+        &self,
+        join: &Join<K, L, R, Left, Right, T>,
+        sink: &mut dyn FnMut(Tuples<T>) -> bool,
+    ) -> Result<(), Error>
+    where
+        K: Tuple,
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: ExpressionExt<L>,
+        Right: ExpressionExt<R>,
+    {
+        self.collect_join(join).map(|batches| stream(batches, sink))
+    }
+
+    /// Collects the stable tuples for an [`Enumerate`] expression.
+    fn collect_enumerate<T, E>(
+        &self,
+        enumerate: &Enumerate<T, E>,
+    ) -> Result<Vec<Tuples<(usize, T)>>, Error>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>;
+
+    /// Collects the stable tuples for a [`GroupBy`] expression.
+    fn collect_group_by<S, K, A, E>(
+        &self,
+        group_by: &GroupBy<S, K, A, E>,
+    ) -> Result<Vec<Tuples<(K, A)>>, Error>
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: ExpressionExt<S>;
+
+    /// Collects the stable tuples for a [`Scan`] expression.
+    fn collect_scan<T, A, E>(&self, scan: &Scan<T, A, E>) -> Result<Vec<Tuples<(T, A)>>, Error>
+    where
+        T: Tuple,
+        A: Tuple,
+        E: ExpressionExt<T>;
+
     /// Collects the stable tuples for a [`View`] expression.            
     fn collect_view<T, E>(&self, view: &View<T, E>) -> Result<Vec<Tuples<T>>, Error>
     where
         T: Tuple + 'static,
         E: ExpressionExt<T> + 'static;
+
+    /// Collects the stable tuples for a [`View`] expression like
+    /// [`StableCollector::collect_view`] but may borrow them from the database.
+    fn collect_view_ref<T, E>(&self, view: &View<T, E>) -> Result<BatchesRef<'_, T>, Error>
+    where
+        T: Tuple + 'static,
+        E: ExpressionExt<T> + 'static,
+    {
+        self.collect_view(view).map(BatchesRef::Owned)
+    }
+}
+
+/// Hands `batches` to `sink` in order until `sink` returns `false`.
+fn stream<T: Tuple>(batches: Vec<Tuples<T>>, sink: &mut dyn FnMut(Tuples<T>) -> bool) {
+    for batch in batches {
+        if !sink(batch) {
+            break;
+        }
+    }
 }
 
 mod r#impl {
-    use super::{ExpressionExt, RecentCollector, StableCollector};
+    use super::{BatchesRef, ExpressionExt, RecentCollector, StableCollector, TuplesRef};
     use crate::{
         expression::view::{View, ViewRef},
         Error, Tuple, Tuples,
@@ -296,14 +656,45 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_view(&self)
+            collector.collect_view(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_view(&self)
+            collector.collect_view(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            // clone the batches one at a time instead of all at once:
+            for batch in collector.collect_view_ref(self)?.iter() {
+                if !sink(batch.clone()) {
+                    break;
+                }
+            }
+            Ok(())
+        }
+
+        fn collect_recent_ref<'c, C>(&self, collector: &'c C) -> Result<TuplesRef<'c, T>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_view_ref(self)
+        }
+
+        fn collect_stable_ref<'c, C>(&self, collector: &'c C) -> Result<BatchesRef<'c, T>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_view_ref(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -327,14 +718,14 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_intersect(&self)
+            collector.collect_intersect(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_intersect(&self)
+            collector.collect_intersect(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -358,14 +749,25 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_union(&self)
+            collector.collect_union(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_union(&self)
+            collector.collect_union(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            collector.stream_union(self, sink)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -389,14 +791,14 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_difference(&self)
+            collector.collect_difference(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_difference(&self)
+            collector.collect_difference(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -408,6 +810,37 @@ mod r#impl {
         }
     }
 
+    use crate::expression::Complement;
+
+    impl<T, E, U> ExpressionExt<T> for Complement<T, E, U>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+        U: ExpressionExt<T>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<T>, Error>
+        where
+            C: RecentCollector,
+        {
+            self.difference().collect_recent(collector)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
+        where
+            C: StableCollector,
+        {
+            self.difference().collect_stable(collector)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.difference().relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.difference().view_deps()
+        }
+    }
+
     use crate::expression::Empty;
 
     impl<T> ExpressionExt<T> for Empty<T>
@@ -418,14 +851,14 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_empty(&self)
+            collector.collect_empty(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_empty(&self)
+            collector.collect_empty(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -437,6 +870,203 @@ mod r#impl {
         }
     }
 
+    use crate::expression::Enumerate;
+
+    impl<T, E> ExpressionExt<(usize, T)> for Enumerate<T, E>
+    where
+        T: Tuple,
+        E: ExpressionExt<T>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<(usize, T)>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_enumerate(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<(usize, T)>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_enumerate(self)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.view_deps()
+        }
+    }
+
+    use crate::expression::GroupBy;
+
+    impl<S, K, A, E> ExpressionExt<(K, A)> for GroupBy<S, K, A, E>
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: ExpressionExt<S>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<(K, A)>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_group_by(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<(K, A)>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_group_by(self)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.view_deps()
+        }
+    }
+
+    use crate::expression::Scan;
+
+    impl<T, A, E> ExpressionExt<(T, A)> for Scan<T, A, E>
+    where
+        T: Tuple,
+        A: Tuple,
+        E: ExpressionExt<T>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<(T, A)>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_scan(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<(T, A)>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_scan(self)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.view_deps()
+        }
+    }
+
+    use crate::expression::FilterMap;
+
+    impl<S, T, E> ExpressionExt<T> for FilterMap<S, T, E>
+    where
+        S: Tuple,
+        T: Tuple,
+        E: ExpressionExt<S>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<T>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_filter_map(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_filter_map(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            collector.stream_filter_map(self, sink)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.view_deps()
+        }
+    }
+
+    use crate::expression::FlatProject;
+
+    impl<S, T, E> ExpressionExt<T> for FlatProject<S, T, E>
+    where
+        S: Tuple,
+        T: Tuple,
+        E: ExpressionExt<S>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<T>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_flat_project(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_flat_project(self)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.view_deps()
+        }
+    }
+
+    use crate::expression::TryProject;
+
+    impl<S, T, E> ExpressionExt<T> for TryProject<S, T, E>
+    where
+        S: Tuple,
+        T: Tuple,
+        E: ExpressionExt<S>,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<T>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_try_project(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_try_project(self)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            self.relation_deps()
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            self.view_deps()
+        }
+    }
+
     use crate::expression::Full;
 
     impl<T> ExpressionExt<T> for Full<T>
@@ -447,14 +1077,14 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_full(&self)
+            collector.collect_full(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_full(&self)
+            collector.collect_full(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -481,14 +1111,25 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_join(&self)
+            collector.collect_join(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_join(&self)
+            collector.collect_join(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            collector.stream_join(self, sink)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -511,9 +1152,11 @@ mod r#impl {
                 Mono::Full(exp) => exp.collect_recent(collector),
                 Mono::Empty(exp) => exp.collect_recent(collector),
                 Mono::Singleton(exp) => exp.collect_recent(collector),
+                Mono::Param(exp) => exp.collect_recent(collector),
                 Mono::Relation(exp) => exp.collect_recent(collector),
                 Mono::Select(exp) => exp.collect_recent(collector),
                 Mono::Project(exp) => exp.collect_recent(collector),
+                Mono::FilterMap(exp) => exp.collect_recent(collector),
                 Mono::Union(exp) => exp.collect_recent(collector),
                 Mono::Intersect(exp) => exp.collect_recent(collector),
                 Mono::Difference(exp) => exp.collect_recent(collector),
@@ -530,9 +1173,11 @@ mod r#impl {
                 Mono::Full(exp) => exp.collect_stable(collector),
                 Mono::Empty(exp) => exp.collect_stable(collector),
                 Mono::Singleton(exp) => exp.collect_stable(collector),
+                Mono::Param(exp) => exp.collect_stable(collector),
                 Mono::Relation(exp) => exp.collect_stable(collector),
                 Mono::Select(exp) => exp.collect_stable(collector),
                 Mono::Project(exp) => exp.collect_stable(collector),
+                Mono::FilterMap(exp) => exp.collect_stable(collector),
                 Mono::Union(exp) => exp.collect_stable(collector),
                 Mono::Intersect(exp) => exp.collect_stable(collector),
                 Mono::Difference(exp) => exp.collect_stable(collector),
@@ -542,14 +1187,64 @@ mod r#impl {
             }
         }
 
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            match self {
+                Mono::Full(exp) => exp.stream_stable(collector, sink),
+                Mono::Empty(exp) => exp.stream_stable(collector, sink),
+                Mono::Singleton(exp) => exp.stream_stable(collector, sink),
+                Mono::Param(exp) => exp.stream_stable(collector, sink),
+                Mono::Relation(exp) => exp.stream_stable(collector, sink),
+                Mono::Select(exp) => exp.stream_stable(collector, sink),
+                Mono::Project(exp) => exp.stream_stable(collector, sink),
+                Mono::FilterMap(exp) => exp.stream_stable(collector, sink),
+                Mono::Union(exp) => exp.stream_stable(collector, sink),
+                Mono::Intersect(exp) => exp.stream_stable(collector, sink),
+                Mono::Difference(exp) => exp.stream_stable(collector, sink),
+                Mono::Product(exp) => exp.stream_stable(collector, sink),
+                Mono::Join(exp) => exp.stream_stable(collector, sink),
+                Mono::View(exp) => exp.stream_stable(collector, sink),
+            }
+        }
+
+        fn collect_recent_ref<'c, C>(&self, collector: &'c C) -> Result<TuplesRef<'c, T>, Error>
+        where
+            C: RecentCollector,
+        {
+            match self {
+                Mono::Relation(exp) => exp.collect_recent_ref(collector),
+                Mono::View(exp) => exp.collect_recent_ref(collector),
+                _ => self.collect_recent(collector).map(TuplesRef::Owned),
+            }
+        }
+
+        fn collect_stable_ref<'c, C>(&self, collector: &'c C) -> Result<BatchesRef<'c, T>, Error>
+        where
+            C: StableCollector,
+        {
+            match self {
+                Mono::Relation(exp) => exp.collect_stable_ref(collector),
+                Mono::View(exp) => exp.collect_stable_ref(collector),
+                _ => self.collect_stable(collector).map(BatchesRef::Owned),
+            }
+        }
+
         fn relation_dependencies(&self) -> &[String] {
             match self {
                 Mono::Full(exp) => exp.relation_dependencies(),
                 Mono::Empty(exp) => exp.relation_dependencies(),
                 Mono::Singleton(exp) => exp.relation_dependencies(),
+                Mono::Param(exp) => exp.relation_dependencies(),
                 Mono::Relation(exp) => exp.relation_dependencies(),
                 Mono::Select(exp) => exp.relation_dependencies(),
                 Mono::Project(exp) => exp.relation_dependencies(),
+                Mono::FilterMap(exp) => exp.relation_dependencies(),
                 Mono::Union(exp) => exp.relation_dependencies(),
                 Mono::Intersect(exp) => exp.relation_dependencies(),
                 Mono::Difference(exp) => exp.relation_dependencies(),
@@ -564,9 +1259,11 @@ mod r#impl {
                 Mono::Full(exp) => exp.view_dependencies(),
                 Mono::Empty(exp) => exp.view_dependencies(),
                 Mono::Singleton(exp) => exp.view_dependencies(),
+                Mono::Param(exp) => exp.view_dependencies(),
                 Mono::Relation(exp) => exp.view_dependencies(),
                 Mono::Select(exp) => exp.view_dependencies(),
                 Mono::Project(exp) => exp.view_dependencies(),
+                Mono::FilterMap(exp) => exp.view_dependencies(),
                 Mono::Union(exp) => exp.view_dependencies(),
                 Mono::Intersect(exp) => exp.view_dependencies(),
                 Mono::Difference(exp) => exp.view_dependencies(),
@@ -591,14 +1288,25 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_product(&self)
+            collector.collect_product(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_product(&self)
+            collector.collect_product(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            collector.stream_product(self, sink)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -622,14 +1330,25 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_project(&self)
+            collector.collect_project(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_project(&self)
+            collector.collect_project(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            collector.stream_project(self, sink)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -651,14 +1370,45 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_relation(&self)
+            collector.collect_relation(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_relation(&self)
+            collector.collect_relation(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            // clone the batches one at a time instead of all at once:
+            for batch in collector.collect_relation_ref(self)?.iter() {
+                if !sink(batch.clone()) {
+                    break;
+                }
+            }
+            Ok(())
+        }
+
+        fn collect_recent_ref<'c, C>(&self, collector: &'c C) -> Result<TuplesRef<'c, T>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_relation_ref(self)
+        }
+
+        fn collect_stable_ref<'c, C>(&self, collector: &'c C) -> Result<BatchesRef<'c, T>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_relation_ref(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -674,21 +1424,32 @@ mod r#impl {
 
     impl<T, E> ExpressionExt<T> for Select<T, E>
     where
-        T: Tuple,
+        T: Tuple + 'static,
         E: ExpressionExt<T>,
     {
         fn collect_recent<C>(&self, collector: &C) -> Result<Tuples<T>, Error>
         where
             C: RecentCollector,
         {
-            collector.collect_select(&self)
+            collector.collect_select(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_select(&self)
+            collector.collect_select(self)
+        }
+
+        fn stream_stable<C>(
+            &self,
+            collector: &C,
+            sink: &mut dyn FnMut(Tuples<T>) -> bool,
+        ) -> Result<(), Error>
+        where
+            C: StableCollector,
+        {
+            collector.stream_select(self, sink)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -710,14 +1471,43 @@ mod r#impl {
         where
             C: RecentCollector,
         {
-            collector.collect_singleton(&self)
+            collector.collect_singleton(self)
+        }
+
+        fn collect_stable<C>(&self, collector: &C) -> Result<Vec<crate::Tuples<T>>, Error>
+        where
+            C: StableCollector,
+        {
+            collector.collect_singleton(self)
+        }
+
+        fn relation_dependencies(&self) -> &[String] {
+            &[]
+        }
+
+        fn view_dependencies(&self) -> &[ViewRef] {
+            &[]
+        }
+    }
+
+    use crate::expression::Param;
+
+    impl<T> ExpressionExt<T> for Param<T>
+    where
+        T: Tuple + 'static,
+    {
+        fn collect_recent<C>(&self, collector: &C) -> Result<crate::Tuples<T>, Error>
+        where
+            C: RecentCollector,
+        {
+            collector.collect_param(self)
         }
 
         fn collect_stable<C>(&self, collector: &C) -> Result<Vec<crate::Tuples<T>>, Error>
         where
             C: StableCollector,
         {
-            collector.collect_singleton(&self)
+            collector.collect_param(self)
         }
 
         fn relation_dependencies(&self) -> &[String] {
@@ -25,31 +25,60 @@ pub(crate) fn gallop<T>(mut slice: &[T], mut cmp: impl FnMut(&T) -> bool) -> &[T
     slice
 }
 
+/// For two sorted slices, removes the elements of `items` that appear in `other`.
+#[inline(always)]
+pub(crate) fn retain_absent<T: Ord>(items: &mut Vec<T>, other: &[T]) {
+    let mut slice = other;
+    items.retain(|x| {
+        slice = gallop(slice, |y| y < x);
+        slice.is_empty() || &slice[0] != x
+    });
+}
+
+/// For a sorted `slice`, returns the contiguous sub-slice of the elements for which `range`
+/// returns [`Ordering::Equal`], assuming that `range` is monotone over `slice`.
+///
+/// [`Ordering::Equal`]: std::cmp::Ordering::Equal
+#[inline(always)]
+pub(crate) fn range_helper<T>(
+    slice: &[T],
+    mut range: impl FnMut(&T) -> std::cmp::Ordering,
+) -> &[T] {
+    use std::cmp::Ordering;
+
+    let start = gallop(slice, |t| range(t) == Ordering::Less);
+    let end = gallop(start, |t| range(t) == Ordering::Equal);
+    &start[..start.len() - end.len()]
+}
+
 /// Applies `result` on elements of `slice`.
 #[inline(always)]
 pub(crate) fn project_helper<T>(slice: &[T], mut result: impl FnMut(&T)) {
-    let slice = &slice[..];
     for tuple in slice {
         result(tuple);
     }
 }
 
-/// Applies `result` on every pair of `left` and `right` slices.
+/// Applies `result` on every pair of `left` and `right` slices until `result` returns
+/// `false`.
 #[inline(always)]
-pub(crate) fn product_helper<L, R>(left: &[L], right: &[R], mut result: impl FnMut(&L, &R)) {
-    let left = &left[..];
-    let right = &right[..];
-
+pub(crate) fn product_helper<L, R>(
+    left: &[L],
+    right: &[R],
+    mut result: impl FnMut(&L, &R) -> bool,
+) {
     for l in left {
         for r in right {
-            result(&l, &r);
+            if !result(l, r) {
+                return;
+            }
         }
     }
 }
 
 /// For two slices `left` and `right` that are sorted by the first element of their tuples,
 /// applies `result` on those pairs of `left` and `right` that agree on their first
-/// element as the key.
+/// element as the key, until `result` returns `false`.
 ///
 /// **Note**: `join_helper` is directly borrowed from [`datafrog`].
 ///
@@ -58,10 +87,10 @@ pub(crate) fn product_helper<L, R>(left: &[L], right: &[R], mut result: impl FnM
 pub(crate) fn join_helper<Key: Ord, L, R>(
     left: &[(Key, L)],
     right: &[(Key, R)],
-    mut result: impl FnMut(&Key, &L, &R),
+    mut result: impl FnMut(&Key, &L, &R) -> bool,
 ) {
-    let mut slice1 = &left[..];
-    let mut slice2 = &right[..];
+    let mut slice1 = left;
+    let mut slice2 = right;
 
     while !slice1.is_empty() && !slice2.is_empty() {
         use std::cmp::Ordering;
@@ -74,7 +103,9 @@ pub(crate) fn join_helper<Key: Ord, L, R>(
 
                 for index1 in 0..count1 {
                     for item in slice2.iter().take(count2) {
-                        result(&slice1[0].0, &slice1[index1].1, &item.1);
+                        if !result(&slice1[0].0, &slice1[index1].1, &item.1) {
+                            return;
+                        }
                     }
                 }
 
@@ -86,12 +117,69 @@ pub(crate) fn join_helper<Key: Ord, L, R>(
     }
 }
 
+/// For two slices `left` and `right` that are sorted by the first element of their tuples,
+/// applies `result` on the pairs of `left` and `right` that agree on their key like
+/// [`join_helper`] and on every element of `left` whose key appears in no element of `right`
+/// with `None`, until `result` returns `false`.
+#[inline(always)]
+pub(crate) fn outer_join_helper<Key: Ord, L, R>(
+    left: &[(Key, L)],
+    right: &[(Key, R)],
+    mut result: impl FnMut(&Key, &L, Option<&R>) -> bool,
+) {
+    let mut slice1 = left;
+    let mut slice2 = right;
+
+    while !slice1.is_empty() {
+        let count1 = slice1.iter().take_while(|x| x.0 == slice1[0].0).count();
+        slice2 = gallop(slice2, |x| x.0 < slice1[0].0);
+        let count2 = slice2.iter().take_while(|x| x.0 == slice1[0].0).count();
+
+        for index1 in 0..count1 {
+            if count2 == 0 && !result(&slice1[0].0, &slice1[index1].1, None) {
+                return;
+            }
+            for item in slice2.iter().take(count2) {
+                if !result(&slice1[0].0, &slice1[index1].1, Some(&item.1)) {
+                    return;
+                }
+            }
+        }
+
+        slice1 = &slice1[count1..];
+        slice2 = &slice2[count2..];
+    }
+}
+
+/// For a slice `left` and the slices of `right`, all sorted by the first element of their
+/// tuples, applies `result` on every element of `left` together with whether its key appears
+/// in some slice of `right`, until `result` returns `false`.
+#[inline(always)]
+pub(crate) fn semi_join_helper<Key: Ord, L, R>(
+    left: &[(Key, L)],
+    right: &[&[(Key, R)]],
+    mut result: impl FnMut(&Key, &L, bool) -> bool,
+) {
+    let mut right = right.to_vec();
+
+    for (key, tuple) in left {
+        let mut found = false;
+        for slice in right.iter_mut() {
+            *slice = gallop(slice, |x| &x.0 < key);
+            found = found || slice.first().map_or(false, |x| &x.0 == key);
+        }
+        if !result(key, tuple, found) {
+            return;
+        }
+    }
+}
+
 /// For two sorted slices `left` and `right`, applies `result` on those elements of `left` and `right`
 /// that are equal.
 #[inline(always)]
 pub(crate) fn intersect_helper<T: Ord>(left: &[T], right: &[T], mut result: impl FnMut(&T)) {
-    let mut left = &left[..];
-    let mut right = &right[..];
+    let mut left = left;
+    let mut right = right;
 
     while !left.is_empty() && !right.is_empty() {
         use std::cmp::Ordering;
@@ -112,7 +200,6 @@ pub(crate) fn intersect_helper<T: Ord>(left: &[T], right: &[T], mut result: impl
 /// in none of the slices of `right`.
 #[inline(always)]
 pub(crate) fn diff_helper<T: Ord>(left: &[T], right: &[&[T]], mut result: impl FnMut(&T)) {
-    let left = &left[..];
     let mut right = right.iter().map(|sl| &sl[..]).collect::<Vec<&[T]>>();
 
     for tuple in left {
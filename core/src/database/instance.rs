@@ -1,11 +1,23 @@
-use super::{evaluate, expression_ext::ExpressionExt, helpers::gallop, Database};
-use crate::{expression::Expression, Error, Tuple};
-use std::any::Any;
-use std::{
-    cell::{Ref, RefCell},
-    ops::Deref,
-    rc::Rc,
+#[cfg(feature = "spill")]
+use super::spill::{MappedBatch, Spill, SpilledBatch};
+use super::{
+    diff::RelationDiff,
+    evaluate,
+    expression_ext::ExpressionExt,
+    helpers::{diff_helper, intersect_helper, join_helper, retain_absent},
+    Database,
+};
+use crate::{
+    expression::Expression,
+    shared::{Atomic, Lock, Ref, Shared},
+    Error, MaybeSync, Tuple,
 };
+use std::any::Any;
+use std::{borrow::Cow, cmp::Ordering, ops::Deref};
+
+/// Is the number of tuples below which the last batch of the to be added tuples of an
+/// [`Instance`] absorbs the tuples of [`Instance::insert_slice`].
+pub(super) const SMALL_BATCH: usize = 1024;
 
 /// Is a wrapper around a vector of tuples. As an invariant, the content of [`Tuples`] is sorted.
 ///
@@ -27,26 +39,264 @@ impl<T: Tuple, I: IntoIterator<Item = T>> From<I> for Tuples<T> {
     }
 }
 
+impl<T: Tuple> std::iter::FromIterator<T> for Tuples<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iterator: I) -> Self {
+        iterator.into()
+    }
+}
+
 impl<T: Tuple> Tuples<T> {
     /// Merges the instances of the reciver with `other` and returns a new [`Tuples`]
     /// instance.
     pub(crate) fn merge(self, other: Self) -> Self {
         let mut tuples = Vec::with_capacity(self.items.len() + other.items.len());
-        tuples.extend(self.items.into_iter());
-        tuples.extend(other.items.into_iter());
+        tuples.extend(self.items);
+        tuples.extend(other.items);
+        tuples.into()
+    }
+
+    /// Merges all `batches` into a single [`Tuples`] instance.
+    ///
+    /// **Note**: unlike folding `batches` with repeated merges, which allocates and sorts
+    /// the accumulated tuples once per batch (`O(k·n·log n)` for `k` batches of `n` tuples
+    /// in total), `merge_all` reserves the capacity for all tuples at once and sorts them
+    /// only once (`O(n·log n)`).
+    pub fn merge_all<I>(batches: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let batches: Vec<Self> = batches.into_iter().collect();
+        let mut tuples = Vec::with_capacity(batches.iter().map(|b| b.items.len()).sum());
+        for batch in batches {
+            tuples.extend(batch.items);
+        }
         tuples.into()
     }
 
+    /// Merges the tuples of `other` into the receiver, keeping the receiver sorted and
+    /// deduplicated.
+    ///
+    /// **Note**: instead of sorting the concatenated tuples, `extend_sorted` relies on both
+    /// instances being sorted and merges them in a single linear pass into one allocation
+    /// that fits the tuples of both. It is meant for accumulating a [`Tuples`] instance
+    /// from a sequence of sorted chunks.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Tuples;
+    ///
+    /// let mut tuples = Tuples::from(vec![1, 4, 7]);
+    /// tuples.extend_sorted(vec![2, 4, 8].into());
+    ///
+    /// assert_eq!(vec![1, 2, 4, 7, 8], tuples.into_tuples());
+    /// ```
+    pub fn extend_sorted(&mut self, other: Self) {
+        if other.items.is_empty() {
+            return;
+        }
+        if self.items.is_empty() {
+            self.items = other.items;
+            return;
+        }
+
+        let mut tuples = Vec::with_capacity(self.items.len() + other.items.len());
+        let mut left = std::mem::take(&mut self.items).into_iter().peekable();
+        let mut right = other.items.into_iter().peekable();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => match l.cmp(r) {
+                    Ordering::Less => left.next(),
+                    Ordering::Greater => right.next(),
+                    Ordering::Equal => {
+                        right.next();
+                        left.next()
+                    }
+                },
+                (Some(_), None) => left.next(),
+                (None, Some(_)) => right.next(),
+                (None, None) => break,
+            };
+            tuples.extend(next);
+        }
+        self.items = tuples;
+    }
+
     /// Returns an immutable reference to the tuples of the receiver.
     pub fn items(&self) -> &[T] {
         &self.items
     }
 
+    /// Returns the smallest tuple of the receiver or `None` if the receiver is empty.
+    ///
+    /// **Note**: since the tuples are sorted, `min` is the same as `first` and takes
+    /// constant time.
+    #[inline(always)]
+    pub fn min(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    /// Returns the largest tuple of the receiver or `None` if the receiver is empty.
+    ///
+    /// **Note**: since the tuples are sorted, `max` is the same as `last` and takes
+    /// constant time.
+    #[inline(always)]
+    pub fn max(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    /// Returns the tuple at position `index` in the sorted order of the receiver or `None`
+    /// if `index` is out of bounds.
+    #[inline(always)]
+    pub fn nth(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// Creates a new [`Tuples`] instance from `items` without sorting and deduplicating them.
+    ///
+    /// **Note**: the caller must guarantee that `items` is sorted and contains no duplicates;
+    /// otherwise, the result violates the invariant of [`Tuples`], and operations on it
+    /// produce incorrect results. The contract is only checked in debug builds, where a
+    /// violation panics. Use [`Tuples::eq_as_set`] to compare instances whose invariant may
+    /// not hold.
+    pub fn from_sorted_unchecked(items: Vec<T>) -> Self {
+        let tuples = Tuples { items };
+        tuples.debug_assert_sorted();
+        tuples
+    }
+
+    /// Creates a new [`Tuples`] instance from `items` that may be unsorted and contain
+    /// duplicates, e.g., the tuples of an unordered evaluation. The result does not satisfy
+    /// the invariant of [`Tuples`] and must only be consumed as a vector.
+    pub(super) fn unordered(items: Vec<T>) -> Self {
+        Tuples { items }
+    }
+
+    /// Panics if the tuples of the receiver are not sorted or contain duplicates. The
+    /// check is only performed in debug builds.
+    #[inline(always)]
+    pub(super) fn debug_assert_sorted(&self) {
+        #[cfg(debug_assertions)]
+        if let Some(i) = self.items.windows(2).position(|w| w[0] >= w[1]) {
+            panic!(
+                "tuples are not sorted and deduplicated: {:?} at index {} is followed by {:?}",
+                self.items[i],
+                i,
+                self.items[i + 1]
+            );
+        }
+    }
+
+    /// Returns true if the receiver and `other` contain the same set of tuples, regardless
+    /// of the order and multiplicity of their tuples. Unlike `==`, which compares the
+    /// underlying vectors element-wise, `eq_as_set` is correct even for instances whose
+    /// tuples are unsorted, e.g., instances that are created by
+    /// [`Tuples::from_sorted_unchecked`] from unsorted vectors in release builds.
+    pub fn eq_as_set(&self, other: &Self) -> bool {
+        fn normalize<T: Tuple>(items: &[T]) -> Cow<'_, [T]> {
+            if items.windows(2).all(|w| w[0] < w[1]) {
+                return Cow::Borrowed(items);
+            }
+            let mut items = items.to_vec();
+            items.sort_unstable();
+            items.dedup();
+            Cow::Owned(items)
+        }
+        normalize(&self.items) == normalize(&other.items)
+    }
+
+    /// Removes every tuple of the receiver for which `same` returns true when applied to the
+    /// first tuple of its run and the tuple itself, keeping the first tuple of every run of
+    /// consecutive tuples that `same` considers equal (like `Vec::dedup_by`).
+    ///
+    /// **Note**: `same` must be consistent with the order of tuples, that is, the tuples
+    /// that it considers equal must be adjacent in their sorted order, such as tuples with
+    /// equal prefixes; otherwise, equal tuples that are not adjacent are retained.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Tuples;
+    ///
+    /// let mut tuples = Tuples::from(vec![(1, 'b'), (2, 'c'), (1, 'a')]);
+    /// tuples.dedup_by(|first, next| first.0 == next.0);
+    ///
+    /// assert_eq!(vec![(1, 'a'), (2, 'c')], tuples.into_tuples());
+    /// ```
+    pub fn dedup_by(&mut self, mut same: impl FnMut(&T, &T) -> bool) {
+        self.items.dedup_by(|next, first| same(first, next));
+    }
+
     /// Consumes the receiver and returns the underlying (sorted) vector of tuples.
     #[inline(always)]
     pub fn into_tuples(self) -> Vec<T> {
         self.items
     }
+
+    /// Consumes the receiver, applies `f` to every tuple and returns the result in a new
+    /// [`Tuples`] instance. Since `f` is not required to preserve the order of tuples, the
+    /// result is sorted and deduplicated again.
+    pub fn map<U, F>(self, f: F) -> Tuples<U>
+    where
+        U: Tuple,
+        F: FnMut(T) -> U,
+    {
+        self.items.into_iter().map(f).collect()
+    }
+
+    /// Consumes the receiver, applies `f` to every tuple and returns the result in a new
+    /// [`Tuples`] instance without sorting it again.
+    ///
+    /// **Note**: the caller must guarantee that `f` is strictly monotone, i.e., for every two
+    /// tuples `a < b` of the receiver, `f(a) < f(b)`; otherwise, the result violates the
+    /// invariant of [`Tuples`], and operations on it produce incorrect results. The contract
+    /// is only checked in debug builds.
+    pub fn map_monotone_unchecked<U, F>(self, f: F) -> Tuples<U>
+    where
+        U: Tuple,
+        F: FnMut(T) -> U,
+    {
+        let items: Vec<U> = self.items.into_iter().map(f).collect();
+        let tuples = Tuples { items };
+        tuples.debug_assert_sorted();
+        tuples
+    }
+}
+
+impl<K: Tuple, V: Tuple> Tuples<(K, V)> {
+    /// Joins the receiver with `other` on the first elements of their pairs as keys and
+    /// returns the result of applying `combine` on the key and the second elements of every
+    /// pair of matching tuples, like a [`Join`] expression that is evaluated directly on
+    /// [`Tuples`] without a database.
+    ///
+    /// [`Join`]: crate::expression::Join
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Tuples;
+    ///
+    /// let ages = Tuples::from(vec![("alice", 30), ("bob", 25)]);
+    /// let cities = Tuples::from(vec![("alice", "paris"), ("carol", "rome")]);
+    ///
+    /// assert_eq!(
+    ///     vec![("paris", 30)],
+    ///     ages.merge_join(&cities, |_, &age, &city| (city, age)).into_tuples()
+    /// );
+    /// ```
+    pub fn merge_join<V2, O>(
+        &self,
+        other: &Tuples<(K, V2)>,
+        mut combine: impl FnMut(&K, &V, &V2) -> O,
+    ) -> Tuples<O>
+    where
+        V2: Tuple,
+        O: Tuple,
+    {
+        let mut result = Vec::new();
+        join_helper(self, other, |k, v, v2| {
+            result.push(combine(k, v, v2));
+            true
+        });
+        result.into()
+    }
 }
 
 impl<T: Tuple> Deref for Tuples<T> {
@@ -63,21 +313,137 @@ impl<T: Tuple> core::ops::DerefMut for Tuples<T> {
     }
 }
 
+/// Is a sequence of tuples that is either owned by an evaluation or borrowed from an
+/// instance of the database. Operators that only read the tuples of their sub-expressions
+/// borrow the tuples of relations and views instead of cloning them.
+pub enum TuplesRef<'a, T: Tuple> {
+    Owned(Tuples<T>),
+    Borrowed(Ref<'a, Tuples<T>>),
+}
+
+impl<T: Tuple> TuplesRef<'_, T> {
+    /// Returns the number of tuples that the receiver owns.
+    pub(super) fn owned_len(&self) -> usize {
+        match self {
+            Self::Owned(tuples) => tuples.len(),
+            Self::Borrowed(_) => 0,
+        }
+    }
+}
+
+impl<T: Tuple> Deref for TuplesRef<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Owned(tuples) => tuples,
+            Self::Borrowed(tuples) => tuples,
+        }
+    }
+}
+
+/// Is a sequence of batches of tuples that is either owned by an evaluation or borrowed
+/// from an instance of the database (see [`TuplesRef`]).
+pub enum BatchesRef<'a, T: Tuple> {
+    Owned(Vec<Tuples<T>>),
+    Borrowed(Ref<'a, Vec<Tuples<T>>>),
+}
+
+impl<T: Tuple> BatchesRef<'_, T> {
+    /// Returns the number of tuples in the batches that the receiver owns.
+    pub(super) fn owned_len(&self) -> usize {
+        match self {
+            Self::Owned(batches) => batches.iter().map(|batch| batch.len()).sum(),
+            Self::Borrowed(_) => 0,
+        }
+    }
+}
+
+impl<T: Tuple> Deref for BatchesRef<'_, T> {
+    type Target = [Tuples<T>];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Owned(batches) => batches,
+            Self::Borrowed(batches) => batches,
+        }
+    }
+}
+
+/// Describes how the tuples of an instance are laid out in its batches.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelationStats {
+    /// Is the size of every batch of stable tuples.
+    pub stable: Vec<usize>,
+
+    /// Is the number of recent tuples.
+    pub recent: usize,
+
+    /// Is the number of tuples to add, including duplicates of existing tuples.
+    pub to_add: usize,
+}
+
+/// Summarizes the values of a numeric result, as computed by [`Database::column_stats`].
+///
+/// [`Database::column_stats`]: super::Database::column_stats
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnStats<T> {
+    /// Is the number of values.
+    pub count: usize,
+
+    /// Is the smallest value or `None` if there are no values.
+    pub min: Option<T>,
+
+    /// Is the largest value or `None` if there are no values.
+    pub max: Option<T>,
+
+    /// Is the sum of all values.
+    pub sum: T,
+}
+
 /// Is used to store instances of a database in a map by hiding their (generic) type.
-pub(super) trait DynInstance {
+pub(super) trait DynInstance: MaybeSync {
     /// Returns the instance as [`Any`]
     fn as_any(&self) -> &dyn Any;
 
     /// Returns true if the instance has been affected by last updates. It also moves all
-    /// `to_add` tuples to `recent` and `recent` tuples to `stable`.
-    fn changed(&self) -> bool;
+    /// `to_add` tuples to `recent` and `recent` tuples to `stable`. It fails if the
+    /// stable tuples that are spilled to disk cannot be loaded, in which case the `to_add`
+    /// tuples are left in place.
+    fn changed(&self) -> Result<bool, Error>;
+
+    /// Returns true if the instance has `recent` or `to_add` tuples that are not yet
+    /// reflected in the views that depend on it.
+    fn is_pending(&self) -> bool;
+
+    /// Removes all tuples of the instance.
+    fn clear(&self);
+
+    /// Compares the tuples of the instance with those of `other` and returns a
+    /// [`RelationDiff`] (in a box) of the tuples that `other` adds and removes, or `None`
+    /// if the instances have the same tuples or `other` is not of the same type.
+    fn diff(&self, other: &dyn DynInstance) -> Result<Option<Box<dyn Any>>, Error>;
+
+    /// Returns the sizes of the batches of tuples in the instance.
+    fn stats(&self) -> RelationStats;
+
+    /// Returns an estimate of the number of bytes that the tuples of the instance occupy
+    /// in memory.
+    fn estimated_bytes(&self) -> usize;
+
+    /// Shrinks the capacity of the vectors of the instance as much as possible.
+    fn shrink(&self);
+
+    /// Returns an error describing the first batch of the instance whose tuples are not
+    /// sorted and deduplicated, if any.
+    fn check_sorted(&self) -> Result<(), Error>;
 
     /// Clones the instance in a [`Box`].
     fn clone_box(&self) -> Box<dyn DynInstance>;
 }
 
 /// Is used to store `ViewInstance`s in a map by hiding their (generic) types.
-pub(super) trait DynViewInstance {
+pub(super) trait DynViewInstance: MaybeSync {
     /// Returns the view instance as `Any`.
     fn as_any(&self) -> &dyn Any;
 
@@ -90,6 +456,21 @@ pub(super) trait DynViewInstance {
     /// Stabilizes the view from the `recent` tuples in the instances of `db`.
     fn stabilize(&self, db: &Database) -> Result<(), Error>;
 
+    /// Returns true if the view is maintained by recomputing its content.
+    fn is_recompute(&self) -> bool;
+
+    /// Returns true if the view is maintained by recomputing its content and it must be
+    /// recomputed before it is evaluated.
+    fn is_dirty(&self) -> bool;
+
+    /// Recomputes the content of a view that is maintained by recomputation if the
+    /// instances of `db` that it depends on have changed since it was last computed.
+    fn recompute(&self, db: &Database) -> Result<(), Error>;
+
+    /// Returns the number of distinct tuples that have arrived in a counting view since it
+    /// was stored, or `None` if the view does not count its tuples.
+    fn arrived(&self) -> Option<u64>;
+
     /// Clones the instance in a [`Box`].
     fn clone_box(&self) -> Box<dyn DynViewInstance>;
 }
@@ -102,23 +483,50 @@ pub(super) trait DynViewInstance {
 #[derive(Debug, PartialEq)]
 pub(super) struct Instance<T: Tuple> {
     /// Is the set of tuples that are already considered when updating views.
-    stable: Rc<RefCell<Vec<Tuples<T>>>>,
+    stable: Shared<Lock<Vec<Tuples<T>>>>,
 
     /// Is the set of tuples that have not yet been reflected in views.
-    recent: Rc<RefCell<Tuples<T>>>,
+    recent: Shared<Lock<Tuples<T>>>,
 
     /// Is the set of tuples to add: they may be duplicates of existing tuples
     /// in which case they are ignored.
-    to_add: Rc<RefCell<Vec<Tuples<T>>>>,
+    to_add: Shared<Lock<Vec<Tuples<T>>>>,
+
+    /// Is incremented every time tuples are inserted into the instance, its to be added
+    /// tuples become recent, or it is cleared.
+    version: Atomic<u64>,
+
+    /// Is the (optional) configuration for spilling large stable batches to disk.
+    #[cfg(feature = "spill")]
+    spill: Option<Shared<Spill<T>>>,
+
+    /// Is the set of stable tuples that are spilled to disk.
+    #[cfg(feature = "spill")]
+    spilled: Shared<Lock<Vec<Shared<SpilledBatch>>>>,
 }
 
 impl<T: Tuple> Instance<T> {
     /// Creates a new empty isntance.
     pub fn new() -> Self {
         Self {
-            stable: Rc::new(RefCell::new(Vec::new())),
-            recent: Rc::new(RefCell::new(Vec::new().into())),
-            to_add: Rc::new(RefCell::new(Vec::new())),
+            stable: Shared::new(Lock::new(Vec::new())),
+            recent: Shared::new(Lock::new(Vec::new().into())),
+            to_add: Shared::new(Lock::new(Vec::new())),
+            version: Atomic::new(0),
+            #[cfg(feature = "spill")]
+            spill: None,
+            #[cfg(feature = "spill")]
+            spilled: Shared::new(Lock::new(Vec::new())),
+        }
+    }
+
+    /// Creates a new empty instance that spills its large stable batches to disk
+    /// according to `spill`.
+    #[cfg(feature = "spill")]
+    pub fn spilled(spill: Spill<T>) -> Self {
+        Self {
+            spill: Some(Shared::new(spill)),
+            ..Self::new()
         }
     }
 
@@ -127,37 +535,241 @@ impl<T: Tuple> Instance<T> {
     pub fn insert(&self, tuples: Tuples<T>) {
         if !tuples.is_empty() {
             self.to_add.borrow_mut().push(tuples);
+            self.bump_version();
+        }
+    }
+
+    /// Adds `tuples` to `to_add` tuples like [`Instance::insert`]. If the last batch of
+    /// `to_add` has fewer than [`SMALL_BATCH`] tuples, `tuples` are sorted and merged into
+    /// that batch in linear time instead of a new batch, so that many small insertions
+    /// leave few batches to merge when the instance is changed.
+    pub fn insert_slice(&self, tuples: &[T]) {
+        if tuples.is_empty() {
+            return;
+        }
+
+        let tuples = Tuples::from(tuples.to_vec());
+        let mut to_add = self.to_add.borrow_mut();
+        match to_add.last_mut() {
+            Some(tail) if tail.len() < SMALL_BATCH => tail.extend_sorted(tuples),
+            _ => to_add.push(tuples),
+        }
+        self.bump_version();
+    }
+
+    /// Merges the last batch of `to_add` tuples into the batch before it for as long as
+    /// that batch is at most twice as large, like the stable batches in `changed`, or there
+    /// are more than `max_batches` batches.
+    ///
+    /// **Note**: calling `coalesce` after every insertion keeps the sizes of the batches
+    /// geometrically decreasing, so every tuple is merged `O(log n)` times for `n` pending
+    /// tuples, unless `max_batches` forces more merges.
+    pub fn coalesce(&self, max_batches: usize) {
+        let mut to_add = self.to_add.borrow_mut();
+        while to_add.len() > 1 {
+            let len = to_add.len();
+            if len <= max_batches && to_add[len - 2].len() > 2 * to_add[len - 1].len() {
+                break;
+            }
+            let last = to_add.pop().unwrap();
+            to_add.last_mut().unwrap().extend_sorted(last);
+        }
+    }
+
+    /// Appends `batches` to the stable tuples of this instance, bypassing `to_add` and
+    /// `recent`. The batches are expected to be disjoint from each other and from the
+    /// existing tuples of the instance; they are not deduplicated against them. It fails if
+    /// the large batches cannot be spilled to disk, in which case they are kept in memory.
+    pub fn load_stable(&self, batches: Vec<Tuples<T>>) -> Result<(), Error> {
+        let mut batches = batches.into_iter().filter(|b| !b.is_empty()).peekable();
+        if batches.peek().is_some() {
+            self.stable.borrow_mut().extend(batches);
+            self.bump_version();
+
+            #[cfg(feature = "spill")]
+            self.spill()?;
         }
+        Ok(())
     }
 
-    /// Returns an immutable reference (of type [`Ref`]) to the stable tuples
+    /// Returns true if some of `tuples` exist in the stable, recent or to be added tuples
     /// of this instance.
-    #[inline(always)]
-    pub fn stable(&self) -> Ref<Vec<Tuples<T>>> {
-        self.stable.borrow()
+    pub fn contains_any(&self, tuples: &Tuples<T>) -> Result<bool, Error> {
+        let mut found = false;
+        #[cfg(feature = "spill")]
+        self.for_each_spilled(|batch| {
+            found = found || batch.contains_any(tuples)?;
+            Ok(())
+        })?;
+        for batch in self.stable.borrow().iter() {
+            intersect_helper(batch, tuples, |_| found = true);
+        }
+        intersect_helper(&self.recent.borrow(), tuples, |_| found = true);
+        for batch in self.to_add.borrow().iter() {
+            intersect_helper(batch, tuples, |_| found = true);
+        }
+        Ok(found)
+    }
+
+    /// Returns the number of `tuples` that exist in none of the stable, recent or to be
+    /// added tuples of this instance.
+    pub fn count_new(&self, tuples: &Tuples<T>) -> Result<usize, Error> {
+        #[cfg(feature = "spill")]
+        let tuples = &{
+            let mut tuples = tuples.clone();
+            self.for_each_spilled(|batch| batch.retain_absent(&mut tuples.items))?;
+            tuples
+        };
+        let stable = self.stable.borrow();
+        let recent = self.recent.borrow();
+        let to_add = self.to_add.borrow();
+
+        let mut batches: Vec<&[T]> = Vec::new();
+        batches.extend(stable.iter().map(|batch| batch.items()));
+        batches.push(recent.items());
+        batches.extend(to_add.iter().map(|batch| batch.items()));
+
+        let mut count = 0;
+        diff_helper(tuples, &batches, |_| count += 1);
+        Ok(count)
+    }
+
+    /// Returns all tuples of this instance, including its recent and to be added tuples.
+    pub fn tuples(&self) -> Result<Tuples<T>, Error> {
+        let mut batches = self.stable_batches()?;
+        batches.push(self.recent.borrow().clone());
+        batches.extend(self.to_add.borrow().iter().cloned());
+        Ok(Tuples::merge_all(batches))
+    }
+
+    /// Returns the batches of stable tuples of this instance, including the batches that
+    /// are spilled to disk.
+    pub fn stable_batches(&self) -> Result<Vec<Tuples<T>>, Error> {
+        let mut result = Vec::new();
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            for batch in self.spilled.borrow().iter() {
+                result.push(spill.load(batch)?);
+            }
+        }
+        for batch in self.stable.borrow().iter() {
+            result.push(batch.clone());
+        }
+        Ok(result)
+    }
+
+    /// Returns the batches of stable tuples of this instance like
+    /// [`Instance::stable_batches`] but borrows them from the instance unless some of them
+    /// are spilled to disk.
+    pub fn stable_ref(&self) -> Result<BatchesRef<'_, T>, Error> {
+        #[cfg(feature = "spill")]
+        if !self.spilled.borrow().is_empty() {
+            return self.stable_batches().map(BatchesRef::Owned);
+        }
+        Ok(BatchesRef::Borrowed(self.stable.borrow()))
     }
 
     /// Returns an immutable reference (of type [`Ref`]) to the recent tuples
     /// of this instance.
     #[inline(always)]
-    pub fn recent(&self) -> Ref<Tuples<T>> {
+    pub fn recent(&self) -> Ref<'_, Tuples<T>> {
         self.recent.borrow()
     }
 
     /// Returns an immutable reference (of type [`Ref`]) to the candidates to
     /// be added to the recent tuples of this instance (if they already don't exist).
     #[inline(always)]
-    pub fn to_add(&self) -> Ref<Vec<Tuples<T>>> {
+    pub fn to_add(&self) -> Ref<'_, Vec<Tuples<T>>> {
         self.to_add.borrow()
     }
+
+    /// Returns the version of this instance. The version increases whenever the content of
+    /// the instance may have changed.
+    #[inline(always)]
+    pub fn version(&self) -> u64 {
+        self.version.get()
+    }
+
+    #[inline(always)]
+    fn bump_version(&self) {
+        self.version.set(self.version.get() + 1);
+    }
+
+    /// Moves the stable batches that are large enough to disk. It fails on the first batch
+    /// that cannot be stored, keeping that batch and the batches after it in memory.
+    #[cfg(feature = "spill")]
+    fn spill(&self) -> Result<(), Error> {
+        if let Some(spill) = &self.spill {
+            let mut stable = self.stable.borrow_mut();
+            let mut spilled = self.spilled.borrow_mut();
+            let mut result = Ok(());
+            stable.retain(|batch| {
+                if result.is_err() || !spill.should_spill(batch) {
+                    return true;
+                }
+                match spill.store(batch) {
+                    Ok(b) => {
+                        spilled.push(Shared::new(b));
+                        false
+                    }
+                    Err(e) => {
+                        result = Err(e);
+                        true
+                    }
+                }
+            });
+            return result;
+        }
+        Ok(())
+    }
+
+    /// Merges the spilled batches into `recent`, starting at the last of them, for as long
+    /// as they are at most twice as large as `recent`, like the stable batches in `changed`.
+    /// The spilled batches are only merged once every stable batch in memory is merged, as
+    /// they are older than those batches.
+    #[cfg(feature = "spill")]
+    fn merge_spilled(&self, recent: &mut Tuples<T>) -> Result<(), Error> {
+        if let Some(spill) = &self.spill {
+            if !self.stable.borrow().is_empty() {
+                return Ok(());
+            }
+            let mut spilled = self.spilled.borrow_mut();
+            while let Some(last) = spilled.last().filter(|b| b.len() <= 2 * recent.len()) {
+                let last = spill.load(last)?;
+                spilled.pop();
+                *recent = std::mem::replace(recent, Vec::new().into()).merge(last);
+            }
+        }
+        Ok(())
+    }
+
+    /// Maps the spilled batches of this instance into memory one at a time and applies `f`
+    /// on them. It fails on the first batch that cannot be mapped or read.
+    #[cfg(feature = "spill")]
+    fn for_each_spilled(
+        &self,
+        mut f: impl FnMut(&MappedBatch<'_, T>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        if let Some(spill) = &self.spill {
+            for batch in self.spilled.borrow().iter() {
+                f(&spill.open(batch)?)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T: Tuple> Clone for Instance<T> {
     fn clone(&self) -> Self {
         Self {
-            stable: Rc::new(RefCell::new(self.stable.borrow().clone())),
-            recent: Rc::new(RefCell::new(self.recent.borrow().clone())),
-            to_add: Rc::new(RefCell::new(self.to_add.borrow().clone())),
+            stable: Shared::new(Lock::new(self.stable.borrow().clone())),
+            recent: Shared::new(Lock::new(self.recent.borrow().clone())),
+            to_add: Shared::new(Lock::new(self.to_add.borrow().clone())),
+            version: self.version.clone(),
+            #[cfg(feature = "spill")]
+            spill: self.spill.clone(),
+            #[cfg(feature = "spill")]
+            spilled: Shared::new(Lock::new(self.spilled.borrow().clone())),
         }
     }
 }
@@ -170,7 +782,7 @@ where
         self
     }
 
-    fn changed(&self) -> bool {
+    fn changed(&self) -> Result<bool, Error> {
         if !self.recent.borrow().is_empty() {
             let mut recent =
                 ::std::mem::replace(&mut (*self.recent.borrow_mut()), Vec::new().into());
@@ -184,25 +796,150 @@ where
                 let last = self.stable.borrow_mut().pop().unwrap();
                 recent = recent.merge(last);
             }
+            #[cfg(feature = "spill")]
+            let merged = self.merge_spilled(&mut recent);
             self.stable.borrow_mut().push(recent);
+
+            #[cfg(feature = "spill")]
+            {
+                merged?;
+                self.spill()?;
+            }
         }
 
-        let to_add = self.to_add.borrow_mut().pop();
-        if let Some(mut to_add) = to_add {
-            while let Some(to_add_more) = self.to_add.borrow_mut().pop() {
-                to_add = to_add.merge(to_add_more);
+        let to_add = std::mem::take(&mut *self.to_add.borrow_mut());
+        if !to_add.is_empty() {
+            let mut to_add = Tuples::merge_all(to_add);
+            #[cfg(feature = "spill")]
+            if let Err(e) = self.for_each_spilled(|batch| batch.retain_absent(&mut to_add.items)) {
+                self.to_add.borrow_mut().push(to_add);
+                return Err(e);
             }
             for batch in self.stable.borrow().iter() {
-                let mut slice = &batch[..];
-                to_add.items.retain(|x| {
-                    slice = gallop(slice, |y| y < x);
-                    slice.is_empty() || &slice[0] != x
-                });
+                retain_absent(&mut to_add.items, batch);
+            }
+            if !to_add.is_empty() {
+                self.bump_version();
             }
             *self.recent.borrow_mut() = to_add;
         }
 
-        !self.recent.borrow().is_empty()
+        Ok(!self.recent.borrow().is_empty())
+    }
+
+    fn is_pending(&self) -> bool {
+        !self.recent.borrow().is_empty() || !self.to_add.borrow().is_empty()
+    }
+
+    fn clear(&self) {
+        #[cfg(feature = "spill")]
+        self.spilled.borrow_mut().clear();
+        self.stable.borrow_mut().clear();
+        *self.recent.borrow_mut() = Vec::new().into();
+        self.to_add.borrow_mut().clear();
+        self.bump_version();
+    }
+
+    fn diff(&self, other: &dyn DynInstance) -> Result<Option<Box<dyn Any>>, Error> {
+        let other = match other.as_any().downcast_ref::<Self>() {
+            Some(other) => other,
+            None => return Ok(None),
+        };
+
+        let left = self.tuples()?;
+        let right = other.tuples()?;
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        diff_helper(&right, &[&left], |t| added.push(t.clone()));
+        diff_helper(&left, &[&right], |t| removed.push(t.clone()));
+        if added.is_empty() && removed.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Box::new(RelationDiff {
+            added: Tuples::from_sorted_unchecked(added),
+            removed: Tuples::from_sorted_unchecked(removed),
+        })))
+    }
+
+    fn stats(&self) -> RelationStats {
+        let mut stable = Vec::new();
+        #[cfg(feature = "spill")]
+        stable.extend(self.spilled.borrow().iter().map(|batch| batch.len()));
+        stable.extend(self.stable.borrow().iter().map(|batch| batch.len()));
+
+        RelationStats {
+            stable,
+            recent: self.recent.borrow().len(),
+            to_add: self.to_add.borrow().iter().map(|batch| batch.len()).sum(),
+        }
+    }
+
+    fn estimated_bytes(&self) -> usize {
+        // spilled batches are on disk and don't count:
+        let tuples = self
+            .stable
+            .borrow()
+            .iter()
+            .map(|batch| batch.len())
+            .sum::<usize>()
+            + self.recent.borrow().len()
+            + self
+                .to_add
+                .borrow()
+                .iter()
+                .map(|batch| batch.len())
+                .sum::<usize>();
+        tuples * std::mem::size_of::<T>()
+    }
+
+    fn shrink(&self) {
+        let mut stable = self.stable.borrow_mut();
+        stable.iter_mut().for_each(|batch| batch.shrink_to_fit());
+        stable.shrink_to_fit();
+
+        self.recent.borrow_mut().shrink_to_fit();
+
+        let mut to_add = self.to_add.borrow_mut();
+        to_add.iter_mut().for_each(|batch| batch.shrink_to_fit());
+        to_add.shrink_to_fit();
+    }
+
+    fn check_sorted(&self) -> Result<(), Error> {
+        fn sorted<T: Tuple>(batch: &[T]) -> bool {
+            batch.windows(2).all(|w| w[0] < w[1])
+        }
+        let corrupted = |detail: String| Err(Error::Corrupted { detail });
+
+        #[cfg(feature = "spill")]
+        {
+            let mut unsorted = None;
+            let mut i = 0;
+            self.for_each_spilled(|batch| {
+                if unsorted.is_none() && !batch.is_sorted()? {
+                    unsorted = Some(i);
+                }
+                i += 1;
+                Ok(())
+            })?;
+            if let Some(i) = unsorted {
+                return corrupted(format!("spilled batch {} is not sorted", i));
+            }
+        }
+        for (i, batch) in self.stable.borrow().iter().enumerate() {
+            if !sorted(batch) {
+                return corrupted(format!("stable batch {} is not sorted", i));
+            }
+        }
+        if !sorted(&self.recent.borrow()) {
+            return corrupted("recent tuples are not sorted".to_string());
+        }
+        for (i, batch) in self.to_add.borrow().iter().enumerate() {
+            if !sorted(batch) {
+                return corrupted(format!("batch {} of tuples to add is not sorted", i));
+            }
+        }
+        Ok(())
     }
 
     fn clone_box(&self) -> Box<dyn DynInstance> {
@@ -219,9 +956,14 @@ where
         }
 
         Box::new(Self {
-            stable: Rc::new(RefCell::new(stable)),
-            recent: Rc::new(RefCell::new(recent)),
-            to_add: Rc::new(RefCell::new(to_add)),
+            stable: Shared::new(Lock::new(stable)),
+            recent: Shared::new(Lock::new(recent)),
+            to_add: Shared::new(Lock::new(to_add)),
+            version: self.version.clone(),
+            #[cfg(feature = "spill")]
+            spill: self.spill.clone(),
+            #[cfg(feature = "spill")]
+            spilled: Shared::new(Lock::new(self.spilled.borrow().clone())),
         })
     }
 }
@@ -238,6 +980,18 @@ where
 
     /// Is the view expression.
     expression: E,
+
+    /// Is true if the view is maintained by recomputing its content from scratch
+    /// instead of incremental updates.
+    recompute: bool,
+
+    /// Is true if the instances on which a recomputed view depends have changed since
+    /// the view was last computed.
+    dirty: Atomic<bool>,
+
+    /// Is the (optional) set of every tuple that has arrived in the view since it was
+    /// stored, which persists when the view is cleared and re-initialized.
+    seen: Option<Lock<Tuples<T>>>,
 }
 
 impl<T, E> ViewInstance<T, E>
@@ -245,10 +999,22 @@ where
     T: Tuple,
     E: Expression<T>,
 {
-    pub fn new(expression: E) -> Self {
+    pub fn new(expression: E, recompute: bool) -> Self {
         Self {
             instance: Instance::new(),
             expression,
+            recompute,
+            dirty: Atomic::new(false),
+            seen: None,
+        }
+    }
+
+    /// Creates a new incrementally maintained view instance that counts the distinct tuples
+    /// that arrive in the view.
+    pub fn counting(expression: E) -> Self {
+        Self {
+            seen: Some(Lock::new(Vec::new().into())),
+            ..Self::new(expression, false)
         }
     }
 
@@ -256,6 +1022,13 @@ where
     pub fn instance(&self) -> &Instance<T> {
         &self.instance
     }
+
+    /// Records `tuples` as seen by the view if the view is counting.
+    fn arrive(&self, tuples: &Tuples<T>) {
+        if let Some(seen) = &self.seen {
+            seen.borrow_mut().extend_sorted(tuples.clone());
+        }
+    }
 }
 
 impl<T, E> DynViewInstance for ViewInstance<T, E>
@@ -276,25 +1049,57 @@ where
         let stable = self.expression.collect_stable(&incremental)?;
 
         for batch in stable {
+            self.arrive(&batch);
             self.instance.insert(batch);
         }
         Ok(())
     }
 
     fn stabilize(&self, db: &Database) -> Result<(), Error> {
+        if self.recompute {
+            // the view is recomputed when it is stabilized:
+            self.dirty.set(true);
+            return Ok(());
+        }
+
         let incremental = evaluate::IncrementalCollector::new(db);
         let recent = self.expression.collect_recent(&incremental)?;
 
+        self.arrive(&recent);
         self.instance.insert(recent);
         Ok(())
     }
 
+    fn is_recompute(&self) -> bool {
+        self.recompute
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty.get()
+    }
+
+    fn recompute(&self, db: &Database) -> Result<(), Error> {
+        if self.dirty.get() {
+            self.instance.clear();
+            self.initialize(db)?;
+            self.dirty.set(false);
+        }
+        Ok(())
+    }
+
     fn clone_box(&self) -> Box<dyn DynViewInstance> {
         Box::new(Self {
             instance: self.instance.clone(),
             expression: self.expression.clone(),
+            recompute: self.recompute,
+            dirty: self.dirty.clone(),
+            seen: self.seen.clone(),
         })
     }
+
+    fn arrived(&self) -> Option<u64> {
+        self.seen.as_ref().map(|seen| seen.borrow().len() as u64)
+    }
 }
 
 #[cfg(test)]
@@ -309,15 +1114,149 @@ mod tests {
         }
         {
             let instance = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![vec![1, 2].into()])),
-                recent: Rc::new(RefCell::new(vec![2, 3, 4].into())),
-                to_add: Rc::new(RefCell::new(vec![vec![4, 5].into()])),
+                stable: Shared::new(Lock::new(vec![vec![1, 2].into()])),
+                recent: Shared::new(Lock::new(vec![2, 3, 4].into())),
+                to_add: Shared::new(Lock::new(vec![vec![4, 5].into()])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
             let cloned = instance.clone();
             assert_eq!(instance, cloned);
         }
     }
 
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spill_instance() {
+        let instance = Instance::<i32>::spilled(Spill::new("r", 3).unwrap());
+        instance.insert(vec![1, 2].into());
+        while instance.changed().unwrap() {}
+        assert_eq!(0, instance.spilled.borrow().len());
+
+        instance.insert(vec![2, 3, 4, 5].into());
+        while instance.changed().unwrap() {}
+        assert_eq!(1, instance.spilled.borrow().len());
+        assert!(instance.stable.borrow().is_empty());
+        assert_eq!(
+            vec![Tuples::<i32>::from(vec![1, 2, 3, 4, 5])],
+            instance.stable_batches().unwrap()
+        );
+        assert!(instance.contains_any(&vec![5, 6].into()).unwrap());
+
+        instance.clear();
+        assert!(instance.spilled.borrow().is_empty());
+        assert!(instance.stable_batches().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spill_instance_merge() {
+        let instance = Instance::<i32>::spilled(Spill::new("r", 2).unwrap());
+        instance.insert(vec![1, 2].into());
+        while instance.changed().unwrap() {}
+        assert_eq!(1, instance.spilled.borrow().len());
+
+        instance.insert(vec![2, 3, 4].into());
+        while instance.changed().unwrap() {}
+        assert_eq!(1, instance.spilled.borrow().len());
+        assert_eq!(4, instance.spilled.borrow()[0].len());
+
+        instance.insert(vec![5].into());
+        while instance.changed().unwrap() {}
+        assert_eq!(1, instance.spilled.borrow().len());
+        assert_eq!(
+            vec![
+                Tuples::<i32>::from(vec![1, 2, 3, 4]),
+                Tuples::<i32>::from(vec![5])
+            ],
+            instance.stable_batches().unwrap()
+        );
+        assert_eq!(1, instance.count_new(&vec![0, 3, 5].into()).unwrap());
+        assert!(!instance.contains_any(&vec![0, 6].into()).unwrap());
+        assert!(instance.check_sorted().is_ok());
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spill_instance_store_error() {
+        let instance = Instance::<i32>::spilled(Spill::new("r", 3).unwrap());
+        instance.load_stable(vec![vec![1, 2, 3].into()]).unwrap();
+        let dir = instance.spilled.borrow()[0]
+            .path()
+            .parent()
+            .unwrap()
+            .to_owned();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert!(matches!(
+            instance.load_stable(vec![vec![4, 5, 6].into(), vec![7, 8, 9].into()]),
+            Err(Error::SpillFailed { .. })
+        ));
+        assert_eq!(
+            vec![
+                Tuples::<i32>::from(vec![4, 5, 6]),
+                Tuples::<i32>::from(vec![7, 8, 9])
+            ],
+            *instance.stable.borrow()
+        );
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spill_instance_missing_batch() {
+        let instance = Instance::<i32>::spilled(Spill::new("r", 3).unwrap());
+        instance.insert(vec![1, 2, 3].into());
+        while instance.changed().unwrap() {}
+        std::fs::remove_file(instance.spilled.borrow()[0].path()).unwrap();
+
+        instance.insert(vec![4].into());
+        assert!(matches!(instance.changed(), Err(Error::SpillFailed { .. })));
+        assert_eq!(vec![Tuples::<i32>::from(vec![4])], *instance.to_add());
+        assert!(instance.contains_any(&vec![4].into()).is_err());
+        assert!(instance.count_new(&vec![4].into()).is_err());
+        assert!(instance.check_sorted().is_err());
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spill_stabilize_missing_batch() {
+        let mut database = Database::new();
+        let r = database.add_relation_spilled::<i32>("r", 2).unwrap();
+        let v = database
+            .store_view(r.builder().project(|t| t * 10))
+            .unwrap();
+        database.insert(&r, vec![1, 2].into()).unwrap();
+        assert_eq!(vec![10, 20], database.evaluate(&v).unwrap().into_tuples());
+
+        let entry = database.relations.get("r").unwrap();
+        let instance = entry
+            .instance
+            .as_any()
+            .downcast_ref::<Instance<i32>>()
+            .unwrap();
+        std::fs::remove_file(instance.spilled.borrow()[0].path()).unwrap();
+
+        database.insert(&r, vec![3].into()).unwrap();
+        assert!(matches!(
+            database.evaluate(&v),
+            Err(Error::SpillFailed { .. })
+        ));
+        // the stabilization is attempted again rather than skipped as in progress:
+        assert!(!entry.stabilizing.get());
+        assert!(database.views.values().all(|view| !view.stabilizing.get()));
+        assert!(matches!(
+            database.evaluate(&v),
+            Err(Error::SpillFailed { .. })
+        ));
+        assert!(matches!(
+            database.evaluate(&r),
+            Err(Error::SpillFailed { .. })
+        ));
+    }
+
     #[test]
     fn test_tuples_from_list() {
         {
@@ -334,6 +1273,164 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tuples_from_iter() {
+        {
+            let tuples = std::iter::empty().collect::<Tuples<i32>>();
+            assert_eq!(Vec::<i32>::new(), tuples.items());
+        }
+        {
+            let tuples = (1..=3).chain(1..=2).collect::<Tuples<i32>>();
+            assert_eq!(vec![1, 2, 3], tuples.items());
+        }
+    }
+
+    #[test]
+    fn test_tuples_map() {
+        {
+            let tuples = Tuples::<i32>::from(vec![]).map(|t| t + 1);
+            assert_eq!(Vec::<i32>::new(), tuples.items());
+        }
+        {
+            let tuples = Tuples::<i32>::from(vec![-2, -1, 0, 1, 3]).map(|t| t * t);
+            assert_eq!(vec![0, 1, 4, 9], tuples.items());
+        }
+        {
+            let tuples = Tuples::<i32>::from(vec![1, 2, 3]).map(|t| -t);
+            assert_eq!(vec![-3, -2, -1], tuples.items());
+        }
+    }
+
+    #[test]
+    fn test_tuples_eq_as_set() {
+        let tuples = Tuples::<i32>::from(vec![3, 1, 2]);
+        let unchecked = Tuples::unordered(vec![2, 3, 1, 3]);
+        assert_ne!(tuples, unchecked);
+        assert!(tuples.eq_as_set(&unchecked));
+        assert!(unchecked.eq_as_set(&tuples));
+        assert!(!tuples.eq_as_set(&Tuples::unordered(vec![2, 1])));
+        assert!(tuples.eq_as_set(&Tuples::from_sorted_unchecked(vec![1, 2, 3])));
+        assert!(Tuples::<i32>::from(vec![]).eq_as_set(&Tuples::from_sorted_unchecked(vec![])));
+    }
+
+    #[test]
+    fn test_tuples_dedup_by() {
+        let mut tuples =
+            Tuples::<(i32, &str)>::from(vec![(2, "x"), (1, "c"), (3, "y"), (1, "a"), (2, "b")]);
+        tuples.dedup_by(|first, next| first.0 == next.0);
+        assert_eq!(vec![(1, "a"), (2, "b"), (3, "y")], tuples.into_tuples());
+
+        let mut tuples = Tuples::<(i32, &str)>::from(vec![]);
+        tuples.dedup_by(|_, _| true);
+        assert!(tuples.is_empty());
+    }
+
+    #[test]
+    fn test_tuples_merge_join() {
+        use crate::expression::Join;
+
+        let left = Tuples::<(i32, char)>::from(vec![(1, 'a'), (1, 'b'), (2, 'c'), (4, 'd')]);
+        let right = Tuples::<(i32, bool)>::from(vec![(0, true), (1, false), (1, true), (4, true)]);
+        let joined = left.merge_join(&right, |&k, &l, &r| (k, l, r));
+        assert_eq!(
+            vec![
+                (1, 'a', false),
+                (1, 'a', true),
+                (1, 'b', false),
+                (1, 'b', true),
+                (4, 'd', true)
+            ],
+            joined.clone().into_tuples()
+        );
+
+        let mut database = Database::new();
+        let l = database.add_relation::<(i32, char)>("l").unwrap();
+        let r = database.add_relation::<(i32, bool)>("r").unwrap();
+        database.insert(&l, left.clone()).unwrap();
+        database.insert(&r, right.clone()).unwrap();
+        let join = Join::new(&l, &r, |t| t.0, |t| t.0, |&k, &l, &r| (k, l.1, r.1));
+        assert_eq!(database.evaluate(&join).unwrap(), joined);
+
+        assert!(left
+            .merge_join(&Tuples::<(i32, bool)>::from(vec![]), |_, _, _| 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tuples_extend_sorted() {
+        let chunks: Vec<Tuples<i32>> = vec![
+            vec![1, 5, 9, 13].into(),
+            vec![0, 5, 6, 20].into(),
+            vec![9, 13, 14].into(),
+        ];
+        let mut tuples = Tuples::<i32>::from(vec![]);
+        for chunk in chunks.clone() {
+            tuples.extend_sorted(chunk);
+        }
+        let merged = chunks
+            .into_iter()
+            .fold(Tuples::from(vec![]), |acc, chunk| acc.merge(chunk));
+        assert_eq!(merged, tuples);
+        assert_eq!(
+            vec![0, 1, 5, 6, 9, 13, 14, 20],
+            tuples.clone().into_tuples()
+        );
+
+        tuples.extend_sorted(vec![].into());
+        assert_eq!(merged, tuples);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "tuples are not sorted and deduplicated: 3 at index 1")]
+    fn test_tuples_from_sorted_unchecked_unsorted() {
+        Tuples::from_sorted_unchecked(vec![1, 3, 2]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "tuples are not sorted and deduplicated: 2 at index 1")]
+    fn test_tuples_from_sorted_unchecked_duplicates() {
+        Tuples::from_sorted_unchecked(vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn test_tuples_map_monotone_unchecked() {
+        {
+            let tuples = Tuples::<i32>::from(vec![]).map_monotone_unchecked(|t| t + 1);
+            assert_eq!(Vec::<i32>::new(), tuples.items());
+        }
+        {
+            let tuples =
+                Tuples::<i32>::from(vec![3, 1, 2]).map_monotone_unchecked(|t| (t, t.to_string()));
+            assert_eq!(
+                vec![(1, "1".to_string()), (2, "2".into()), (3, "3".into())],
+                tuples.items()
+            );
+        }
+    }
+
+    #[test]
+    fn test_tuples_merge_all() {
+        {
+            let tuples = Tuples::<i32>::merge_all(vec![]);
+            assert_eq!(Vec::<i32>::new(), tuples.items());
+        }
+        {
+            let tuples = Tuples::<i32>::merge_all(vec![vec![].into(), vec![].into()]);
+            assert_eq!(Vec::<i32>::new(), tuples.items());
+        }
+        {
+            let tuples = Tuples::<i32>::merge_all(vec![
+                vec![1, 4, 7].into(),
+                vec![2, 4].into(),
+                vec![].into(),
+                vec![7, 3, 1].into(),
+            ]);
+            assert_eq!(vec![1, 2, 3, 4, 7], tuples.items());
+        }
+    }
+
     #[test]
     fn test_tuples_merge() {
         {
@@ -354,9 +1451,14 @@ mod tests {
     fn test_instance_insert() {
         {
             let relation = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![].into())),
-                to_add: Rc::new(RefCell::new(vec![])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![].into())),
+                to_add: Shared::new(Lock::new(vec![])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
             relation.insert(vec![].into());
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.stable.borrow());
@@ -366,9 +1468,14 @@ mod tests {
 
         {
             let relation: Instance<i32> = Instance {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![1, 2, 3].into())),
-                to_add: Rc::new(RefCell::new(vec![])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![1, 2, 3].into())),
+                to_add: Shared::new(Lock::new(vec![])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
             relation.insert(vec![].into());
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.stable.borrow());
@@ -378,29 +1485,101 @@ mod tests {
 
         {
             let relation: Instance<i32> = Instance {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![1, 2, 3].into())),
-                to_add: Rc::new(RefCell::new(vec![])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![1, 2, 3].into())),
+                to_add: Shared::new(Lock::new(vec![])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
             relation.insert(vec![5, 4].into());
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.stable.borrow());
             assert_eq!(vec![1, 2, 3], relation.recent.borrow().items);
             assert_eq!(
-                Vec::<Tuples<i32>>::from(vec![vec![4, 5].into()]),
+                vec![Tuples::<i32>::from(vec![4, 5])],
                 *relation.to_add.borrow(),
             );
         }
     }
 
+    #[test]
+    fn test_instance_insert_slice() {
+        let relation = Instance::<i32>::new();
+        relation.insert_slice(&[]);
+        assert_eq!(0, relation.version());
+        assert!(relation.to_add.borrow().is_empty());
+
+        // every batch absorbs `SMALL_BATCH / 2` slices of two tuples:
+        for i in (0..SMALL_BATCH as i32).rev() {
+            relation.insert_slice(&[2 * i + 1, 2 * i]);
+        }
+        assert_eq!(2, relation.to_add.borrow().len());
+        assert_eq!(SMALL_BATCH as u64, relation.version());
+        assert!(relation.check_sorted().is_ok());
+
+        relation.changed().unwrap();
+        assert_eq!(
+            (0..2 * SMALL_BATCH as i32).collect::<Vec<_>>(),
+            relation.recent.borrow().items
+        );
+    }
+
+    #[test]
+    fn test_instance_coalesce() {
+        let relation = Instance::<i32>::new();
+        for i in 0..1000 {
+            relation.insert(vec![i].into());
+            relation.coalesce(usize::MAX);
+        }
+        {
+            let to_add = relation.to_add.borrow();
+            assert!(to_add.len() <= 10);
+            assert!(to_add.windows(2).all(|w| w[0].len() > 2 * w[1].len()));
+        }
+
+        relation.coalesce(2);
+        assert_eq!(2, relation.to_add.borrow().len());
+        relation.changed().unwrap();
+        assert_eq!(
+            (0..1000).collect::<Vec<_>>(),
+            relation.recent.borrow().items
+        );
+    }
+
+    #[test]
+    fn test_instance_clear() {
+        let relation = Instance::<i32> {
+            stable: Shared::new(Lock::new(vec![vec![1, 2].into()])),
+            recent: Shared::new(Lock::new(vec![2, 3, 4].into())),
+            to_add: Shared::new(Lock::new(vec![vec![4, 5].into()])),
+            version: Atomic::new(0),
+            #[cfg(feature = "spill")]
+            spill: None,
+            #[cfg(feature = "spill")]
+            spilled: Shared::new(Lock::new(Vec::new())),
+        };
+        relation.clear();
+        assert_eq!(Vec::<Tuples<i32>>::new(), *relation.stable.borrow());
+        assert_eq!(Vec::<i32>::new(), relation.recent.borrow().items);
+        assert_eq!(Vec::<Tuples<i32>>::new(), *relation.to_add.borrow());
+    }
+
     #[test]
     fn test_instance_changed() {
         {
             let relation: Instance<i32> = Instance {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![].into())),
-                to_add: Rc::new(RefCell::new(vec![])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![].into())),
+                to_add: Shared::new(Lock::new(vec![])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
-            relation.changed();
+            relation.changed().unwrap();
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.stable.borrow());
             assert_eq!(Vec::<i32>::new(), relation.recent.borrow().items);
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.to_add.borrow());
@@ -408,11 +1587,16 @@ mod tests {
 
         {
             let relation = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![].into())),
-                to_add: Rc::new(RefCell::new(vec![vec![1, 2].into()])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![].into())),
+                to_add: Shared::new(Lock::new(vec![vec![1, 2].into()])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
-            assert!(relation.changed());
+            assert!(relation.changed().unwrap());
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.stable.borrow());
             assert_eq!(vec![1, 2], relation.recent.borrow().items);
             assert_eq!(Vec::<Tuples<i32>>::new(), *relation.to_add.borrow());
@@ -420,13 +1604,18 @@ mod tests {
 
         {
             let relation = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![1, 2].into())),
-                to_add: Rc::new(RefCell::new(vec![])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![1, 2].into())),
+                to_add: Shared::new(Lock::new(vec![])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
-            assert!(!relation.changed());
+            assert!(!relation.changed().unwrap());
             assert_eq!(
-                Vec::<Tuples<i32>>::from(vec![vec![1, 2].into()]),
+                vec![Tuples::<i32>::from(vec![1, 2])],
                 *relation.stable.borrow()
             );
             assert_eq!(Vec::<i32>::new(), relation.recent.borrow().items);
@@ -435,13 +1624,18 @@ mod tests {
 
         {
             let relation = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![])),
-                recent: Rc::new(RefCell::new(vec![1, 2].into())),
-                to_add: Rc::new(RefCell::new(vec![vec![3, 4].into()])),
+                stable: Shared::new(Lock::new(vec![])),
+                recent: Shared::new(Lock::new(vec![1, 2].into())),
+                to_add: Shared::new(Lock::new(vec![vec![3, 4].into()])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
-            assert!(relation.changed());
+            assert!(relation.changed().unwrap());
             assert_eq!(
-                Vec::<Tuples<i32>>::from(vec![vec![1, 2].into()]),
+                vec![Tuples::<i32>::from(vec![1, 2])],
                 *relation.stable.borrow()
             );
             assert_eq!(vec![3, 4], relation.recent.borrow().items);
@@ -450,13 +1644,18 @@ mod tests {
 
         {
             let relation = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![vec![1, 2].into()])),
-                recent: Rc::new(RefCell::new(vec![2, 3, 4].into())),
-                to_add: Rc::new(RefCell::new(vec![vec![4, 5].into()])),
+                stable: Shared::new(Lock::new(vec![vec![1, 2].into()])),
+                recent: Shared::new(Lock::new(vec![2, 3, 4].into())),
+                to_add: Shared::new(Lock::new(vec![vec![4, 5].into()])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
-            assert!(relation.changed());
+            assert!(relation.changed().unwrap());
             assert_eq!(
-                Vec::<Tuples<i32>>::from(vec![vec![1, 2, 3, 4].into()]),
+                vec![Tuples::<i32>::from(vec![1, 2, 3, 4])],
                 *relation.stable.borrow()
             );
             assert_eq!(vec![5], relation.recent.borrow().items);
@@ -465,13 +1664,18 @@ mod tests {
 
         {
             let relation = Instance::<i32> {
-                stable: Rc::new(RefCell::new(vec![vec![1, 2].into()])),
-                recent: Rc::new(RefCell::new(vec![2, 3, 4].into())),
-                to_add: Rc::new(RefCell::new(vec![vec![1, 5].into()])),
+                stable: Shared::new(Lock::new(vec![vec![1, 2].into()])),
+                recent: Shared::new(Lock::new(vec![2, 3, 4].into())),
+                to_add: Shared::new(Lock::new(vec![vec![1, 5].into()])),
+                version: Atomic::new(0),
+                #[cfg(feature = "spill")]
+                spill: None,
+                #[cfg(feature = "spill")]
+                spilled: Shared::new(Lock::new(Vec::new())),
             };
-            assert!(relation.changed());
+            assert!(relation.changed().unwrap());
             assert_eq!(
-                Vec::<Tuples<i32>>::from(vec![vec![1, 2, 3, 4].into()]),
+                vec![Tuples::<i32>::from(vec![1, 2, 3, 4])],
                 *relation.stable.borrow()
             );
             assert_eq!(vec![5], relation.recent.borrow().items);
@@ -0,0 +1,130 @@
+/*! Implements interning the fields of tuples that are materialized by the database.
+
+Results of wide queries often repeat the same large field values, such as the name of a
+category, across many tuples; every tuple would then own a separate clone of the value. An
+[`Interner`] deduplicates such values by replacing them with reference counted pointers
+that are shared by all tuples with identical values.
+*/
+use crate::Tuple;
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
+
+/// Is the trait of tuples whose fields can be interned by an [`Interner`]. The interned
+/// representation of a tuple is a tuple of type `Interned`, in which the large fields of
+/// the original tuple are replaced by shared pointers obtained from the interner.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Internable, Interner};
+/// use std::sync::Arc;
+///
+/// #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+/// struct Product {
+///     id: i32,
+///     category: String,
+/// }
+///
+/// impl Internable for Product {
+///     type Interned = (i32, Arc<str>);
+///
+///     fn intern(&self, interner: &mut Interner) -> Self::Interned {
+///         (self.id, interner.intern_str(&self.category))
+///     }
+/// }
+/// ```
+pub trait Internable: Tuple {
+    /// Is the type of interned tuples.
+    type Interned: Tuple;
+
+    /// Returns the interned representation of the receiver, using `interner` to share the
+    /// values of its fields.
+    fn intern(&self, interner: &mut Interner) -> Self::Interned;
+}
+
+impl Internable for String {
+    type Interned = Arc<str>;
+
+    fn intern(&self, interner: &mut Interner) -> Self::Interned {
+        interner.intern_str(self)
+    }
+}
+
+impl<V> Internable for Vec<V>
+where
+    V: Tuple + Hash + 'static,
+{
+    type Interned = Arc<[V]>;
+
+    fn intern(&self, interner: &mut Interner) -> Self::Interned {
+        interner.intern_slice(self)
+    }
+}
+
+/// Deduplicates values by handing out a single shared pointer for all equal values.
+#[derive(Default)]
+pub struct Interner {
+    /// Is the set of interned strings.
+    strings: HashSet<Arc<str>>,
+
+    /// Maps the type of slice elements to the set of interned slices of that type.
+    slices: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Interner {
+    /// Creates a new empty [`Interner`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a shared pointer to a string equal to `value`.
+    pub fn intern_str(&mut self, value: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(value) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(value);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    /// Returns a shared pointer to a slice equal to `value`.
+    pub fn intern_slice<V>(&mut self, value: &[V]) -> Arc<[V]>
+    where
+        V: Hash + Eq + Clone + 'static,
+    {
+        let slices = self
+            .slices
+            .entry(TypeId::of::<V>())
+            .or_insert_with(|| Box::new(HashSet::<Arc<[V]>>::new()))
+            .downcast_mut::<HashSet<Arc<[V]>>>()
+            .unwrap(); // the entry of `V` always holds a set of slices of `V`
+
+        if let Some(interned) = slices.get(value) {
+            return interned.clone();
+        }
+        let interned: Arc<[V]> = Arc::from(value);
+        slices.insert(interned.clone());
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+        let first = interner.intern_str("shared");
+        let second = "shared".to_string().intern(&mut interner);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &interner.intern_str("other")));
+
+        let first = interner.intern_slice(&[1, 2, 3]);
+        let second = vec![1, 2, 3].intern(&mut interner);
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+}
@@ -0,0 +1,66 @@
+/*! Implements presentation logic for rendering [`Tuples`] as human-readable tables.*/
+use super::Tuples;
+use crate::Tuple;
+
+impl<T: Tuple> Tuples<T> {
+    /// Renders the tuples of the receiver in a table with aligned columns.
+    ///
+    /// **Note**: tuple types carry no column schema, so every tuple is rendered in a
+    /// single column by its `Debug` representation under the name of the tuple type.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::Tuples;
+    ///
+    /// let tuples = Tuples::<i32>::from(vec![42, 7]);
+    /// assert_eq!("| i32 |\n|-----|\n| 7   |\n| 42  |\n", tuples.to_table());
+    /// ```
+    pub fn to_table(&self) -> String {
+        let header = std::any::type_name::<T>().to_string();
+        let rows: Vec<String> = self.items().iter().map(|t| format!("{:?}", t)).collect();
+        let width = rows
+            .iter()
+            .map(|r| r.chars().count())
+            .fold(header.chars().count(), usize::max);
+
+        let mut result = String::new();
+        result.push_str(&format!("| {:<width$} |\n", header, width = width));
+        result.push_str(&format!("|{}|\n", "-".repeat(width + 2)));
+        for row in rows {
+            result.push_str(&format!("| {:<width$} |\n", row, width = width));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_table() {
+        {
+            let tuples = Tuples::<i32>::from(vec![]);
+            assert_eq!("| i32 |\n|-----|\n", tuples.to_table());
+        }
+        {
+            let tuples = Tuples::<(u32, String)>::from(vec![
+                (10, "Banana".to_string()),
+                (2, "Apple".to_string()),
+            ]);
+            let table = tuples.to_table();
+            let lines: Vec<&str> = table.lines().collect();
+
+            assert_eq!(4, lines.len());
+            assert!(lines[0].contains("u32"));
+            assert_eq!("| (2, \"Apple\")", lines[2].trim_end_matches([' ', '|']));
+            assert!(lines.iter().all(|l| l.len() == lines[0].len()));
+        }
+        {
+            let tuples = Tuples::<String>::from(vec!["Crème brûlée".to_string()]);
+            let table = tuples.to_table();
+            let widths: Vec<usize> = table.lines().map(|l| l.chars().count()).collect();
+            assert!(widths.iter().all(|&w| w == widths[0]));
+        }
+    }
+}
@@ -0,0 +1,376 @@
+/*! Implements spilling the stable tuples of relation instances to disk.
+
+Large batches of stable tuples are rarely modified once they are stable; therefore, an
+instance that is configured with a [`Spill`] moves every stable batch that reaches a size
+threshold to a file in a temporary directory. A batch file is laid out as
+
+```text
+[len: u64] [offsets: (len + 1) × u64] [tuples]
+```
+
+where every number is little-endian, every tuple is encoded separately with [`bincode`] and
+`offsets` are the positions of the encoded tuples relative to the first of them. A spilled
+batch is mapped into memory on demand and read in place: since the batch is sorted, looking
+up a tuple decodes `O(log len)` tuples of the batch rather than all of them.
+
+[`bincode`]: https://docs.rs/bincode
+*/
+use super::Tuples;
+use crate::{
+    shared::{Atomic, Shared},
+    Error, Tuple,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    convert::TryInto,
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+};
+
+/// Is the configuration for spilling the stable batches of an instance to disk.
+pub(super) struct Spill<T: Tuple> {
+    /// Is the name of the relation whose tuples are spilled.
+    name: String,
+
+    /// Is the directory in which the batch files are stored.
+    dir: Shared<tempfile::TempDir>,
+
+    /// Is the minimum number of tuples in a stable batch for the batch to be spilled.
+    threshold: usize,
+
+    /// Is used to generate unique names for batch files.
+    counter: Atomic<usize>,
+
+    /// Appends the encoding of a tuple to a buffer.
+    encode: fn(&T, &mut Vec<u8>) -> Result<(), String>,
+
+    /// Decodes a tuple from bytes.
+    decode: fn(&[u8]) -> Result<T, String>,
+}
+
+impl<T: Tuple> Spill<T> {
+    /// Creates a new [`Spill`] for the relation identified by `name`, spilling stable
+    /// batches of at least `threshold` tuples to a fresh temporary directory.
+    pub fn new(name: &str, threshold: usize) -> Result<Self, Error>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("codd-{}-", name))
+            .tempdir()
+            .map_err(|e| Error::SpillFailed {
+                name: name.into(),
+                reason: e.to_string(),
+            })?;
+
+        Ok(Self {
+            name: name.into(),
+            dir: Shared::new(dir),
+            threshold,
+            counter: Atomic::new(0),
+            encode: |tuple, buffer| {
+                bincode::serialize_into(buffer, tuple).map_err(|e| e.to_string())
+            },
+            decode: |bytes| bincode::deserialize(bytes).map_err(|e| e.to_string()),
+        })
+    }
+
+    /// Returns true if `batch` is large enough to be spilled.
+    #[inline(always)]
+    pub fn should_spill(&self, batch: &Tuples<T>) -> bool {
+        batch.len() >= self.threshold
+    }
+
+    /// Writes `batch` to a new file and returns the spilled batch.
+    pub fn store(&self, batch: &Tuples<T>) -> Result<SpilledBatch, Error> {
+        let counter = self.counter.get();
+        self.counter.set(counter + 1);
+
+        let mut header = Vec::with_capacity(WORD * (batch.len() + 2));
+        let mut tuples = Vec::new();
+        header.extend_from_slice(&(batch.len() as u64).to_le_bytes());
+        for tuple in batch.iter() {
+            header.extend_from_slice(&(tuples.len() as u64).to_le_bytes());
+            (self.encode)(tuple, &mut tuples).map_err(|reason| self.error(reason))?;
+        }
+        header.extend_from_slice(&(tuples.len() as u64).to_le_bytes());
+
+        let path = self.dir.path().join(format!("batch-{}", counter));
+        File::create(&path)
+            .and_then(|mut file| {
+                file.write_all(&header)?;
+                file.write_all(&tuples)
+            })
+            .map_err(|e| self.error(e.to_string()))?;
+
+        Ok(SpilledBatch {
+            path,
+            len: batch.len(),
+            _dir: self.dir.clone(),
+        })
+    }
+
+    /// Maps the file of `batch` into memory for reading its tuples in place.
+    pub fn open(&self, batch: &SpilledBatch) -> Result<MappedBatch<'_, T>, Error> {
+        let file = File::open(&batch.path).map_err(|e| self.error(e.to_string()))?;
+
+        // SAFETY: batch files are private to `dir` and are never modified after they
+        // are written.
+        let bytes = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| self.error(e.to_string()))?;
+        let start = WORD * (batch.len + 2);
+        if bytes.len() < start || read_word(&bytes, 0) != Some(batch.len) {
+            return Err(self.error(format!("malformed batch file {:?}", batch.path)));
+        }
+
+        Ok(MappedBatch {
+            spill: self,
+            bytes,
+            len: batch.len,
+            start,
+        })
+    }
+
+    /// Loads all tuples of `batch` from its file.
+    pub fn load(&self, batch: &SpilledBatch) -> Result<Tuples<T>, Error> {
+        let mapped = self.open(batch)?;
+        let items = (0..mapped.len())
+            .map(|i| mapped.get(i))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Tuples::from_sorted_unchecked(items))
+    }
+
+    fn error(&self, reason: String) -> Error {
+        Error::SpillFailed {
+            name: self.name.clone(),
+            reason,
+        }
+    }
+}
+
+impl<T: Tuple> PartialEq for Spill<T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl<T: Tuple> fmt::Debug for Spill<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spill")
+            .field("name", &self.name)
+            .field("dir", &self.dir.path())
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+/// Is the size in bytes of the numbers in the header of a batch file.
+const WORD: usize = std::mem::size_of::<u64>();
+
+/// Reads the little-endian number at `position` of `bytes`, if it fits in `bytes`.
+#[inline(always)]
+fn read_word(bytes: &[u8], position: usize) -> Option<usize> {
+    let word = bytes.get(position..position + WORD)?;
+    Some(u64::from_le_bytes(word.try_into().unwrap()) as usize)
+}
+
+/// Is a spilled batch whose file is mapped into memory, decoding its tuples on demand.
+pub(super) struct MappedBatch<'s, T: Tuple> {
+    /// Is the configuration by which the batch was spilled.
+    spill: &'s Spill<T>,
+
+    /// Is the content of the batch file.
+    bytes: memmap2::Mmap,
+
+    /// Is the number of tuples in the batch.
+    len: usize,
+
+    /// Is the position of the first encoded tuple in `bytes`.
+    start: usize,
+}
+
+impl<T: Tuple> MappedBatch<'_, T> {
+    /// Returns the number of tuples in the batch.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Decodes the tuple at `index` of the batch.
+    pub fn get(&self, index: usize) -> Result<T, Error> {
+        let from = read_word(&self.bytes, WORD * (index + 1));
+        let to = read_word(&self.bytes, WORD * (index + 2));
+        let bytes = from
+            .zip(to)
+            .and_then(|(from, to)| self.bytes.get(self.start + from..self.start + to))
+            .ok_or_else(|| {
+                self.spill
+                    .error(format!("tuple {} is out of bounds", index))
+            })?;
+        (self.spill.decode)(bytes).map_err(|reason| self.spill.error(reason))
+    }
+
+    /// Returns the position of the first tuple of the batch, starting at `from`, that is not
+    /// less than `tuple` by galloping over the batch like [`gallop`].
+    ///
+    /// [`gallop`]: super::helpers::gallop
+    fn lower_bound(&self, mut from: usize, tuple: &T) -> Result<usize, Error> {
+        if from < self.len && &self.get(from)? < tuple {
+            let mut step = 1;
+            while from + step < self.len && &self.get(from + step)? < tuple {
+                from += step;
+                step <<= 1;
+            }
+
+            step >>= 1;
+            while step > 0 {
+                if from + step < self.len && &self.get(from + step)? < tuple {
+                    from += step;
+                }
+                step >>= 1;
+            }
+
+            from += 1;
+        }
+        Ok(from)
+    }
+
+    /// For a sorted `items`, returns true if some of `items` appear in the batch.
+    pub fn contains_any(&self, items: &[T]) -> Result<bool, Error> {
+        let mut position = 0;
+        for item in items {
+            position = self.lower_bound(position, item)?;
+            if position == self.len {
+                break;
+            }
+            if &self.get(position)? == item {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// For a sorted `items`, removes the elements of `items` that appear in the batch like
+    /// [`retain_absent`].
+    ///
+    /// [`retain_absent`]: super::helpers::retain_absent
+    pub fn retain_absent(&self, items: &mut Vec<T>) -> Result<(), Error> {
+        let mut position = 0;
+        let mut result = Ok(());
+        items.retain(|item| {
+            if result.is_err() || position == self.len {
+                return true;
+            }
+            let found = self.lower_bound(position, item).and_then(|p| {
+                position = p;
+                Ok(p < self.len && &self.get(p)? == item)
+            });
+            match found {
+                Ok(found) => !found,
+                Err(e) => {
+                    result = Err(e);
+                    true
+                }
+            }
+        });
+        result
+    }
+
+    /// Returns true if the tuples of the batch are strictly increasing, decoding one tuple
+    /// at a time.
+    pub fn is_sorted(&self) -> Result<bool, Error> {
+        let mut previous = None;
+        for i in 0..self.len {
+            let tuple = self.get(i)?;
+            if previous.map_or(false, |p| p >= tuple) {
+                return Ok(false);
+            }
+            previous = Some(tuple);
+        }
+        Ok(true)
+    }
+}
+
+/// Is a batch of stable tuples that is stored in a file.
+#[derive(Debug)]
+pub(super) struct SpilledBatch {
+    /// Is the path to the file containing the tuples.
+    path: PathBuf,
+
+    /// Is the number of tuples in the batch.
+    len: usize,
+
+    /// Keeps the directory of the file alive while the batch exists.
+    _dir: Shared<tempfile::TempDir>,
+}
+
+impl SpilledBatch {
+    /// Returns the number of tuples in the batch.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the path to the file containing the tuples.
+    #[cfg(test)]
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl PartialEq for SpilledBatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Drop for SpilledBatch {
+    fn drop(&mut self) {
+        // the file may have already been removed with its directory:
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapped_batch() {
+        let spill = Spill::<(i32, String)>::new("r", 1).unwrap();
+        let tuples: Tuples<_> = (0..100).map(|i| (i * 2, i.to_string())).collect();
+        let batch = spill.store(&tuples).unwrap();
+        let mapped = spill.open(&batch).unwrap();
+
+        assert_eq!(100, mapped.len());
+        assert_eq!((42, "21".to_string()), mapped.get(21).unwrap());
+        assert!(mapped.get(100).is_err());
+        assert!(mapped.is_sorted().unwrap());
+        assert!(mapped
+            .contains_any(&[(1, "".into()), (198, "99".into())])
+            .unwrap());
+        assert!(!mapped
+            .contains_any(&[(1, "".into()), (200, "".into())])
+            .unwrap());
+
+        let mut items = vec![
+            (-1, "".into()),
+            (0, "0".into()),
+            (3, "".into()),
+            (4, "2".into()),
+        ];
+        mapped.retain_absent(&mut items).unwrap();
+        assert_eq!(vec![(-1, "".to_string()), (3, "".to_string())], items);
+
+        assert_eq!(tuples, spill.load(&batch).unwrap());
+    }
+
+    #[test]
+    fn test_malformed_batch() {
+        let spill = Spill::<i32>::new("r", 1).unwrap();
+        let batch = spill.store(&vec![1, 2, 3].into()).unwrap();
+        fs::write(batch.path(), [0u8; 4]).unwrap();
+        assert!(matches!(spill.open(&batch), Err(Error::SpillFailed { .. })));
+    }
+}
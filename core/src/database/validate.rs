@@ -1,44 +1,199 @@
 use crate::{
-    expression::{Difference, Expression, Visitor},
+    expression::{
+        Difference, Enumerate, Expression, Full, GroupBy, Join, Param, Project, Scan, Select,
+        TryProject, Visitor,
+    },
     Error, Tuple,
 };
 
 /// Is a [`Visitor`] that validates if an expression can be turned into a [`View`].
-/// Currently, expressions containing `Difference` are not supported.
-pub(crate) struct ViewExpressionValidator(Option<Error>);
+/// Currently, expressions containing `Full`, `Difference`, `Enumerate`, `GroupBy`, `Scan`,
+/// `Param` or `TryProject`, and `Select` or `Project` expressions with a context, are not
+/// supported; neither are `Join` expressions in the `Anti` or `LeftOuter` mode. If the view
+/// is maintained by recomputation, `Difference`, `Enumerate`, `GroupBy`, `Scan` and the
+/// joins in those modes, whose results may shrink as their inputs grow, are supported.
+///
+/// **Note**: `Full` is not range-restricted and cannot be evaluated in any context; it is
+/// rejected when the view is stored rather than when the view is first evaluated. Also,
+/// `TryProject` may fail halfway through the maintenance of a view, after the tuples that
+/// the view has missed are already stable in its dependees.
+pub(crate) struct ViewExpressionValidator {
+    error: Option<Error>,
+    recompute: bool,
+}
 
 impl ViewExpressionValidator {
-    pub fn new() -> Self {
-        Self(None)
+    pub fn new(recompute: bool) -> Self {
+        Self {
+            error: None,
+            recompute,
+        }
     }
 
     #[inline]
     pub fn into_error(self) -> Option<Error> {
-        self.0
+        self.error
     }
 }
 
 impl Visitor for ViewExpressionValidator {
-    fn visit_difference<T, L, R>(&mut self, _: &Difference<T, L, R>)
+    fn visit_full<T>(&mut self, _: &Full<T>)
+    where
+        T: Tuple,
+    {
+        self.error = Some(Error::UnsupportedExpression {
+            name: "Full".to_string(),
+            operation: "Create View".to_string(),
+        })
+    }
+
+    fn visit_difference<T, L, R>(&mut self, difference: &Difference<T, L, R>)
     where
         T: Tuple,
         L: Expression<T>,
         R: Expression<T>,
     {
-        self.0 = Some(Error::UnsupportedExpression {
-            name: "Difference".to_string(),
+        if self.recompute {
+            difference.left().visit(self);
+            difference.right().visit(self);
+        } else {
+            self.error = Some(Error::UnsupportedExpression {
+                name: "Difference".to_string(),
+                operation: "Create View".to_string(),
+            })
+        }
+    }
+
+    fn visit_join<K, L, R, Left, Right, T>(&mut self, join: &Join<K, L, R, Left, Right, T>)
+    where
+        K: Tuple,
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: Expression<L>,
+        Right: Expression<R>,
+    {
+        if self.recompute || join.mode().is_monotone() {
+            join.left().visit(self);
+            join.right().visit(self);
+        } else {
+            self.error = Some(Error::UnsupportedExpression {
+                name: format!("{:?} Join", join.mode()),
+                operation: "Create View".to_string(),
+            })
+        }
+    }
+
+    fn visit_enumerate<T, E>(&mut self, enumerate: &Enumerate<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        if self.recompute {
+            enumerate.expression().visit(self)
+        } else {
+            self.error = Some(Error::UnsupportedExpression {
+                name: "Enumerate".to_string(),
+                operation: "Create View".to_string(),
+            })
+        }
+    }
+
+    fn visit_group_by<S, K, A, E>(&mut self, group_by: &GroupBy<S, K, A, E>)
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: Expression<S>,
+    {
+        if self.recompute {
+            group_by.expression().visit(self)
+        } else {
+            self.error = Some(Error::UnsupportedExpression {
+                name: "GroupBy".to_string(),
+                operation: "Create View".to_string(),
+            })
+        }
+    }
+
+    fn visit_scan<T, A, E>(&mut self, scan: &Scan<T, A, E>)
+    where
+        T: Tuple,
+        A: Tuple,
+        E: Expression<T>,
+    {
+        if self.recompute {
+            scan.expression().visit(self)
+        } else {
+            self.error = Some(Error::UnsupportedExpression {
+                name: "Scan".to_string(),
+                operation: "Create View".to_string(),
+            })
+        }
+    }
+
+    fn visit_select<T, E>(&mut self, select: &Select<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        if select.is_contextual() {
+            self.error = Some(Error::UnsupportedExpression {
+                name: "Select with context".to_string(),
+                operation: "Create View".to_string(),
+            })
+        } else {
+            select.expression().visit(self)
+        }
+    }
+
+    fn visit_project<S, T, E>(&mut self, project: &Project<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        if project.is_contextual() {
+            self.error = Some(Error::UnsupportedExpression {
+                name: "Project with context".to_string(),
+                operation: "Create View".to_string(),
+            })
+        } else {
+            project.expression().visit(self)
+        }
+    }
+
+    fn visit_try_project<S, T, E>(&mut self, _: &TryProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        self.error = Some(Error::UnsupportedExpression {
+            name: "TryProject".to_string(),
+            operation: "Create View".to_string(),
+        })
+    }
+
+    fn visit_param<T>(&mut self, _: &Param<T>)
+    where
+        T: Tuple,
+    {
+        self.error = Some(Error::UnsupportedExpression {
+            name: "Param".to_string(),
             operation: "Create View".to_string(),
         })
     }
 }
 
-/// Validates `expression` and returns an error if it cannot be turned into a [`View`].
-pub(super) fn validate_view_expression<T, E>(expression: &E) -> Result<(), Error>
+/// Validates `expression` and returns an error if it cannot be turned into a [`View`],
+/// which is maintained by recomputation if `recompute` is true.
+pub(super) fn validate_view_expression<T, E>(expression: &E, recompute: bool) -> Result<(), Error>
 where
     T: Tuple,
     E: Expression<T>,
 {
-    let mut validator = ViewExpressionValidator::new();
+    let mut validator = ViewExpressionValidator::new(recompute);
     expression.visit(&mut validator);
     if let Some(e) = validator.into_error() {
         Err(e)
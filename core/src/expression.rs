@@ -1,38 +1,62 @@
 /*! Defines relational algebraic expressions as generic types over [`Tuple`] types.*/
 mod builder;
+mod closures;
+mod complement;
 pub(crate) mod dependency;
 mod difference;
+mod dot;
 mod empty;
+mod enumerate;
+mod filter_map;
+mod flat_project;
 mod full;
+mod group_by;
 mod intersect;
 mod join;
 mod mono;
+pub mod optimize;
+mod param;
 mod product;
 mod project;
 mod relation;
+mod scan;
 mod select;
+#[cfg(feature = "serde")]
+mod shape;
 mod singleton;
+mod try_project;
 mod union;
 pub(crate) mod view;
 
-use crate::Tuple;
+use crate::{MaybeSync, Tuple};
 pub use builder::Builder;
+pub use closures::Closures;
+pub use complement::Complement;
 pub use difference::Difference;
 pub use empty::Empty;
+pub use enumerate::Enumerate;
+pub use filter_map::FilterMap;
+pub use flat_project::FlatProject;
 pub use full::Full;
+pub use group_by::GroupBy;
 pub use intersect::Intersect;
-pub use join::Join;
+pub use join::{Join, JoinMode};
 pub use mono::Mono;
+pub use param::{Param, Params};
 pub use product::Product;
 pub use project::Project;
 pub use relation::Relation;
+pub use scan::Scan;
 pub use select::Select;
+#[cfg(feature = "serde")]
+pub use shape::Shape;
 pub use singleton::Singleton;
+pub use try_project::TryProject;
 pub use union::Union;
-pub use view::View;
+pub use view::{Counter, View, ViewRef};
 
 /// Is the trait of expressions in relational algebra that can be evaluated in a database.
-pub trait Expression<T: Tuple>: Clone + std::fmt::Debug {
+pub trait Expression<T: Tuple>: Clone + std::fmt::Debug + MaybeSync {
     /// Visits this expression by a [`Visitor`].
     fn visit<V>(&self, visitor: &mut V)
     where
@@ -42,6 +66,36 @@ pub trait Expression<T: Tuple>: Clone + std::fmt::Debug {
     fn builder(&self) -> Builder<T, Self> {
         Builder::from(self.clone())
     }
+
+    /// Returns the name of the type of tuples to which this expression evaluates.
+    ///
+    /// **Note**: the returned name is intended for diagnostics; its exact format is
+    /// not guaranteed (see [`std::any::type_name`]).
+    fn tuple_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    /// Returns the operator tree of this expression in the DOT language of Graphviz,
+    /// with a node for every operator and an edge from every operator to each of its
+    /// sub-expressions. Views appear as leaves.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Expression, expression::Relation};
+    ///
+    /// let r = Relation::<i32>::new("r");
+    /// let dot = r.builder().select(|t| t % 2 == 0).build().to_ast_dot();
+    ///
+    /// assert_eq!(
+    ///     "digraph {\n    n0 [label=\"Select\"];\n    n1 [label=\"Relation r\"];\n    n0 -> n1;\n}",
+    ///     dot
+    /// );
+    /// ```
+    fn to_ast_dot(&self) -> String {
+        let mut dot = dot::DotVisitor::new();
+        self.visit(&mut dot);
+        dot.into_dot()
+    }
 }
 
 impl<T, E> Expression<T> for &E
@@ -90,6 +144,30 @@ where
     }
 }
 
+/// Turns a tuple into a [`Singleton`] expression, allowing bare tuples to appear where
+/// expressions are expected.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, Expression};
+///
+/// let mut db = Database::new();
+/// let r = db.add_relation::<i32>("r").unwrap();
+/// db.insert(&r, vec![1, 2].into());
+///
+/// let union = r.builder().union(42).build();
+///
+/// assert_eq!(vec![1, 2, 42], db.evaluate(&union).unwrap().into_tuples());
+/// ```
+impl<T> IntoExpression<T, Singleton<T>> for T
+where
+    T: Tuple,
+{
+    fn into_expression(self) -> Singleton<T> {
+        Singleton::new(self)
+    }
+}
+
 /// Is the trait of objects that visit sub-expressions of an [`Expression`]. The default
 /// implementation guides the visitor through all sub-expressions of the expressions that
 /// is visited.
@@ -118,6 +196,14 @@ pub trait Visitor: Sized {
         walk_singlenton(self, singleton)
     }
 
+    /// Visits a [`Param`] expression.
+    fn visit_param<T>(&mut self, param: &Param<T>)
+    where
+        T: Tuple,
+    {
+        walk_param(self, param)
+    }
+
     /// Visits a [`Relation`] expression.
     fn visit_relation<T>(&mut self, relation: &Relation<T>)
     where
@@ -175,6 +261,36 @@ pub trait Visitor: Sized {
         walk_project(self, project);
     }
 
+    /// Visits a [`FilterMap`] expression.
+    fn visit_filter_map<S, T, E>(&mut self, filter_map: &FilterMap<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        walk_filter_map(self, filter_map);
+    }
+
+    /// Visits a [`FlatProject`] expression.
+    fn visit_flat_project<S, T, E>(&mut self, flat_project: &FlatProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        walk_flat_project(self, flat_project);
+    }
+
+    /// Visits a [`TryProject`] expression.
+    fn visit_try_project<S, T, E>(&mut self, try_project: &TryProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        walk_try_project(self, try_project);
+    }
+
     /// Visits a [`Product`] expression.    
     fn visit_product<L, R, Left, Right, T>(&mut self, product: &Product<L, R, Left, Right, T>)
     where
@@ -200,6 +316,36 @@ pub trait Visitor: Sized {
         walk_join(self, join);
     }
 
+    /// Visits an [`Enumerate`] expression.
+    fn visit_enumerate<T, E>(&mut self, enumerate: &Enumerate<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        walk_enumerate(self, enumerate);
+    }
+
+    /// Visits a [`GroupBy`] expression.
+    fn visit_group_by<S, K, A, E>(&mut self, group_by: &GroupBy<S, K, A, E>)
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: Expression<S>,
+    {
+        walk_group_by(self, group_by);
+    }
+
+    /// Visits a [`Scan`] expression.
+    fn visit_scan<T, A, E>(&mut self, scan: &Scan<T, A, E>)
+    where
+        T: Tuple,
+        A: Tuple,
+        E: Expression<T>,
+    {
+        walk_scan(self, scan);
+    }
+
     /// Visits a [`View`] expression.    
     fn visit_view<T, E>(&mut self, view: &View<T, E>)
     where
@@ -234,6 +380,14 @@ where
     // nothing to do
 }
 
+fn walk_param<T, V>(_: &mut V, _: &Param<T>)
+where
+    T: Tuple,
+    V: Visitor,
+{
+    // nothing to do
+}
+
 fn walk_relation<T, V>(_: &mut V, _: &Relation<T>)
 where
     T: Tuple,
@@ -294,6 +448,36 @@ where
     project.expression().visit(visitor);
 }
 
+fn walk_filter_map<S, T, E, V>(visitor: &mut V, filter_map: &FilterMap<S, T, E>)
+where
+    T: Tuple,
+    S: Tuple,
+    E: Expression<S>,
+    V: Visitor,
+{
+    filter_map.expression().visit(visitor);
+}
+
+fn walk_flat_project<S, T, E, V>(visitor: &mut V, flat_project: &FlatProject<S, T, E>)
+where
+    T: Tuple,
+    S: Tuple,
+    E: Expression<S>,
+    V: Visitor,
+{
+    flat_project.expression().visit(visitor);
+}
+
+fn walk_try_project<S, T, E, V>(visitor: &mut V, try_project: &TryProject<S, T, E>)
+where
+    T: Tuple,
+    S: Tuple,
+    E: Expression<S>,
+    V: Visitor,
+{
+    try_project.expression().visit(visitor);
+}
+
 fn walk_product<L, R, Left, Right, T, V>(visitor: &mut V, product: &Product<L, R, Left, Right, T>)
 where
     L: Tuple,
@@ -321,6 +505,36 @@ where
     join.right().visit(visitor);
 }
 
+fn walk_enumerate<T, E, V>(visitor: &mut V, enumerate: &Enumerate<T, E>)
+where
+    T: Tuple,
+    E: Expression<T>,
+    V: Visitor,
+{
+    enumerate.expression().visit(visitor);
+}
+
+fn walk_group_by<S, K, A, E, V>(visitor: &mut V, group_by: &GroupBy<S, K, A, E>)
+where
+    S: Tuple,
+    K: Tuple,
+    A: Tuple,
+    E: Expression<S>,
+    V: Visitor,
+{
+    group_by.expression().visit(visitor);
+}
+
+fn walk_scan<T, A, E, V>(visitor: &mut V, scan: &Scan<T, A, E>)
+where
+    T: Tuple,
+    A: Tuple,
+    E: Expression<T>,
+    V: Visitor,
+{
+    scan.expression().visit(visitor);
+}
+
 fn walk_view<T, E, V>(_: &mut V, _: &View<T, E>)
 where
     T: Tuple,
@@ -1,5 +1,6 @@
 use super::*;
-use crate::Tuple;
+use crate::{database::ExpressionExt, Database, Error, Tuple};
+use either::Either;
 use std::marker::PhantomData;
 
 /// Is a builder for building [`Expression`] values.
@@ -33,7 +34,10 @@ where
     ///
     /// assert_eq!(vec!["apple", "banana", "cherry"], db.evaluate(&lower).unwrap().into_tuples());
     /// ```
-    pub fn project<T>(self, f: impl FnMut(&L) -> T + 'static) -> Builder<T, Project<L, T, Left>>
+    pub fn project<T>(
+        self,
+        f: impl FnMut(&L) -> T + MaybeSync + 'static,
+    ) -> Builder<T, Project<L, T, Left>>
     where
         T: Tuple,
     {
@@ -43,6 +47,227 @@ where
         }
     }
 
+    /// Builds a [`Project`] expression over the receiver's expression that projects every
+    /// tuple to a fixed-size array, e.g., to turn tuples of homogeneous columns into arrays.
+    /// Arrays of any length of [`Tuple`] elements are tuples themselves; they are ordered
+    /// lexicographically.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let points = db.add_relation::<(i32, i32, i32)>("points").unwrap();
+    ///
+    /// db.insert(&points, vec![(3, 2, 1), (1, 2, 3)].into());
+    ///
+    /// let arrays = points.builder().project_array(|&(x, y, z)| [x, y, z]).build();
+    ///
+    /// assert_eq!(vec![[1, 2, 3], [3, 2, 1]], db.evaluate(&arrays).unwrap().into_tuples());
+    /// ```
+    pub fn project_array<V, const N: usize>(
+        self,
+        f: impl FnMut(&L) -> [V; N] + MaybeSync + 'static,
+    ) -> Builder<[V; N], Project<L, [V; N], Left>>
+    where
+        V: Tuple,
+    {
+        self.project(f)
+    }
+
+    /// Builds a [`Project`] expression over the receiver's expression that pairs every tuple
+    /// with a column computed by `f`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    ///
+    /// db.insert(&numbers, vec![1, 2, 3].into());
+    ///
+    /// let squares = numbers.builder().with_column(|t| t * t).build();
+    ///
+    /// assert_eq!(vec![(1, 1), (2, 4), (3, 9)], db.evaluate(&squares).unwrap().into_tuples());
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn with_column<C>(
+        self,
+        mut f: impl FnMut(&L) -> C + MaybeSync + 'static,
+    ) -> Builder<(L, C), Project<L, (L, C), Left>>
+    where
+        C: Tuple,
+    {
+        self.project(move |t| (t.clone(), f(t)))
+    }
+
+    /// Builds a [`Project`] expression over the receiver's expression like [`Builder::project`]
+    /// where the results of `f` are memoized by their input tuples. The memoized results are
+    /// shared by all clones of the resulting expression and reused across evaluations.
+    ///
+    /// **Note**: the memoized results are never evicted; use `project_memoized` only for
+    /// expensive mappers over a bounded set of tuples.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let fruit = db.add_relation::<String>("R").unwrap();
+    ///
+    /// db.insert(&fruit, vec!["Apple".to_string(), "BANANA".into(), "cherry".into()].into());
+    ///
+    /// let lower = fruit.builder().project_memoized(|t| t.to_lowercase()).build();
+    ///
+    /// assert_eq!(vec!["apple", "banana", "cherry"], db.evaluate(&lower).unwrap().into_tuples());
+    /// ```
+    pub fn project_memoized<T>(
+        self,
+        mut f: impl FnMut(&L) -> T + MaybeSync + 'static,
+    ) -> Builder<T, Project<L, T, Left>>
+    where
+        L: 'static,
+        T: Tuple + 'static,
+    {
+        let mut memo = std::collections::BTreeMap::<L, T>::new();
+        self.project(move |t| {
+            if let Some(result) = memo.get(t) {
+                return result.clone();
+            }
+            let result = f(t);
+            memo.insert(t.clone(), result.clone());
+            result
+        })
+    }
+
+    /// Builds a [`FilterMap`] expression over the receiver's expression.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let words = db.add_relation::<String>("Words").unwrap();
+    ///
+    /// db.insert(&words, vec!["1".to_string(), "two".into(), "3".into()].into());
+    ///
+    /// let numbers = words.builder().filter_map(|t| t.parse::<i32>().ok()).build();
+    ///
+    /// assert_eq!(vec![1, 3], db.evaluate(&numbers).unwrap().into_tuples());
+    /// ```
+    pub fn filter_map<T>(
+        self,
+        f: impl FnMut(&L) -> Option<T> + MaybeSync + 'static,
+    ) -> Builder<T, FilterMap<L, T, Left>>
+    where
+        T: Tuple,
+    {
+        Builder {
+            expression: FilterMap::new(self.expression, f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`FlatProject`] expression over the receiver's expression.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    ///
+    /// db.insert(&numbers, vec![1, 2].into());
+    ///
+    /// let signed = numbers.builder().flat_project(|&t| vec![-t, t]).build();
+    ///
+    /// assert_eq!(vec![-2, -1, 1, 2], db.evaluate(&signed).unwrap().into_tuples());
+    /// ```
+    pub fn flat_project<T>(
+        self,
+        f: impl FnMut(&L) -> Vec<T> + MaybeSync + 'static,
+    ) -> Builder<T, FlatProject<L, T, Left>>
+    where
+        T: Tuple,
+    {
+        Builder {
+            expression: FlatProject::new(self.expression, f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`TryProject`] expression over the receiver's expression. The evaluation of
+    /// the resulting expression fails if `f` returns an error for some tuple.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Error, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let words = db.add_relation::<String>("Words").unwrap();
+    ///
+    /// db.insert(&words, vec!["1".to_string(), "two".into()].into());
+    ///
+    /// let numbers = words
+    ///     .builder()
+    ///     .try_project(|t| t.parse::<i32>().map_err(|e| format!("{}: {}", t, e)))
+    ///     .build();
+    ///
+    /// assert!(matches!(db.evaluate(&numbers), Err(Error::ProjectionFailed { .. })));
+    /// ```
+    pub fn try_project<T>(
+        self,
+        f: impl FnMut(&L) -> Result<T, String> + MaybeSync + 'static,
+    ) -> Builder<T, TryProject<L, T, Left>>
+    where
+        T: Tuple,
+    {
+        Builder {
+            expression: TryProject::new(self.expression, f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`FilterMap`] expression over the receiver's expression that enriches every
+    /// tuple with its entry in `table`, looked up by `key`, and applies `mapper` on the tuple
+    /// and its entry. Tuples without an entry in `table` are dropped.
+    ///
+    /// **Note**: `lookup` is a lightweight alternative to [`Builder::with_key`] for joining
+    /// against a small static table that does not change with the database.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut db = Database::new();
+    /// let ids = db.add_relation::<i32>("Ids").unwrap();
+    ///
+    /// db.insert(&ids, vec![1, 2, 3].into());
+    ///
+    /// let names: HashMap<_, _> = vec![(1, "one"), (3, "three")].into_iter().collect();
+    /// let named = ids.builder().lookup(|&t| t, names, |&t, &n| (t, n.to_string())).build();
+    ///
+    /// assert_eq!(
+    ///     vec![(1, "one".to_string()), (3, "three".into())],
+    ///     db.evaluate(&named).unwrap().into_tuples()
+    /// );
+    /// ```
+    pub fn lookup<K, V, T>(
+        self,
+        key: impl Fn(&L) -> K + MaybeSync + 'static,
+        table: std::collections::HashMap<K, V>,
+        mapper: impl Fn(&L, &V) -> T + MaybeSync + 'static,
+    ) -> Builder<T, FilterMap<L, T, Left>>
+    where
+        K: Eq + std::hash::Hash + MaybeSync + 'static,
+        V: MaybeSync + 'static,
+        T: Tuple,
+    {
+        self.filter_map(move |t| table.get(&key(t)).map(|v| mapper(t, v)))
+    }
+
     /// Builds a [`Select`] expression over the receiver's expression.
     ///
     /// **Example**:
@@ -58,13 +283,112 @@ where
     ///
     /// assert_eq!(vec!["Apple", "BANANA"], db.evaluate(&select).unwrap().into_tuples());
     /// ```
-    pub fn select(self, f: impl FnMut(&L) -> bool + 'static) -> Builder<L, Select<L, Left>> {
+    pub fn select(
+        self,
+        f: impl FnMut(&L) -> bool + MaybeSync + 'static,
+    ) -> Builder<L, Select<L, Left>> {
         Builder {
             expression: Select::new(self.expression, f),
             _marker: PhantomData,
         }
     }
 
+    /// Builds a [`Select`] expression over the receiver's expression with a three-valued
+    /// predicate: `Some(true)` keeps a tuple, whereas `Some(false)` and `None` (unknown)
+    /// drop it, following the semantics of `WHERE` clauses over `NULL` values in SQL.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let ages = db.add_relation::<(String, Option<i32>)>("Ages").unwrap();
+    ///
+    /// db.insert(&ages, vec![("alice".to_string(), Some(30)), ("bob".into(), None), ("carol".into(), Some(15))].into());
+    ///
+    /// let adults = ages.builder().select3(|t| t.1.map(|age| age >= 18)).build();
+    ///
+    /// assert_eq!(vec![("alice".to_string(), Some(30))], db.evaluate(&adults).unwrap().into_tuples());
+    /// ```
+    pub fn select3(
+        self,
+        mut f: impl FnMut(&L) -> Option<bool> + MaybeSync + 'static,
+    ) -> Builder<L, Select<L, Left>> {
+        self.select(move |t| f(t) == Some(true))
+    }
+
+    /// Builds a [`Select`] expression over the receiver's expression that selects the tuples
+    /// whose `key` is less than `bound`.
+    ///
+    /// **Note**: `key` must be monotone over the sorted order of the tuples of the receiver's
+    /// expression. This allows the selected tuples to be found by galloping over the sorted
+    /// batches of tuples rather than scanning them (see [`Select::ranged`]).
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<(i32, String)>("R").unwrap();
+    ///
+    /// db.insert(&r, vec![(1, "a".to_string()), (2, "b".into()), (3, "c".into())].into());
+    ///
+    /// let select = r.builder().select_sorted(|t| t.0, 3).build();
+    ///
+    /// assert_eq!(
+    ///     vec![(1, "a".to_string()), (2, "b".into())],
+    ///     db.evaluate(&select).unwrap().into_tuples()
+    /// );
+    /// ```
+    pub fn select_sorted<K>(
+        self,
+        mut key: impl FnMut(&L) -> K + MaybeSync + 'static,
+        bound: K,
+    ) -> Builder<L, Select<L, Left>>
+    where
+        L: 'static,
+        K: Ord + MaybeSync + 'static,
+    {
+        use std::cmp::Ordering;
+
+        Builder {
+            expression: Select::ranged(self.expression, move |t| {
+                if key(t) < bound {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater
+                }
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`Select`] expression over the receiver's expression that selects `target`
+    /// if it exists. Instead of comparing every tuple against `target`, the tuple is looked
+    /// up by galloping over the sorted batches of tuples (see [`Select::ranged`]).
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    ///
+    /// db.insert(&r, (0..10_000).collect::<Vec<_>>().into());
+    ///
+    /// assert_eq!(vec![42], db.evaluate(&r.builder().find(42).build()).unwrap().into_tuples());
+    /// assert!(db.evaluate(&r.builder().find(-1).build()).unwrap().is_empty());
+    /// ```
+    pub fn find(self, target: L) -> Builder<L, Select<L, Left>>
+    where
+        L: 'static,
+    {
+        Builder {
+            expression: Select::ranged(self.expression, move |t| t.cmp(&target)),
+            _marker: PhantomData,
+        }
+    }
+
     /// Builds an [`Intersect`] expression with the receiver's expression on left and `other` on right.
     ///
     /// **Example**:
@@ -151,6 +475,144 @@ where
         }
     }
 
+    /// Builds an [`Enumerate`] expression over the receiver's expression.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<char>("R").unwrap();
+    ///
+    /// db.insert(&r, vec!['c', 'a', 'b'].into());
+    ///
+    /// let enumerate = r.builder().enumerate().build();
+    ///
+    /// assert_eq!(vec![(0, 'a'), (1, 'b'), (2, 'c')], db.evaluate(&enumerate).unwrap().into_tuples());
+    /// ```
+    pub fn enumerate(self) -> Builder<(usize, L), Enumerate<L, Left>> {
+        Builder {
+            expression: Enumerate::new(self.expression),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`GroupBy`] expression over the receiver's expression that groups its tuples
+    /// by `key` and aggregates every group by folding its tuples with `fold`, starting from
+    /// `init`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3, 4, 5].into());
+    ///
+    /// let counts = r.builder().group_by(|t| t % 2 == 0, 0, |count, _| count + 1).build();
+    ///
+    /// assert_eq!(vec![(false, 3), (true, 2)], db.evaluate(&counts).unwrap().into_tuples());
+    /// ```
+    pub fn group_by<K, A>(
+        self,
+        key: impl FnMut(&L) -> K + MaybeSync + 'static,
+        init: A,
+        fold: impl FnMut(A, &L) -> A + MaybeSync + 'static,
+    ) -> Builder<(K, A), GroupBy<L, K, A, Left>>
+    where
+        K: Tuple,
+        A: Tuple,
+    {
+        Builder {
+            expression: GroupBy::new(self.expression, key, init, fold),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`Scan`] expression over the receiver's expression that pairs every tuple
+    /// with the value that `f` accumulates over the tuples up to and including that tuple in
+    /// their sorted order, starting from `init`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// let sums = r.builder().scan(0, |sum, t| sum + t).build();
+    ///
+    /// assert_eq!(vec![(1, 1), (2, 3), (3, 6)], db.evaluate(&sums).unwrap().into_tuples());
+    /// ```
+    pub fn scan<A>(
+        self,
+        init: A,
+        f: impl FnMut(&A, &L) -> A + MaybeSync + 'static,
+    ) -> Builder<(L, A), Scan<L, A, Left>>
+    where
+        A: Tuple,
+    {
+        Builder {
+            expression: Scan::new(self.expression, init, f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Stores the receiver's expression as a view in `database` and returns a builder over
+    /// the resulting [`View`]. Downstream expressions read the materialized tuples of the
+    /// view instead of recomputing the receiver's expression.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3, 4].into());
+    ///
+    /// let evens = r.builder().select(|t| t % 2 == 0).cache(&mut db).unwrap();
+    /// let doubled = evens.project(|t| t * 2).build();
+    ///
+    /// assert_eq!(vec![4, 8], db.evaluate(&doubled).unwrap().into_tuples());
+    /// ```
+    pub fn cache(self, database: &mut Database) -> Result<Builder<L, View<L, Left>>, Error>
+    where
+        L: 'static,
+        Left: ExpressionExt<L> + 'static,
+    {
+        Ok(Builder {
+            expression: database.store_view(self.expression)?,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Builds the receiver's expression and stores it as a view in `database` like
+    /// [`Database::store_view`], returning the resulting [`View`].
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    /// let evens = r.builder().select(|t| t % 2 == 0).store(&mut db).unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3, 4].into());
+    ///
+    /// assert_eq!(vec![2, 4], db.evaluate(&evens).unwrap().into_tuples());
+    /// ```
+    pub fn store(self, database: &mut Database) -> Result<View<L, Left>, Error>
+    where
+        L: 'static,
+        Left: ExpressionExt<L> + 'static,
+    {
+        self.cache(database).map(Builder::build)
+    }
+
     /// Combines the receiver's expression with `other` in a temporary builder, which then can be turned into
     /// a [`Product`] expression using a combining closure provided by method `on`.
     ///
@@ -211,7 +673,10 @@ where
     ///     
     /// assert_eq!(vec!["Apple0", "Cherry4"], db.evaluate(&join).unwrap().into_tuples());
     /// ```
-    pub fn with_key<K>(self, f: impl FnMut(&L) -> K + 'static) -> WithKeyBuilder<K, L, Left>
+    pub fn with_key<K>(
+        self,
+        f: impl FnMut(&L) -> K + MaybeSync + 'static,
+    ) -> WithKeyBuilder<K, L, Left>
     where
         K: Tuple,
     {
@@ -221,12 +686,231 @@ where
         }
     }
 
+    /// Applies `f` to the receiver if `cond` is true and returns the receiver unchanged
+    /// otherwise. It is useful for building expressions from configuration flags.
+    ///
+    /// **Note**: `f` must preserve the type of the receiver's expression. Because the
+    /// closures of expressions are not part of their types, a configurable step is
+    /// typically built on a neutral expression, such as a [`Select`] that keeps every tuple,
+    /// which `f` replaces with an expression of the same type.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let numbers = db.add_relation::<i32>("numbers").unwrap();
+    ///
+    /// db.insert(&numbers, vec![1, 2, 3, 4].into());
+    ///
+    /// let query = |evens_only| {
+    ///     numbers
+    ///         .builder()
+    ///         .select(|_| true)
+    ///         .when(evens_only, |_| numbers.builder().select(|t| t % 2 == 0))
+    ///         .build()
+    /// };
+    ///
+    /// assert_eq!(vec![2, 4], db.evaluate(&query(true)).unwrap().into_tuples());
+    /// assert_eq!(vec![1, 2, 3, 4], db.evaluate(&query(false)).unwrap().into_tuples());
+    /// ```
+    pub fn when(self, cond: bool, f: impl FnOnce(Self) -> Self) -> Self {
+        if cond {
+            f(self)
+        } else {
+            self
+        }
+    }
+
+    /// Wraps the receiver's expression in a [`Mono`] expression, erasing its concrete type.
+    ///
+    /// **Note**: every operator of a builder nests the type of its sub-expression in the
+    /// type of its resulting expression; long chains of operators produce deeply nested
+    /// types that slow down compilation. Sealing the builder between operators caps the
+    /// nesting: the operators that follow a sealed builder act on a [`Mono`] expression
+    /// and can be sealed again.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression, expression::Mono};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3, 4].into());
+    ///
+    /// let query: Mono<i32> = r
+    ///     .builder()
+    ///     .seal()
+    ///     .select(|t| t % 2 == 0)
+    ///     .seal()
+    ///     .project(|t| t * 10)
+    ///     .seal()
+    ///     .build();
+    ///
+    /// assert_eq!(vec![20, 40], db.evaluate(&query).unwrap().into_tuples());
+    /// ```
+    pub fn seal(self) -> Builder<L, Mono<L>>
+    where
+        L: 'static,
+        Left: Into<Mono<L>>,
+    {
+        Builder {
+            expression: self.expression.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the receiver unchanged while asserting that the tuples of its expression are
+    /// of type `T`. It documents the type of the tuples at a stage of a long chain of
+    /// builder methods and turns an unexpected type into a compile error at that stage
+    /// rather than at the end of the chain.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// let labels = r
+    ///     .builder()
+    ///     .project(|&t| (t, format!("#{}", t)))
+    ///     .typed::<(i32, String)>()
+    ///     .select(|(t, _)| t % 2 == 1)
+    ///     .project(|(_, label)| label.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(vec!["#1", "#3"], db.evaluate(&labels).unwrap().into_tuples());
+    /// ```
+    ///
+    /// A different type fails to compile:
+    /// ```rust,compile_fail
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    ///
+    /// let labels = r.builder().project(|&t| (t, t)).typed::<(i32, String)>();
+    /// ```
+    pub fn typed<T>(self) -> Builder<T, Left>
+    where
+        T: Tuple,
+        Left: Expression<T>,
+    {
+        Builder {
+            expression: self.expression,
+            _marker: PhantomData,
+        }
+    }
+
     /// Builds an expression from the receiver.
     pub fn build(self) -> Left {
         self.into_expression()
     }
 }
 
+impl<A, B, Left> Builder<Either<A, B>, Left>
+where
+    A: Tuple,
+    B: Tuple,
+    Left: Expression<Either<A, B>>,
+{
+    /// Splits the receiver's expression over [`Either`] tuples into a pair of [`FilterMap`]
+    /// expressions over its `Left` and its `Right` values respectively.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    /// use either::Either;
+    ///
+    /// let mut db = Database::new();
+    /// let mixed = db.add_relation::<Either<i32, String>>("Mixed").unwrap();
+    ///
+    /// db.insert(&mixed, vec![Either::Left(1), Either::Right("one".to_string())].into());
+    ///
+    /// let (numbers, words) = mixed.builder().split_either();
+    ///
+    /// assert_eq!(vec![1], db.evaluate(&numbers.build()).unwrap().into_tuples());
+    /// assert_eq!(vec!["one"], db.evaluate(&words.build()).unwrap().into_tuples());
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn split_either(
+        self,
+    ) -> (
+        Builder<A, FilterMap<Either<A, B>, A, Left>>,
+        Builder<B, FilterMap<Either<A, B>, B, Left>>,
+    ) {
+        let lefts = Builder::from(self.expression.clone())
+            .filter_map(|t: &Either<A, B>| t.as_ref().left().cloned());
+        let rights = self.filter_map(|t| t.as_ref().right().cloned());
+        (lefts, rights)
+    }
+}
+
+impl<K, A, Left> Builder<(K, A), Left>
+where
+    K: Tuple,
+    A: Tuple,
+    Left: Expression<(K, A)>,
+{
+    /// Builds a [`Select`] expression over the receiver's expression over `(key, aggregate)`
+    /// pairs, e.g., the groups of a [`GroupBy`], that keeps the pairs whose aggregate
+    /// satisfies `f` (like `HAVING` in SQL).
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3, 4, 5].into());
+    ///
+    /// let sums = r
+    ///     .builder()
+    ///     .group_by(|t| t % 3, 0, |sum, t| sum + t)
+    ///     .having(|&sum| sum > 5)
+    ///     .build();
+    ///
+    /// assert_eq!(vec![(2, 7)], db.evaluate(&sums).unwrap().into_tuples());
+    /// ```
+    pub fn having(
+        self,
+        mut f: impl FnMut(&A) -> bool + MaybeSync + 'static,
+    ) -> Builder<(K, A), Select<(K, A), Left>> {
+        self.select(move |(_, aggregate)| f(aggregate))
+    }
+}
+
+impl<T, Left> Builder<Vec<T>, Left>
+where
+    T: Tuple,
+    Left: Expression<Vec<T>>,
+{
+    /// Builds a [`FlatProject`] expression that flattens the receiver's expression over
+    /// vectors of tuples into an expression over the elements of the vectors.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let lists = db.add_relation::<Vec<i32>>("Lists").unwrap();
+    ///
+    /// db.insert(&lists, vec![vec![1, 2], vec![3], vec![]].into());
+    ///
+    /// let elements = lists.builder().flatten().build();
+    ///
+    /// assert_eq!(vec![1, 2, 3], db.evaluate(&elements).unwrap().into_tuples());
+    /// ```
+    pub fn flatten(self) -> Builder<T, FlatProject<Vec<T>, T, Left>> {
+        self.flat_project(|v| v.clone())
+    }
+}
+
 impl<T, E> IntoExpression<T, E> for Builder<T, E>
 where
     T: Tuple,
@@ -271,13 +955,106 @@ where
 {
     pub fn on<T: Tuple>(
         self,
-        f: impl FnMut(&L, &R) -> T + 'static,
+        f: impl FnMut(&L, &R) -> T + MaybeSync + 'static,
     ) -> Builder<T, Product<L, R, Left, Right, T>> {
         Builder {
             expression: Product::new(self.left, self.right, f),
             _marker: PhantomData,
         }
     }
+
+    /// Builds a [`Product`] expression like [`ProductBuilder::on`] where the pairs of tuples
+    /// for which `f` returns `None` are dropped.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    /// let s = db.add_relation::<i32>("S").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3].into());
+    /// db.insert(&s, vec![1, 2].into());
+    ///
+    /// let diff = r
+    ///     .builder()
+    ///     .product(s)
+    ///     .on_filter(|&l, &r| if l > r { Some(l - r) } else { None }) // drop non-positives
+    ///     .build();
+    ///
+    /// assert_eq!(vec![1, 2], db.evaluate(&diff).unwrap().into_tuples());
+    /// ```
+    pub fn on_filter<T: Tuple>(
+        self,
+        f: impl FnMut(&L, &R) -> Option<T> + MaybeSync + 'static,
+    ) -> Builder<T, Product<L, R, Left, Right, T>> {
+        Builder {
+            expression: Product::filtered(self.left, self.right, f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`Product`] expression whose tuples are the pairs of the tuples of the
+    /// receiver's sub-expressions.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    /// let s = db.add_relation::<char>("S").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2].into());
+    /// db.insert(&s, vec!['a'].into());
+    ///
+    /// let pairs = r.builder().product(s).flat().build();
+    ///
+    /// assert_eq!(vec![(1, 'a'), (2, 'a')], db.evaluate(&pairs).unwrap().into_tuples());
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn flat(self) -> Builder<(L, R), Product<L, R, Left, Right, (L, R)>> {
+        self.on(|l, r| (l.clone(), r.clone()))
+    }
+}
+
+impl<A, B, R, Left, Right> ProductBuilder<(A, B), R, Left, Right>
+where
+    A: Tuple,
+    B: Tuple,
+    R: Tuple,
+    Left: Expression<(A, B)>,
+    Right: Expression<R>,
+{
+    /// Builds a [`Product`] expression over a left sub-expression of pairs, such as another
+    /// product built by [`ProductBuilder::flat`], whose tuples are flat triples rather than
+    /// nested pairs.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    /// let s = db.add_relation::<char>("S").unwrap();
+    /// let t = db.add_relation::<bool>("T").unwrap();
+    ///
+    /// db.insert(&r, vec![1].into());
+    /// db.insert(&s, vec!['a', 'b'].into());
+    /// db.insert(&t, vec![true].into());
+    ///
+    /// let triples = r.builder().product(s).flat().product(t).flat3().build();
+    ///
+    /// assert_eq!(
+    ///     vec![(1, 'a', true), (1, 'b', true)],
+    ///     db.evaluate(&triples).unwrap().into_tuples()
+    /// );
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn flat3(self) -> Builder<(A, B, R), Product<(A, B), R, Left, Right, (A, B, R)>> {
+        self.on(|(a, b), r| (a.clone(), b.clone(), r.clone()))
+    }
 }
 
 pub struct WithKeyBuilder<K, L, Left>
@@ -287,7 +1064,7 @@ where
     Left: Expression<L>,
 {
     expression: Left,
-    key: Box<dyn FnMut(&L) -> K>,
+    key: Box<closure!(FnMut(&L) -> K)>,
 }
 
 impl<K, L, Left> WithKeyBuilder<K, L, Left>
@@ -333,7 +1110,7 @@ where
 {
     pub fn on<T: Tuple>(
         self,
-        f: impl FnMut(&K, &L, &R) -> T + 'static,
+        f: impl FnMut(&K, &L, &R) -> T + MaybeSync + 'static,
     ) -> Builder<T, Join<K, L, R, Left, Right, T>> {
         Builder {
             expression: Join::new(
@@ -346,4 +1123,178 @@ where
             _marker: PhantomData,
         }
     }
+
+    /// Builds a [`Join`] expression like [`JoinBuilder::on`] where the pairs of joined tuples
+    /// for which `f` returns `None` are dropped.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let fruit = db.add_relation::<(i32, String)>("Fruit").unwrap();
+    /// let price = db.add_relation::<(i32, i32)>("Price").unwrap();
+    ///
+    /// db.insert(&fruit, vec![
+    ///     (0, "Apple".to_string()),
+    ///     (1, "Banana".to_string()),
+    ///     (2, "Cherry".to_string())
+    /// ].into());
+    /// db.insert(&price, vec![(0, 550), (1, 210), (2, 1000)].into());
+    ///
+    /// let cheap = fruit
+    ///     .builder()
+    ///     .with_key(|t| t.0)
+    ///     .join(price.builder().with_key(|t| t.0))
+    ///     .on_filter(|_, l, r| if r.1 < 600 { Some(l.1.clone()) } else { None })
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     vec!["Apple".to_string(), "Banana".to_string()],
+    ///     db.evaluate(&cheap).unwrap().into_tuples()
+    /// );
+    /// ```
+    pub fn on_filter<T: Tuple>(
+        self,
+        f: impl FnMut(&K, &L, &R) -> Option<T> + MaybeSync + 'static,
+    ) -> Builder<T, Join<K, L, R, Left, Right, T>> {
+        Builder {
+            expression: Join::filtered(
+                self.left.expression,
+                self.right.expression,
+                self.left.key,
+                self.right.key,
+                f,
+            ),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`Join`] expression like [`JoinBuilder::on`] that pairs every resulting tuple
+    /// with the number of pairs of joined tuples for which `f` returns that tuple.
+    ///
+    /// **Note**: the multiplicities are computed by a [`GroupBy`] over the joined pairs,
+    /// whose counts change as new pairs arrive. Therefore, [`Database::store_view`] rejects
+    /// the resulting expression with [`Error::UnsupportedExpression`]; it can be stored by
+    /// [`Database::store_view_recompute`], which recounts the pairs whenever the view is
+    /// evaluated after its sub-expressions have changed.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<(i32, char)>("R").unwrap();
+    /// let s = db.add_relation::<(i32, char)>("S").unwrap();
+    ///
+    /// db.insert(&r, vec![(0, 'a'), (0, 'b'), (1, 'c')].into());
+    /// db.insert(&s, vec![(0, 'x'), (1, 'y'), (1, 'z')].into());
+    ///
+    /// let counted = r
+    ///     .builder()
+    ///     .with_key(|t| t.0)
+    ///     .join(s.builder().with_key(|t| t.0))
+    ///     .on_counted(|&k, _, _| k)
+    ///     .build();
+    ///
+    /// assert_eq!(vec![(0, 2), (1, 2)], db.evaluate(&counted).unwrap().into_tuples());
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn on_counted<T: Tuple>(
+        self,
+        mut f: impl FnMut(&K, &L, &R) -> T + MaybeSync + 'static,
+    ) -> Builder<(T, usize), GroupBy<(T, L, R), T, usize, Join<K, L, R, Left, Right, (T, L, R)>>>
+    {
+        // the joined tuples are kept alongside the result of `f` so that the pairs that
+        // produce the same tuple are not collapsed before they are counted:
+        self.on(move |k, l, r| (f(k, l, r), l.clone(), r.clone()))
+            .group_by(|t| t.0.clone(), 0, |count, _| count + 1)
+    }
+
+    /// Returns a [`JoinModeBuilder`] for building a [`Join`] of the receiver's
+    /// sub-expressions whose rows are determined by `mode` (see [`Join::with_mode`]).
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression, expression::JoinMode};
+    ///
+    /// let mut db = Database::new();
+    /// let fruit = db.add_relation::<(i32, String)>("R").unwrap();
+    /// let numbers = db.add_relation::<i32>("S").unwrap();
+    ///
+    /// db.insert(&fruit, vec![(0, "Apple".to_string()), (1, "Banana".into())].into());
+    /// db.insert(&numbers, vec![0, 2].into());
+    ///
+    /// let join = |mode| {
+    ///     fruit
+    ///         .builder()
+    ///         .with_key(|t| t.0)
+    ///         .join(numbers.builder().with_key(|&t| t))
+    ///         .mode(mode)
+    /// };
+    /// let semi = join(JoinMode::Semi).left().build();
+    /// let anti = join(JoinMode::Anti).left().build();
+    /// let outer = join(JoinMode::LeftOuter)
+    ///     .on(|_, l, r| (l.1.clone(), r.cloned()))
+    ///     .build();
+    ///
+    /// assert_eq!(vec![(0, "Apple".to_string())], db.evaluate(&semi).unwrap().into_tuples());
+    /// assert_eq!(vec![(1, "Banana".to_string())], db.evaluate(&anti).unwrap().into_tuples());
+    /// assert_eq!(
+    ///     vec![("Apple".to_string(), Some(0)), ("Banana".to_string(), None)],
+    ///     db.evaluate(&outer).unwrap().into_tuples()
+    /// );
+    /// ```
+    pub fn mode(self, mode: JoinMode) -> JoinModeBuilder<K, L, R, Left, Right> {
+        JoinModeBuilder { join: self, mode }
+    }
+}
+
+pub struct JoinModeBuilder<K, L, R, Left, Right>
+where
+    K: Tuple + 'static,
+    L: Tuple + 'static,
+    R: Tuple + 'static,
+    Left: Expression<L>,
+    Right: Expression<R>,
+{
+    join: JoinBuilder<K, L, R, Left, Right>,
+    mode: JoinMode,
+}
+
+impl<K, L, R, Left, Right> JoinModeBuilder<K, L, R, Left, Right>
+where
+    K: Tuple + 'static,
+    L: Tuple + 'static,
+    R: Tuple + 'static,
+    Left: Expression<L>,
+    Right: Expression<R>,
+{
+    /// Builds a [`Join`] expression in the mode of the receiver where the closure `f`
+    /// computes the resulting tuples from the join key, the left tuple and the (optional)
+    /// right tuple of every row.
+    pub fn on<T: Tuple>(
+        self,
+        f: impl FnMut(&K, &L, Option<&R>) -> T + MaybeSync + 'static,
+    ) -> Builder<T, Join<K, L, R, Left, Right, T>> {
+        let join = self.join;
+        Builder {
+            expression: Join::with_mode(
+                join.left.expression,
+                join.right.expression,
+                join.left.key,
+                join.right.key,
+                self.mode,
+                f,
+            ),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds a [`Join`] expression in the mode of the receiver whose tuples are the left
+    /// tuples of its rows. It is typically used for [`JoinMode::Semi`] and
+    /// [`JoinMode::Anti`], whose rows have no right tuples.
+    pub fn left(self) -> Builder<L, Join<K, L, R, Left, Right, L>> {
+        self.on(|_, l, _| l.clone())
+    }
 }
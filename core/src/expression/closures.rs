@@ -0,0 +1,109 @@
+/*! Implements [`Closures`], a registry of the closures and tuples that are supplied for the
+placeholders of the operators of a [`Mono`] expression when the expression is copied.
+*/
+use super::*;
+use crate::Error;
+use std::collections::HashMap;
+
+/// Removes the entry of `name` from `map` or fails if no such entry exists.
+pub(crate) fn take<V>(map: &mut HashMap<String, V>, name: &str) -> Result<V, Error> {
+    map.remove(name).ok_or_else(|| Error::ClosureNotFound {
+        name: name.to_string(),
+    })
+}
+
+/// Is a registry of named closures and tuples for copying a [`Mono`] expression with
+/// [`Mono::deep_clone`] or, with the `serde` feature, reconstructing it from a `Shape`.
+#[allow(clippy::type_complexity)]
+pub struct Closures<T>
+where
+    T: Tuple,
+{
+    pub(crate) tuples: HashMap<String, T>,
+    pub(crate) predicates: HashMap<String, Box<closure!(FnMut(&T) -> bool)>>,
+    pub(crate) mappers: HashMap<String, Box<closure!(FnMut(&T) -> T)>>,
+    pub(crate) filter_mappers: HashMap<String, Box<closure!(FnMut(&T) -> Option<T>)>>,
+    pub(crate) product_mappers: HashMap<String, Box<closure!(FnMut(&T, &T) -> T)>>,
+    pub(crate) join_mappers: HashMap<String, Box<closure!(FnMut(&T, &T, &T) -> T)>>,
+}
+
+impl<T> Closures<T>
+where
+    T: Tuple,
+{
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            tuples: HashMap::new(),
+            predicates: HashMap::new(),
+            mappers: HashMap::new(),
+            filter_mappers: HashMap::new(),
+            product_mappers: HashMap::new(),
+            join_mappers: HashMap::new(),
+        }
+    }
+
+    /// Registers `tuple` for the [`Singleton`] placeholder `name` of a `Shape`.
+    pub fn tuple(mut self, name: &str, tuple: T) -> Self {
+        self.tuples.insert(name.to_string(), tuple);
+        self
+    }
+
+    /// Registers `predicate` for the [`Select`] placeholder `name`.
+    pub fn predicate(
+        mut self,
+        name: &str,
+        predicate: impl FnMut(&T) -> bool + MaybeSync + 'static,
+    ) -> Self {
+        self.predicates
+            .insert(name.to_string(), Box::new(predicate));
+        self
+    }
+
+    /// Registers `mapper` for the [`Project`] mapper or the [`Join`] key placeholder `name`.
+    pub fn mapper(mut self, name: &str, mapper: impl FnMut(&T) -> T + MaybeSync + 'static) -> Self {
+        self.mappers.insert(name.to_string(), Box::new(mapper));
+        self
+    }
+
+    /// Registers `mapper` for the [`FilterMap`] placeholder `name`.
+    pub fn filter_mapper(
+        mut self,
+        name: &str,
+        mapper: impl FnMut(&T) -> Option<T> + MaybeSync + 'static,
+    ) -> Self {
+        self.filter_mappers
+            .insert(name.to_string(), Box::new(mapper));
+        self
+    }
+
+    /// Registers `mapper` for the [`Product`] placeholder `name`.
+    pub fn product_mapper(
+        mut self,
+        name: &str,
+        mapper: impl FnMut(&T, &T) -> T + MaybeSync + 'static,
+    ) -> Self {
+        self.product_mappers
+            .insert(name.to_string(), Box::new(mapper));
+        self
+    }
+
+    /// Registers `mapper` for the [`Join`] mapper placeholder `name`.
+    pub fn join_mapper(
+        mut self,
+        name: &str,
+        mapper: impl FnMut(&T, &T, &T) -> T + MaybeSync + 'static,
+    ) -> Self {
+        self.join_mappers.insert(name.to_string(), Box::new(mapper));
+        self
+    }
+}
+
+impl<T> Default for Closures<T>
+where
+    T: Tuple,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
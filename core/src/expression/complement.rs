@@ -0,0 +1,118 @@
+use super::{Difference, Expression, IntoExpression, Visitor};
+use crate::Tuple;
+
+/// Evaluates to the complement of its `inner` sub-expression within a finite `universe`,
+/// that is, the tuples that are in `universe` but not in `inner` (`universe - inner`).
+///
+/// **Note**: unlike [`Full`], [`Complement`] can be evaluated because its tuples are
+/// drawn from `universe`. It is evaluated as a [`Difference`]; therefore, like
+/// [`Difference`], it can only be stored in views that are maintained by recomputation
+/// (see [`Database::store_view_recompute`]).
+///
+/// [`Full`]: super::Full
+/// [`Database::store_view_recompute`]: crate::Database::store_view_recompute
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::Complement};
+///
+/// let mut db = Database::new();
+/// let digits = db.add_relation::<i32>("digits").unwrap();
+/// let primes = db.add_relation::<i32>("primes").unwrap();
+///
+/// db.insert(&digits, (0..10).collect::<Vec<_>>().into());
+/// db.insert(&primes, vec![2, 3, 5, 7].into());
+///
+/// let non_primes = Complement::new(&primes, &digits);
+///
+/// assert_eq!(vec![0, 1, 4, 6, 8, 9], db.evaluate(&non_primes).unwrap().into_tuples());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Complement<T, E, U>
+where
+    T: Tuple,
+    E: Expression<T>,
+    U: Expression<T>,
+{
+    difference: Difference<T, U, E>,
+}
+
+impl<T, E, U> Complement<T, E, U>
+where
+    T: Tuple,
+    E: Expression<T>,
+    U: Expression<T>,
+{
+    /// Creates a new instance of [`Complement`] corresponding to `universe` - `inner`.
+    pub fn new<IE, IU>(inner: IE, universe: IU) -> Self
+    where
+        IE: IntoExpression<T, E>,
+        IU: IntoExpression<T, U>,
+    {
+        Self {
+            difference: Difference::new(universe, inner),
+        }
+    }
+
+    /// Returns a reference to the sub-expression whose complement is computed.
+    #[inline(always)]
+    pub fn inner(&self) -> &E {
+        self.difference.right()
+    }
+
+    /// Returns a reference to the universe sub-expression.
+    #[inline(always)]
+    pub fn universe(&self) -> &U {
+        self.difference.left()
+    }
+
+    /// Returns a reference to the [`Difference`] to which the receiver evaluates.
+    #[inline(always)]
+    pub(crate) fn difference(&self) -> &Difference<T, U, E> {
+        &self.difference
+    }
+}
+
+impl<T, E, U> Expression<T> for Complement<T, E, U>
+where
+    T: Tuple,
+    E: Expression<T>,
+    U: Expression<T>,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor,
+    {
+        visitor.visit_difference(&self.difference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Tuples};
+
+    #[test]
+    fn test_complement() {
+        let mut database = Database::new();
+        let u = database.add_relation::<i32>("u").unwrap();
+        database
+            .insert(&u, (0..10).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let evens = u.builder().select(|t| t % 2 == 0).build();
+        let odds = Complement::new(evens, u.clone());
+        assert_eq!(
+            Tuples::<i32>::from(vec![1, 3, 5, 7, 9]),
+            database.evaluate(&odds).unwrap()
+        );
+
+        assert!(database.store_view(odds.clone()).is_err());
+        let view = database.store_view_recompute(odds).unwrap();
+        database.insert(&u, vec![10, 11].into()).unwrap();
+        assert_eq!(
+            Tuples::<i32>::from(vec![1, 3, 5, 7, 9, 11]),
+            database.evaluate(&view).unwrap()
+        );
+    }
+}
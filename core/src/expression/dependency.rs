@@ -2,26 +2,26 @@ use crate::{
     expression::{view::ViewRef, Expression, Relation, View, Visitor},
     Tuple,
 };
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 /// Implements the [`Visitor`] to collect the relations and views to which
 /// the visited expression depends.
 pub(crate) struct DependencyVisitor {
-    relations: HashSet<String>,
-    views: HashSet<ViewRef>,
+    relations: BTreeSet<String>,
+    views: BTreeSet<ViewRef>,
 }
 
 impl DependencyVisitor {
     /// Creates a new [`DependencyVisitor`].
     pub fn new() -> Self {
         Self {
-            relations: HashSet::new(),
-            views: HashSet::new(),
+            relations: BTreeSet::new(),
+            views: BTreeSet::new(),
         }
     }
 
     /// Consumes the reciever and returns a pair of relation and view dependencies.
-    pub fn into_dependencies(self) -> (HashSet<String>, HashSet<ViewRef>) {
+    pub fn into_dependencies(self) -> (BTreeSet<String>, BTreeSet<ViewRef>) {
         (self.relations, self.views)
     }
 }
@@ -43,7 +43,7 @@ impl Visitor for DependencyVisitor {
     }
 }
 
-pub(crate) fn expression_dependencies<T, E>(expression: &E) -> (HashSet<String>, HashSet<ViewRef>)
+pub(crate) fn expression_dependencies<T, E>(expression: &E) -> (BTreeSet<String>, BTreeSet<ViewRef>)
 where
     T: Tuple,
     E: Expression<T>,
@@ -72,12 +72,24 @@ where
         &self.left
     }
 
+    /// Returns a mutable reference to the left sub-expression.
+    #[inline(always)]
+    pub(crate) fn left_mut(&mut self) -> &mut L {
+        &mut self.left
+    }
+
     /// Returns a reference to the right sub-expression.
     #[inline(always)]
     pub fn right(&self) -> &R {
         &self.right
     }
 
+    /// Returns a mutable reference to the right sub-expression.
+    #[inline(always)]
+    pub(crate) fn right_mut(&mut self) -> &mut R {
+        &mut self.right
+    }
+
     /// Returns a reference to the relation dependencies of the receiver.
     #[inline(always)]
     pub(crate) fn relation_deps(&self) -> &[String] {
@@ -101,7 +113,7 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_difference(&self);
+        visitor.visit_difference(self);
     }
 }
 
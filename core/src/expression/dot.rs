@@ -0,0 +1,257 @@
+use super::*;
+use std::fmt::Write;
+
+/// Implements the [`Visitor`] to render the operator tree of the visited expression in
+/// the DOT language of Graphviz.
+pub(crate) struct DotVisitor {
+    nodes: Vec<String>,
+    edges: Vec<(usize, usize)>,
+    parents: Vec<usize>,
+}
+
+impl DotVisitor {
+    /// Creates a new [`DotVisitor`].
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    /// Consumes the receiver and returns the DOT graph of the visited expression.
+    pub fn into_dot(self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for (id, label) in self.nodes.iter().enumerate() {
+            writeln!(dot, "    n{} [label=\"{}\"];", id, escape(label)).unwrap();
+        }
+        for (parent, child) in &self.edges {
+            writeln!(dot, "    n{} -> n{};", parent, child).unwrap();
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Adds a node with `label` for the expression that is being visited, connects it to
+    /// its parent and visits its sub-expressions by `walk`.
+    fn node(&mut self, label: String, walk: impl FnOnce(&mut Self)) {
+        let id = self.nodes.len();
+        self.nodes.push(label);
+        if let Some(&parent) = self.parents.last() {
+            self.edges.push((parent, id));
+        }
+
+        self.parents.push(id);
+        walk(self);
+        self.parents.pop();
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl Visitor for DotVisitor {
+    fn visit_full<T>(&mut self, _: &Full<T>)
+    where
+        T: Tuple,
+    {
+        self.node("Full".into(), |_| {});
+    }
+
+    fn visit_empty<T>(&mut self, _: &Empty<T>)
+    where
+        T: Tuple,
+    {
+        self.node("Empty".into(), |_| {});
+    }
+
+    fn visit_singleton<T>(&mut self, singleton: &Singleton<T>)
+    where
+        T: Tuple,
+    {
+        self.node(format!("Singleton {:?}", singleton.tuple()), |_| {});
+    }
+
+    fn visit_param<T>(&mut self, param: &Param<T>)
+    where
+        T: Tuple,
+    {
+        self.node(format!("Param {}", param.name()), |_| {});
+    }
+
+    fn visit_relation<T>(&mut self, relation: &Relation<T>)
+    where
+        T: Tuple,
+    {
+        self.node(format!("Relation {}", relation.name()), |_| {});
+    }
+
+    fn visit_select<T, E>(&mut self, select: &Select<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        self.node("Select".into(), |v| walk_select(v, select));
+    }
+
+    fn visit_union<T, L, R>(&mut self, union: &Union<T, L, R>)
+    where
+        T: Tuple,
+        L: Expression<T>,
+        R: Expression<T>,
+    {
+        self.node("Union".into(), |v| walk_union(v, union));
+    }
+
+    fn visit_intersect<T, L, R>(&mut self, intersect: &Intersect<T, L, R>)
+    where
+        T: Tuple,
+        L: Expression<T>,
+        R: Expression<T>,
+    {
+        self.node("Intersect".into(), |v| walk_intersect(v, intersect));
+    }
+
+    fn visit_difference<T, L, R>(&mut self, difference: &Difference<T, L, R>)
+    where
+        T: Tuple,
+        L: Expression<T>,
+        R: Expression<T>,
+    {
+        self.node("Difference".into(), |v| walk_difference(v, difference));
+    }
+
+    fn visit_project<S, T, E>(&mut self, project: &Project<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        self.node("Project".into(), |v| walk_project(v, project));
+    }
+
+    fn visit_filter_map<S, T, E>(&mut self, filter_map: &FilterMap<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        self.node("FilterMap".into(), |v| walk_filter_map(v, filter_map));
+    }
+
+    fn visit_flat_project<S, T, E>(&mut self, flat_project: &FlatProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        self.node("FlatProject".into(), |v| walk_flat_project(v, flat_project));
+    }
+
+    fn visit_try_project<S, T, E>(&mut self, try_project: &TryProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        self.node("TryProject".into(), |v| walk_try_project(v, try_project));
+    }
+
+    fn visit_product<L, R, Left, Right, T>(&mut self, product: &Product<L, R, Left, Right, T>)
+    where
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: Expression<L>,
+        Right: Expression<R>,
+    {
+        self.node("Product".into(), |v| walk_product(v, product));
+    }
+
+    fn visit_join<K, L, R, Left, Right, T>(&mut self, join: &Join<K, L, R, Left, Right, T>)
+    where
+        K: Tuple,
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: Expression<L>,
+        Right: Expression<R>,
+    {
+        let label = match join.mode() {
+            JoinMode::Inner => "Join".to_string(),
+            mode => format!("{:?} Join", mode),
+        };
+        self.node(label, |v| walk_join(v, join));
+    }
+
+    fn visit_enumerate<T, E>(&mut self, enumerate: &Enumerate<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        self.node("Enumerate".into(), |v| walk_enumerate(v, enumerate));
+    }
+
+    fn visit_group_by<S, K, A, E>(&mut self, group_by: &GroupBy<S, K, A, E>)
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: Expression<S>,
+    {
+        self.node("GroupBy".into(), |v| walk_group_by(v, group_by));
+    }
+
+    fn visit_scan<T, A, E>(&mut self, scan: &Scan<T, A, E>)
+    where
+        T: Tuple,
+        A: Tuple,
+        E: Expression<T>,
+    {
+        self.node("Scan".into(), |v| walk_scan(v, scan));
+    }
+
+    fn visit_view<T, E>(&mut self, view: &View<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        self.node(format!("View {}", view.reference()), |_| {});
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ast_dot() {
+        let r = Relation::<i32>::new("r");
+        let s = Relation::<i32>::new("s");
+        let join = Join::new(
+            Select::new(&r, |t| t % 2 == 0),
+            &s,
+            |t| *t,
+            |t| *t,
+            |_, l, r| l + r,
+        );
+        let dot = join.to_ast_dot();
+
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("n0 [label=\"Join\"];"));
+        assert!(dot.contains("n1 [label=\"Select\"];"));
+        assert!(dot.contains("n2 [label=\"Relation r\"];"));
+        assert!(dot.contains("n3 [label=\"Relation s\"];"));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n1 -> n2;"));
+        assert!(dot.contains("n0 -> n3;"));
+        assert_eq!(3, dot.matches("->").count());
+    }
+
+    #[test]
+    fn test_to_ast_dot_escape() {
+        let dot = Singleton::new("\"a\"".to_string()).to_ast_dot();
+        assert!(dot.contains(r#"n0 [label="Singleton \"\\\"a\\\"\""];"#));
+    }
+}
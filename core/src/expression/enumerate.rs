@@ -0,0 +1,113 @@
+use super::{view::ViewRef, Expression, IntoExpression, Visitor};
+use crate::Tuple;
+use std::marker::PhantomData;
+
+/// Pairs every tuple of its sub-expression with its index in the sorted order of all
+/// tuples of the sub-expression.
+///
+/// **Note**: the indices are global over the result of the sub-expression; therefore, they
+/// are assigned when the sub-expression is fully materialized, not incrementally. For the
+/// same reason, [`Enumerate`] cannot be used in views.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::Enumerate};
+///
+/// let mut db = Database::new();
+/// let fruit = db.add_relation::<String>("Fruit").unwrap();
+///
+/// db.insert(&fruit, vec!["cherry".to_string(), "apple".into(), "banana".into()].into());
+///
+/// let enumerate = Enumerate::new(&fruit);
+///
+/// assert_eq!(
+///     vec![(0, "apple".to_string()), (1, "banana".into()), (2, "cherry".into())],
+///     db.evaluate(&enumerate).unwrap().into_tuples(),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Enumerate<T, E>
+where
+    T: Tuple,
+    E: Expression<T>,
+{
+    expression: E,
+    relation_deps: Vec<String>,
+    view_deps: Vec<ViewRef>,
+    _marker: PhantomData<T>,
+}
+
+impl<T, E> Enumerate<T, E>
+where
+    T: Tuple,
+    E: Expression<T>,
+{
+    /// Creates a new [`Enumerate`] expression over `expression`.
+    pub fn new<I>(expression: I) -> Self
+    where
+        I: IntoExpression<T, E>,
+    {
+        use super::dependency;
+        let expression = expression.into_expression();
+
+        let mut deps = dependency::DependencyVisitor::new();
+        expression.visit(&mut deps);
+        let (relation_deps, view_deps) = deps.into_dependencies();
+
+        Self {
+            expression,
+            relation_deps: relation_deps.into_iter().collect(),
+            view_deps: view_deps.into_iter().collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying sub-expression.
+    #[inline(always)]
+    pub fn expression(&self) -> &E {
+        &self.expression
+    }
+
+    /// Returns a reference to the relation dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn relation_deps(&self) -> &[String] {
+        &self.relation_deps
+    }
+
+    /// Returns a reference to the view dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn view_deps(&self) -> &[ViewRef] {
+        &self.view_deps
+    }
+}
+
+impl<T, E> Expression<(usize, T)> for Enumerate<T, E>
+where
+    T: Tuple,
+    E: Expression<T>,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor,
+    {
+        visitor.visit_enumerate(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Tuples};
+
+    #[test]
+    fn test_clone() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![30, 10, 20].into()).unwrap();
+        let e = Enumerate::new(&r).clone();
+        assert_eq!(
+            Tuples::<(usize, i32)>::from(vec![(0, 10), (1, 20), (2, 30)]),
+            database.evaluate(&e).unwrap()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use super::{view::ViewRef, Expression, IntoExpression, Visitor};
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
+};
+use std::marker::PhantomData;
+
+/// Is the type of [`FilterMap`] closures that map tuples of type `S` to tuples of type `T`.
+type Mapper<S, T> = closure!(FnMut(&S) -> Option<T>);
+
+/// Projects the tuples of an inner sub-expression of type `S` to tuples of type `T` and
+/// drops the tuples for which its closure returns `None`. [`FilterMap`] acts as a
+/// [`Select`] followed by a [`Project`] in a single pass over the tuples.
+///
+/// [`Select`]: super::Select
+/// [`Project`]: super::Project
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::FilterMap};
+///
+/// let mut db = Database::new();
+/// let words = db.add_relation::<String>("Words").unwrap();
+///
+/// db.insert(&words, vec!["1".to_string(), "two".into(), "3".into()].into());
+///
+/// let numbers = FilterMap::new(&words, |t| t.parse::<i32>().ok());
+///
+/// assert_eq!(vec![1, 3], db.evaluate(&numbers).unwrap().into_tuples());
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct FilterMap<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    expression: E,
+    mapper: Shared<Lock<Mapper<S, T>>>,
+    relation_deps: Vec<String>,
+    view_deps: Vec<ViewRef>,
+}
+
+impl<S, T, E> FilterMap<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    /// Creates a new [`FilterMap`] expression over `expression` with a closure `mapper`
+    /// that projects tuples of `expression` to the resulting tuples or drops them by
+    /// returning `None`.
+    pub fn new<I>(expression: I, mapper: impl FnMut(&S) -> Option<T> + MaybeSync + 'static) -> Self
+    where
+        I: IntoExpression<S, E>,
+    {
+        use super::dependency;
+        let expression = expression.into_expression();
+
+        let mut deps = dependency::DependencyVisitor::new();
+        expression.visit(&mut deps);
+        let (relation_deps, view_deps) = deps.into_dependencies();
+
+        Self {
+            expression,
+            mapper: Shared::new(Lock::new(mapper)),
+            relation_deps: relation_deps.into_iter().collect(),
+            view_deps: view_deps.into_iter().collect(),
+        }
+    }
+
+    /// Returns a reference to the underlying sub-expression.
+    #[inline(always)]
+    pub fn expression(&self) -> &E {
+        &self.expression
+    }
+
+    /// Returns a mutable reference to the underlying sub-expression.
+    #[inline(always)]
+    pub(crate) fn expression_mut(&mut self) -> &mut E {
+        &mut self.expression
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the projecting closure.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn mapper_mut(&self) -> RefMut<'_, Mapper<S, T>> {
+        self.mapper.borrow_mut()
+    }
+
+    /// Returns a reference to the relation dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn relation_deps(&self) -> &[String] {
+        &self.relation_deps
+    }
+
+    /// Returns a reference to the view dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn view_deps(&self) -> &[ViewRef] {
+        &self.view_deps
+    }
+}
+
+impl<S, T, E> Expression<T> for FilterMap<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor,
+    {
+        visitor.visit_filter_map(self);
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Debuggable<S, E>
+where
+    S: Tuple,
+    E: Expression<S>,
+{
+    expression: E,
+    _marker: PhantomData<S>,
+}
+
+impl<S, T, E> std::fmt::Debug for FilterMap<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debuggable {
+            expression: self.expression.clone(),
+            _marker: PhantomData,
+        }
+        .fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Tuples};
+
+    #[test]
+    fn test_clone() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        let p = FilterMap::new(&r, |&t| if t != 2 { Some(t * 10) } else { None }).clone();
+        assert_eq!(
+            Tuples::<i32>::from(vec![10, 30]),
+            database.evaluate(&p).unwrap()
+        );
+    }
+}
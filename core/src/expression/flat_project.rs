@@ -0,0 +1,153 @@
+use super::{view::ViewRef, Expression, IntoExpression, Visitor};
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
+};
+use std::marker::PhantomData;
+
+/// Is the type of [`FlatProject`] closures that map tuples of type `S` to tuples of type `T`.
+type Mapper<S, T> = closure!(FnMut(&S) -> Vec<T>);
+
+/// Projects every tuple of an inner sub-expression of type `S` to any number of tuples of
+/// type `T`. Unlike [`Project`], which maps every tuple to exactly one tuple, the closure
+/// of [`FlatProject`] returns a vector of the resulting tuples for every tuple.
+///
+/// [`Project`]: super::Project
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::FlatProject};
+///
+/// let mut db = Database::new();
+/// let words = db.add_relation::<String>("Words").unwrap();
+///
+/// db.insert(&words, vec!["ab".to_string(), "bc".into()].into());
+///
+/// let letters = FlatProject::new(&words, |t| t.chars().collect());
+///
+/// assert_eq!(vec!['a', 'b', 'c'], db.evaluate(&letters).unwrap().into_tuples());
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct FlatProject<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    expression: E,
+    mapper: Shared<Lock<Mapper<S, T>>>,
+    relation_deps: Vec<String>,
+    view_deps: Vec<ViewRef>,
+}
+
+impl<S, T, E> FlatProject<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    /// Creates a new [`FlatProject`] expression over `expression` with a closure `mapper`
+    /// that projects every tuple of `expression` to a vector of the resulting tuples.
+    pub fn new<I>(expression: I, mapper: impl FnMut(&S) -> Vec<T> + MaybeSync + 'static) -> Self
+    where
+        I: IntoExpression<S, E>,
+    {
+        use super::dependency;
+        let expression = expression.into_expression();
+
+        let mut deps = dependency::DependencyVisitor::new();
+        expression.visit(&mut deps);
+        let (relation_deps, view_deps) = deps.into_dependencies();
+
+        Self {
+            expression,
+            mapper: Shared::new(Lock::new(mapper)),
+            relation_deps: relation_deps.into_iter().collect(),
+            view_deps: view_deps.into_iter().collect(),
+        }
+    }
+
+    /// Returns a reference to the underlying sub-expression.
+    #[inline(always)]
+    pub fn expression(&self) -> &E {
+        &self.expression
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the projecting closure.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn mapper_mut(&self) -> RefMut<'_, Mapper<S, T>> {
+        self.mapper.borrow_mut()
+    }
+
+    /// Returns a reference to the relation dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn relation_deps(&self) -> &[String] {
+        &self.relation_deps
+    }
+
+    /// Returns a reference to the view dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn view_deps(&self) -> &[ViewRef] {
+        &self.view_deps
+    }
+}
+
+impl<S, T, E> Expression<T> for FlatProject<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor,
+    {
+        visitor.visit_flat_project(self);
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Debuggable<S, E>
+where
+    S: Tuple,
+    E: Expression<S>,
+{
+    expression: E,
+    _marker: PhantomData<S>,
+}
+
+impl<S, T, E> std::fmt::Debug for FlatProject<S, T, E>
+where
+    S: Tuple,
+    T: Tuple,
+    E: Expression<S>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debuggable {
+            expression: self.expression.clone(),
+            _marker: PhantomData,
+        }
+        .fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Tuples};
+
+    #[test]
+    fn test_clone() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        let p = FlatProject::new(&r, |&t| vec![t; t as usize]).clone();
+        assert_eq!(
+            Tuples::<i32>::from(vec![1, 2, 3]),
+            database.evaluate(&p).unwrap()
+        );
+    }
+}
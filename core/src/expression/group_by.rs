@@ -0,0 +1,190 @@
+use super::{view::ViewRef, Expression, IntoExpression, Visitor};
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
+};
+use std::marker::PhantomData;
+
+/// Is the type of [`GroupBy`] closures that return the group keys of tuples of type `S`.
+type Key<S, K> = closure!(FnMut(&S) -> K);
+
+/// Is the type of [`GroupBy`] closures that fold tuples of type `S` into aggregates of type `A`.
+type Fold<S, A> = closure!(FnMut(A, &S) -> A);
+
+/// Groups the tuples of its sub-expression by a key and aggregates every group into a
+/// single value by folding the tuples of the group, in their sorted order, starting from
+/// an initial value. The resulting tuples are pairs of keys and their aggregates.
+///
+/// **Note**: an aggregate depends on every tuple of its group; therefore, the aggregates
+/// are computed when the sub-expression is fully materialized, not incrementally. For the
+/// same reason, [`GroupBy`] cannot be used in views.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::GroupBy};
+///
+/// let mut db = Database::new();
+/// let sales = db.add_relation::<(String, i32)>("Sales").unwrap();
+///
+/// db.insert(&sales, vec![("apple".to_string(), 3), ("banana".into(), 5), ("apple".into(), 4)].into());
+///
+/// let totals = GroupBy::new(&sales, |t| t.0.clone(), 0, |total, t| total + t.1);
+///
+/// assert_eq!(
+///     vec![("apple".to_string(), 7), ("banana".into(), 5)],
+///     db.evaluate(&totals).unwrap().into_tuples(),
+/// );
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct GroupBy<S, K, A, E>
+where
+    S: Tuple,
+    K: Tuple,
+    A: Tuple,
+    E: Expression<S>,
+{
+    expression: E,
+    key: Shared<Lock<Key<S, K>>>,
+    init: A,
+    fold: Shared<Lock<Fold<S, A>>>,
+    relation_deps: Vec<String>,
+    view_deps: Vec<ViewRef>,
+}
+
+impl<S, K, A, E> GroupBy<S, K, A, E>
+where
+    S: Tuple,
+    K: Tuple,
+    A: Tuple,
+    E: Expression<S>,
+{
+    /// Creates a new [`GroupBy`] expression over `expression` that groups its tuples by the
+    /// keys that `key` returns and aggregates every group by folding its tuples with `fold`,
+    /// starting from `init`.
+    pub fn new<I>(
+        expression: I,
+        key: impl FnMut(&S) -> K + MaybeSync + 'static,
+        init: A,
+        fold: impl FnMut(A, &S) -> A + MaybeSync + 'static,
+    ) -> Self
+    where
+        I: IntoExpression<S, E>,
+    {
+        use super::dependency;
+        let expression = expression.into_expression();
+
+        let mut deps = dependency::DependencyVisitor::new();
+        expression.visit(&mut deps);
+        let (relation_deps, view_deps) = deps.into_dependencies();
+
+        Self {
+            expression,
+            key: Shared::new(Lock::new(key)),
+            init,
+            fold: Shared::new(Lock::new(fold)),
+            relation_deps: relation_deps.into_iter().collect(),
+            view_deps: view_deps.into_iter().collect(),
+        }
+    }
+
+    /// Returns a reference to the underlying sub-expression.
+    #[inline(always)]
+    pub fn expression(&self) -> &E {
+        &self.expression
+    }
+
+    /// Returns a reference to the initial value of the aggregates.
+    #[inline(always)]
+    pub fn init(&self) -> &A {
+        &self.init
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the key closure.
+    #[inline(always)]
+    pub(crate) fn key_mut(&self) -> RefMut<'_, Key<S, K>> {
+        self.key.borrow_mut()
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the folding closure.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn fold_mut(&self) -> RefMut<'_, Fold<S, A>> {
+        self.fold.borrow_mut()
+    }
+
+    /// Returns a reference to the relation dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn relation_deps(&self) -> &[String] {
+        &self.relation_deps
+    }
+
+    /// Returns a reference to the view dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn view_deps(&self) -> &[ViewRef] {
+        &self.view_deps
+    }
+}
+
+impl<S, K, A, E> Expression<(K, A)> for GroupBy<S, K, A, E>
+where
+    S: Tuple,
+    K: Tuple,
+    A: Tuple,
+    E: Expression<S>,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor,
+    {
+        visitor.visit_group_by(self);
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Debuggable<S, A, E>
+where
+    S: Tuple,
+    A: Tuple,
+    E: Expression<S>,
+{
+    expression: E,
+    init: A,
+    _marker: PhantomData<S>,
+}
+
+impl<S, K, A, E> std::fmt::Debug for GroupBy<S, K, A, E>
+where
+    S: Tuple,
+    K: Tuple,
+    A: Tuple,
+    E: Expression<S>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debuggable {
+            expression: self.expression.clone(),
+            init: self.init.clone(),
+            _marker: PhantomData,
+        }
+        .fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Tuples};
+
+    #[test]
+    fn test_clone() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4, 5].into()).unwrap();
+        let g = GroupBy::new(&r, |t| t % 2, 0, |sum, t| sum + t).clone();
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(0, 6), (1, 9)]),
+            database.evaluate(&g).unwrap()
+        );
+    }
+}
@@ -1,14 +1,49 @@
 use super::{view::ViewRef, Expression, IntoExpression, Visitor};
-use crate::Tuple;
-use std::{
-    cell::{RefCell, RefMut},
-    marker::PhantomData,
-    rc::Rc,
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
 };
+use std::marker::PhantomData;
+
+/// Is the type of [`Join`] closures that return the join keys of tuples of type `S`.
+type Key<S, K> = closure!(FnMut(&S) -> K);
 
 /// Is the type of [`Join`] mapping closures for constructing tuples of type `T` from a key
-/// of type `K`, a left tuple of type `L`, and a right tuple of type `R`.
-type Mapper<K, L, R, T> = dyn FnMut(&K, &L, &R) -> T;
+/// of type `K`, a left tuple of type `L`, and a right tuple of type `R`. The mapping closure
+/// returns `None` to drop a joined pair.
+type Mapper<K, L, R, T> = closure!(FnMut(&K, &L, &R) -> Option<T>);
+
+/// Is the type of [`Join`] mapping closures for constructing tuples of type `T` from a key of
+/// type `K` and a left tuple of type `L` that is not paired with a right tuple.
+type LeftMapper<K, L, T> = closure!(FnMut(&K, &L) -> Option<T>);
+
+/// Determines the rows that a [`Join`] produces from its left and right sub-expressions.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum JoinMode {
+    /// Pairs every left tuple with every right tuple of the same key.
+    #[default]
+    Inner,
+
+    /// Keeps every left tuple that joins with at least one right tuple.
+    Semi,
+
+    /// Keeps every left tuple that joins with no right tuple.
+    Anti,
+
+    /// Pairs the left and right tuples like `Inner` and keeps every left tuple that joins
+    /// with no right tuple.
+    LeftOuter,
+}
+
+impl JoinMode {
+    /// Returns `true` if the rows of the mode are never retracted when new tuples arrive in
+    /// the sub-expressions of the join.
+    #[inline(always)]
+    pub fn is_monotone(&self) -> bool {
+        matches!(self, Self::Inner | Self::Semi)
+    }
+}
 
 /// Represents the join of its `left` and `right` sub-expressions.
 ///
@@ -39,6 +74,7 @@ type Mapper<K, L, R, T> = dyn FnMut(&K, &L, &R) -> T;
 /// assert_eq!(vec!["Apple0", "Cherry4"], db.evaluate(&join).unwrap().into_tuples());
 /// ```
 #[derive(Clone)]
+#[allow(clippy::type_complexity)]
 pub struct Join<K, L, R, Left, Right, T>
 where
     K: Tuple,
@@ -50,9 +86,11 @@ where
 {
     left: Left,
     right: Right,
-    left_key: Rc<RefCell<dyn FnMut(&L) -> K>>,
-    right_key: Rc<RefCell<dyn FnMut(&R) -> K>>,
-    mapper: Rc<RefCell<Mapper<K, L, R, T>>>,
+    left_key: Shared<Lock<Key<L, K>>>,
+    right_key: Shared<Lock<Key<R, K>>>,
+    mapper: Shared<Lock<Mapper<K, L, R, T>>>,
+    left_mapper: Shared<Lock<LeftMapper<K, L, T>>>,
+    mode: JoinMode,
     relation_deps: Vec<String>,
     view_deps: Vec<ViewRef>,
 }
@@ -74,9 +112,27 @@ where
     pub fn new<IL, IR>(
         left: IL,
         right: IR,
-        left_key: impl FnMut(&L) -> K + 'static,
-        right_key: impl FnMut(&R) -> K + 'static,
-        mapper: impl FnMut(&K, &L, &R) -> T + 'static,
+        left_key: impl FnMut(&L) -> K + MaybeSync + 'static,
+        right_key: impl FnMut(&R) -> K + MaybeSync + 'static,
+        mut mapper: impl FnMut(&K, &L, &R) -> T + MaybeSync + 'static,
+    ) -> Self
+    where
+        IL: IntoExpression<L, Left>,
+        IR: IntoExpression<R, Right>,
+    {
+        Self::filtered(left, right, left_key, right_key, move |k, l, r| {
+            Some(mapper(k, l, r))
+        })
+    }
+
+    /// Creates a new [`Join`] expression like [`Join::new`] where the pairs of joined
+    /// tuples for which `mapper` returns `None` are dropped.
+    pub fn filtered<IL, IR>(
+        left: IL,
+        right: IR,
+        left_key: impl FnMut(&L) -> K + MaybeSync + 'static,
+        right_key: impl FnMut(&R) -> K + MaybeSync + 'static,
+        mapper: impl FnMut(&K, &L, &R) -> Option<T> + MaybeSync + 'static,
     ) -> Self
     where
         IL: IntoExpression<L, Left>,
@@ -94,46 +150,137 @@ where
         Self {
             left,
             right,
-            left_key: Rc::new(RefCell::new(left_key)),
-            right_key: Rc::new(RefCell::new(right_key)),
-            mapper: Rc::new(RefCell::new(mapper)),
+            left_key: Shared::new(Lock::new(left_key)),
+            right_key: Shared::new(Lock::new(right_key)),
+            mapper: Shared::new(Lock::new(mapper)),
+            left_mapper: Shared::new(Lock::new(|_: &K, _: &L| None)),
+            mode: JoinMode::Inner,
             relation_deps: relation_deps.into_iter().collect(),
             view_deps: view_deps.into_iter().collect(),
         }
     }
 
+    /// Creates a new [`Join`] expression like [`Join::new`] whose rows are determined by
+    /// `mode`. The closure `mapper` computes the tuples of the resulting expression from the
+    /// join key, the left tuple and the right tuple of every row. The right tuple is `None`
+    /// in the rows of [`JoinMode::Semi`] and [`JoinMode::Anti`], and in the rows of
+    /// [`JoinMode::LeftOuter`] for the left tuples that join with no right tuple.
+    ///
+    /// **Note**: [`JoinMode::Anti`] and [`JoinMode::LeftOuter`] are not monotone: inserting
+    /// right tuples may retract rows. Therefore, the resulting expression can only be
+    /// stored in views that are maintained by recomputation (see
+    /// [`Database::store_view_recompute`]).
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::{Join, JoinMode}};
+    ///
+    /// let mut db = Database::new();
+    /// let fruit = db.add_relation::<(i32, String)>("R").unwrap();
+    /// let numbers = db.add_relation::<i32>("S").unwrap();
+    ///
+    /// db.insert(&fruit, vec![(0, "Apple".to_string()), (1, "Banana".into())].into());
+    /// db.insert(&numbers, vec![0, 2].into());
+    ///
+    /// let outer = Join::with_mode(
+    ///     &fruit,
+    ///     &numbers,
+    ///     |t| t.0,
+    ///     |&t| t,
+    ///     JoinMode::LeftOuter,
+    ///     |_, l, r| (l.1.clone(), r.cloned()),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     vec![("Apple".to_string(), Some(0)), ("Banana".to_string(), None)],
+    ///     db.evaluate(&outer).unwrap().into_tuples()
+    /// );
+    /// ```
+    ///
+    /// [`Database::store_view_recompute`]: crate::Database::store_view_recompute
+    pub fn with_mode<IL, IR>(
+        left: IL,
+        right: IR,
+        left_key: impl FnMut(&L) -> K + MaybeSync + 'static,
+        right_key: impl FnMut(&R) -> K + MaybeSync + 'static,
+        mode: JoinMode,
+        mapper: impl FnMut(&K, &L, Option<&R>) -> T + MaybeSync + 'static,
+    ) -> Self
+    where
+        IL: IntoExpression<L, Left>,
+        IR: IntoExpression<R, Right>,
+    {
+        // the paired and the unpaired rows share the state of `mapper`:
+        let mapper = Shared::new(Lock::new(mapper));
+        let left_mapper = {
+            let mapper = mapper.clone();
+            move |k: &K, l: &L| Some((mapper.borrow_mut())(k, l, None))
+        };
+        let mut join = Self::filtered(left, right, left_key, right_key, move |k, l, r| {
+            Some((mapper.borrow_mut())(k, l, Some(r)))
+        });
+        join.left_mapper = Shared::new(Lock::new(left_mapper));
+        join.mode = mode;
+        join
+    }
+
     /// Returns a reference to the left sub-expression.
     #[inline(always)]
     pub fn left(&self) -> &Left {
         &self.left
     }
 
+    /// Returns a mutable reference to the left sub-expression.
+    #[inline(always)]
+    pub(crate) fn left_mut(&mut self) -> &mut Left {
+        &mut self.left
+    }
+
     /// Returns a reference to the right sub-expression.
     #[inline(always)]
     pub fn right(&self) -> &Right {
         &self.right
     }
 
+    /// Returns a mutable reference to the right sub-expression.
+    #[inline(always)]
+    pub(crate) fn right_mut(&mut self) -> &mut Right {
+        &mut self.right
+    }
+
     /// Returns a mutable reference (of type [`RefMut`]) of the key closure for
     /// the left sub-expression.
     #[inline(always)]
-    pub(crate) fn left_key_mut(&self) -> RefMut<dyn FnMut(&L) -> K> {
+    pub(crate) fn left_key_mut(&self) -> RefMut<'_, Key<L, K>> {
         self.left_key.borrow_mut()
     }
 
     /// Returns a mutable reference (of type [`RefMut`]) of the key closure for
     /// the right sub-expression.
     #[inline(always)]
-    pub(crate) fn right_key_mut(&self) -> RefMut<dyn FnMut(&R) -> K> {
+    pub(crate) fn right_key_mut(&self) -> RefMut<'_, Key<R, K>> {
         self.right_key.borrow_mut()
     }
 
     /// Returns a mutable reference (of type [`RefMut`]) to the joining closure.
     #[inline(always)]
-    pub(crate) fn mapper_mut(&self) -> RefMut<dyn FnMut(&K, &L, &R) -> T> {
+    pub(crate) fn mapper_mut(&self) -> RefMut<'_, Mapper<K, L, R, T>> {
         self.mapper.borrow_mut()
     }
 
+    /// Returns a mutable reference (of type [`RefMut`]) to the closure that maps the left
+    /// tuples that are not paired with right tuples.
+    #[inline(always)]
+    pub(crate) fn left_mapper_mut(&self) -> RefMut<'_, LeftMapper<K, L, T>> {
+        self.left_mapper.borrow_mut()
+    }
+
+    /// Returns the mode of the receiver.
+    #[inline(always)]
+    pub fn mode(&self) -> JoinMode {
+        self.mode
+    }
+
     /// Returns a reference to the relation dependencies of the receiver.
     #[inline(always)]
     pub(crate) fn relation_deps(&self) -> &[String] {
@@ -160,12 +307,13 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_join(&self);
+        visitor.visit_join(self);
     }
 }
 
 // A hack for debugging purposes:
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Debuggable<L, R, Left, Right>
 where
     L: Tuple,
@@ -2,9 +2,14 @@
 same [`Tuple`] type.
 */
 
-use super::*;
+use super::{closures::take, *};
+use crate::Error;
 
 /// Is a recursive [`Expression`] where all subexpressions act on the same [`Tuple`] type.;
+///
+/// **Note**: the closures of a cloned expression are shared with the original expression;
+/// therefore, the state of stateful closures leaks from one copy to another. A copy with
+/// its own closures is made by [`Mono::deep_clone`].
 #[derive(Clone, Debug)]
 #[allow(clippy::type_complexity)]
 pub enum Mono<T>
@@ -14,9 +19,11 @@ where
     Full(Full<T>),
     Empty(Empty<T>),
     Singleton(Singleton<T>),
+    Param(Param<T>),
     Relation(Relation<T>),
     Select(Box<Select<T, Mono<T>>>),
     Project(Box<Project<T, T, Mono<T>>>),
+    FilterMap(Box<FilterMap<T, T, Mono<T>>>),
     Union(Box<Union<T, Mono<T>, Mono<T>>>),
     Intersect(Box<Intersect<T, Mono<T>, Mono<T>>>),
     Difference(Box<Difference<T, Mono<T>, Mono<T>>>),
@@ -30,6 +37,132 @@ impl<T: Tuple + 'static> Mono<T> {
     pub fn boxed(self) -> Box<Self> {
         Box::new(self)
     }
+
+    /// Consumes `closures` and returns a copy of the receiver where every node is cloned
+    /// and every closure is replaced by the closure of its placeholder in `closures`;
+    /// therefore, unlike [`Clone::clone`], the copy shares no state with the receiver.
+    /// The placeholders are named after their operators and numbered in the pre-order
+    /// of the operator tree (e.g., `select#0`), like the placeholders of a `Shape`. The
+    /// tuples of singletons are copied. Fails if a placeholder has no closure in
+    /// `closures` or the receiver contains a closure that cannot be registered in
+    /// [`Closures`].
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::{Closures, Mono, Select}};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// let odd = |t: &i32| t % 2 == 1;
+    /// let mono: Mono<i32> = Select::new(Mono::from(r), odd).into();
+    /// let copy = mono
+    ///     .deep_clone(Closures::new().predicate("select#0", odd))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vec![1, 3], db.evaluate(&copy).unwrap().into_tuples());
+    /// ```
+    pub fn deep_clone(&self, mut closures: Closures<T>) -> Result<Self, Error> {
+        self.deep_clone_with(&mut closures, &mut 0)
+    }
+
+    fn deep_clone_with(
+        &self,
+        closures: &mut Closures<T>,
+        counter: &mut usize,
+    ) -> Result<Self, Error> {
+        let mut placeholder = |operator: &str| {
+            let name = format!("{}#{}", operator, counter);
+            *counter += 1;
+            name
+        };
+
+        Ok(match self {
+            Mono::Full(_) => Full::new().into(),
+            Mono::Empty(_) => Empty::new().into(),
+            Mono::Singleton(exp) => {
+                placeholder("singleton");
+                Singleton::new(exp.tuple().clone()).into()
+            }
+            Mono::Param(exp) => exp.clone().into(),
+            Mono::Relation(exp) => exp.clone().into(),
+            Mono::Select(exp) if exp.is_contextual() => {
+                return Err(unsupported("Select with context"));
+            }
+            Mono::Select(exp) => {
+                let predicate = take(&mut closures.predicates, &placeholder("select"))?;
+                Select::new(
+                    exp.expression().deep_clone_with(closures, counter)?,
+                    predicate,
+                )
+                .into()
+            }
+            Mono::Project(exp) if exp.is_contextual() => {
+                return Err(unsupported("Project with context"));
+            }
+            Mono::Project(exp) => {
+                let mapper = take(&mut closures.mappers, &placeholder("project"))?;
+                Project::new(exp.expression().deep_clone_with(closures, counter)?, mapper).into()
+            }
+            Mono::FilterMap(exp) => {
+                let mapper = take(&mut closures.filter_mappers, &placeholder("filter_map"))?;
+                FilterMap::new(exp.expression().deep_clone_with(closures, counter)?, mapper).into()
+            }
+            Mono::Union(exp) => Union::new(
+                exp.left().deep_clone_with(closures, counter)?,
+                exp.right().deep_clone_with(closures, counter)?,
+            )
+            .into(),
+            Mono::Intersect(exp) => Intersect::new(
+                exp.left().deep_clone_with(closures, counter)?,
+                exp.right().deep_clone_with(closures, counter)?,
+            )
+            .into(),
+            Mono::Difference(exp) => Difference::new(
+                exp.left().deep_clone_with(closures, counter)?,
+                exp.right().deep_clone_with(closures, counter)?,
+            )
+            .into(),
+            Mono::Product(exp) if exp.filter_mut().is_some() => {
+                return Err(unsupported("filtered Product"));
+            }
+            Mono::Product(exp) => {
+                let mapper = take(&mut closures.product_mappers, &placeholder("product"))?;
+                Product::new(
+                    exp.left().deep_clone_with(closures, counter)?,
+                    exp.right().deep_clone_with(closures, counter)?,
+                    mapper,
+                )
+                .into()
+            }
+            Mono::Join(exp) if exp.mode() != JoinMode::Inner => {
+                return Err(unsupported(&format!("{:?} Join", exp.mode())));
+            }
+            Mono::Join(exp) => {
+                let left_key = take(&mut closures.mappers, &placeholder("join_left_key"))?;
+                let right_key = take(&mut closures.mappers, &placeholder("join_right_key"))?;
+                let mapper = take(&mut closures.join_mappers, &placeholder("join"))?;
+                Join::new(
+                    exp.left().deep_clone_with(closures, counter)?,
+                    exp.right().deep_clone_with(closures, counter)?,
+                    left_key,
+                    right_key,
+                    mapper,
+                )
+                .into()
+            }
+            Mono::View(exp) => exp.as_ref().clone().into(),
+        })
+    }
+}
+
+/// Returns the error for an operator whose closures cannot be registered in [`Closures`].
+fn unsupported(name: &str) -> Error {
+    Error::UnsupportedExpression {
+        name: name.to_string(),
+        operation: "Deep Clone".to_string(),
+    }
 }
 
 impl<T: Tuple> From<Full<T>> for Mono<T> {
@@ -50,6 +183,12 @@ impl<T: Tuple> From<Singleton<T>> for Mono<T> {
     }
 }
 
+impl<T: Tuple> From<Param<T>> for Mono<T> {
+    fn from(param: Param<T>) -> Self {
+        Self::Param(param)
+    }
+}
+
 impl<T: Tuple> From<Relation<T>> for Mono<T> {
     fn from(relation: Relation<T>) -> Self {
         Self::Relation(relation)
@@ -68,6 +207,12 @@ impl<T: Tuple> From<Project<T, T, Mono<T>>> for Mono<T> {
     }
 }
 
+impl<T: Tuple> From<FilterMap<T, T, Mono<T>>> for Mono<T> {
+    fn from(filter_map: FilterMap<T, T, Mono<T>>) -> Self {
+        Self::FilterMap(Box::new(filter_map))
+    }
+}
+
 impl<T: Tuple> From<Union<T, Mono<T>, Mono<T>>> for Mono<T> {
     fn from(union: Union<T, Mono<T>, Mono<T>>) -> Self {
         Self::Union(Box::new(union))
@@ -113,9 +258,11 @@ impl<T: Tuple + 'static> Expression<T> for Mono<T> {
             Mono::Full(exp) => exp.visit(visitor),
             Mono::Empty(exp) => exp.visit(visitor),
             Mono::Singleton(exp) => exp.visit(visitor),
+            Mono::Param(exp) => exp.visit(visitor),
             Mono::Relation(exp) => exp.visit(visitor),
             Mono::Select(exp) => exp.visit(visitor),
             Mono::Project(exp) => exp.visit(visitor),
+            Mono::FilterMap(exp) => exp.visit(visitor),
             Mono::Union(exp) => exp.visit(visitor),
             Mono::Intersect(exp) => exp.visit(visitor),
             Mono::Difference(exp) => exp.visit(visitor),
@@ -125,3 +272,128 @@ impl<T: Tuple + 'static> Expression<T> for Mono<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+
+    /// Returns a predicate that selects the first `n` tuples that it is applied to.
+    fn take(n: usize) -> impl FnMut(&i32) -> bool {
+        let mut seen = 0;
+        move |_| {
+            seen += 1;
+            seen <= n
+        }
+    }
+
+    #[test]
+    fn test_clone_shares_state() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        let mono: Mono<i32> = Select::new(Mono::from(r), take(2)).into();
+        let clone = mono.clone();
+        assert_eq!(vec![1, 2], database.evaluate(&mono).unwrap().into_tuples());
+        // the predicate of `clone` is the one that has already seen the tuples:
+        assert!(database.evaluate(&clone).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_seal() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database
+            .insert(&r, (0..20).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(&s, (10..30).collect::<Vec<_>>().into())
+            .unwrap();
+
+        // the type of every sealed step is `Mono<i32>`, regardless of the chain's length:
+        let sealed: Mono<i32> = r
+            .builder()
+            .seal()
+            .select(|t| t % 2 == 0)
+            .seal()
+            .project(|t| t + 1)
+            .seal()
+            .union(s.builder().seal())
+            .seal()
+            .intersect(Mono::from(s.clone()))
+            .seal()
+            .filter_map(|&t| if t < 20 { Some(t * 10) } else { None })
+            .seal()
+            .build();
+        let nested = r
+            .builder()
+            .select(|t| t % 2 == 0)
+            .project(|t| t + 1)
+            .union(s.clone())
+            .intersect(s)
+            .filter_map(|&t| if t < 20 { Some(t * 10) } else { None })
+            .build();
+        assert_eq!(
+            database.evaluate(&nested).unwrap(),
+            database.evaluate(&sealed).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_deep_clone() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+        database.insert(&s, vec![2, 4].into()).unwrap();
+
+        {
+            let mono: Mono<i32> = Select::new(Mono::from(r.clone()), take(2)).into();
+            let copy = mono
+                .deep_clone(Closures::new().predicate("select#0", take(2)))
+                .unwrap();
+            assert_eq!(vec![1, 2], database.evaluate(&mono).unwrap().into_tuples());
+            // the predicate of `copy` has its own state:
+            assert_eq!(vec![1, 2], database.evaluate(&copy).unwrap().into_tuples());
+        }
+        {
+            let mono: Mono<i32> = Join::new(
+                Mono::from(Select::new(Mono::from(r.clone()), |&t| t < 4)),
+                Mono::from(s.clone()),
+                |&l| l,
+                |&r| r,
+                |&k, _, _| k,
+            )
+            .into();
+            let copy = mono
+                .deep_clone(
+                    Closures::new()
+                        .mapper("join_left_key#0", |&l| l)
+                        .mapper("join_right_key#1", |&r| r)
+                        .join_mapper("join#2", |&k, _, _| k * 10)
+                        .predicate("select#3", |&t| t < 4),
+                )
+                .unwrap();
+            assert_eq!(vec![2], database.evaluate(&mono).unwrap().into_tuples());
+            assert_eq!(vec![20], database.evaluate(&copy).unwrap().into_tuples());
+        }
+        {
+            let mono: Mono<i32> = Select::new(Mono::from(r), take(2)).into();
+            let result = mono.deep_clone(Closures::new().predicate("select#1", take(2)));
+            assert!(matches!(
+                result,
+                Err(Error::ClosureNotFound { name }) if name == "select#0"
+            ));
+        }
+        {
+            let mono: Mono<i32> =
+                Select::with_context(Mono::from(s), |min: &i32, t| t > min).into();
+            assert!(matches!(
+                mono.deep_clone(Closures::new()),
+                Err(Error::UnsupportedExpression { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+/*! Implements rewrite passes that optimize [`Mono`] expressions before evaluation.
+
+A [`Project`] directly over a [`Select`] walks the tuples of the inner expression twice:
+once to filter them and once to map the selected tuples. [`fuse`] rewrites every such pair
+into a single [`FilterMap`], which applies the predicate and the mapper in one pass and
+never materializes the intermediate selected tuples.
+*/
+use super::*;
+
+/// Returns an expression equivalent to `expression` in which every [`Project`] over a
+/// [`Select`] is fused into a single [`FilterMap`].
+///
+/// **Note**: the fused expression shares the closures of `expression`. Selects that are
+/// created by [`Select::ranged`] are not fused because their ranges are found by galloping
+/// over the sorted tuples, which is cheaper than a pass over the tuples. Selects and
+/// projects with a context are not fused either. The contents of
+/// views are not rewritten since they are already materialized in the database.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::{optimize, Mono, Project, Select}};
+///
+/// let mut db = Database::new();
+/// let r = db.add_relation::<i32>("r").unwrap();
+/// db.insert(&r, vec![1, 2, 3, 4].into());
+///
+/// let odd = Select::new(Mono::from(r), |t| t % 2 == 1);
+/// let mono: Mono<i32> = Project::new(Mono::from(odd), |t| t * 10).into();
+/// let fused = optimize::fuse(mono);
+///
+/// assert!(matches!(fused, Mono::FilterMap(_)));
+/// assert_eq!(vec![10, 30], db.evaluate(&fused).unwrap().into_tuples());
+/// ```
+pub fn fuse<T, E>(expression: E) -> Mono<T>
+where
+    T: Tuple + 'static,
+    E: Into<Mono<T>>,
+{
+    let mut expression = expression.into();
+    fuse_mut(&mut expression);
+    expression
+}
+
+fn fuse_mut<T: Tuple + 'static>(expression: &mut Mono<T>) {
+    let fused = match expression {
+        Mono::Full(_) | Mono::Empty(_) | Mono::Singleton(_) | Mono::Relation(_) => None,
+        Mono::Param(_) => None,
+        Mono::View(_) => None,
+        Mono::Select(exp) => {
+            fuse_mut(exp.expression_mut());
+            None
+        }
+        Mono::Project(exp) => {
+            fuse_mut(exp.expression_mut());
+            fuse_project(exp)
+        }
+        Mono::FilterMap(exp) => {
+            fuse_mut(exp.expression_mut());
+            None
+        }
+        Mono::Union(exp) => {
+            fuse_mut(exp.left_mut());
+            fuse_mut(exp.right_mut());
+            None
+        }
+        Mono::Intersect(exp) => {
+            fuse_mut(exp.left_mut());
+            fuse_mut(exp.right_mut());
+            None
+        }
+        Mono::Difference(exp) => {
+            fuse_mut(exp.left_mut());
+            fuse_mut(exp.right_mut());
+            None
+        }
+        Mono::Product(exp) => {
+            fuse_mut(exp.left_mut());
+            fuse_mut(exp.right_mut());
+            None
+        }
+        Mono::Join(exp) => {
+            fuse_mut(exp.left_mut());
+            fuse_mut(exp.right_mut());
+            None
+        }
+    };
+
+    if let Some(fused) = fused {
+        *expression = fused;
+    }
+}
+
+fn fuse_project<T: Tuple + 'static>(project: &Project<T, T, Mono<T>>) -> Option<Mono<T>> {
+    match project.expression() {
+        Mono::Select(select)
+            if !select.is_ranged() && !select.is_contextual() && !project.is_contextual() =>
+        {
+            let predicate = select.predicate_cell();
+            let mapper = project.mapper_cell();
+            let filter_map = FilterMap::new(select.expression().clone(), move |t| {
+                if (predicate.borrow_mut())(t) {
+                    Some((mapper.borrow_mut())(t))
+                } else {
+                    None
+                }
+            });
+            Some(filter_map.into())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_fuse() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let s = database.add_relation::<i32>("s").unwrap();
+        database
+            .insert(&r, (0..100).collect::<Vec<_>>().into())
+            .unwrap();
+        database
+            .insert(&s, (50..150).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let project = |e: Mono<i32>| -> Mono<i32> {
+            let select = Select::new(e, |t| t % 3 == 0);
+            Project::new(Mono::from(select), |t| t * 2).into()
+        };
+        let mono: Mono<i32> = Union::new(project(r.into()), project(s.into())).into();
+        let fused = fuse(mono.clone());
+        match &fused {
+            Mono::Union(union) => {
+                assert!(matches!(union.left(), Mono::FilterMap(_)));
+                assert!(matches!(union.right(), Mono::FilterMap(_)));
+            }
+            _ => panic!("expecting a union"),
+        }
+        assert_eq!(
+            database.evaluate(&mono).unwrap(),
+            database.evaluate(&fused).unwrap()
+        );
+        assert_eq!(
+            database.evaluate(&mono).unwrap(),
+            database.evaluate_optimized(&mono).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fuse_ranged() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database
+            .insert(&r, (0..10).collect::<Vec<_>>().into())
+            .unwrap();
+
+        let select = Select::ranged(Mono::from(r), |t| {
+            if *t < 3 {
+                Ordering::Less
+            } else if *t > 5 {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        });
+        let mono: Mono<i32> = Project::new(Mono::from(select), |t| t * 2).into();
+        let fused = fuse(mono);
+        assert!(matches!(fused, Mono::Project(_)));
+        assert_eq!(
+            vec![6, 8, 10],
+            database.evaluate(&fused).unwrap().into_tuples()
+        );
+    }
+}
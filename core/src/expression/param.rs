@@ -0,0 +1,121 @@
+use super::Expression;
+use crate::Tuple;
+use std::{any::Any, collections::HashMap, marker::PhantomData};
+
+/// Is a placeholder for a single tuple of type `T` that is supplied by name when the
+/// expression is evaluated by [`Database::evaluate_with_params`]. [`Param`] allows an
+/// expression to be built once and evaluated with different values, like a prepared
+/// statement.
+///
+/// **Note**: the value of a [`Param`] is only known at evaluation; therefore, [`Param`]
+/// cannot be used in views.
+///
+/// [`Database::evaluate_with_params`]: crate::Database::evaluate_with_params
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, Expression, expression::{Param, Params}};
+///
+/// let mut db = Database::new();
+/// let r = db.add_relation::<i32>("r").unwrap();
+/// db.insert(&r, vec![1, 2, 3].into());
+///
+/// let id = Param::<i32>::new("id");
+/// let found = r.builder().intersect(&id).build();
+///
+/// assert_eq!(
+///     vec![2],
+///     db.evaluate_with_params(&found, &Params::new().bind("id", 2)).unwrap().into_tuples()
+/// );
+/// assert!(db
+///     .evaluate_with_params(&found, &Params::new().bind("id", 4))
+///     .unwrap()
+///     .is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Param<T>
+where
+    T: Tuple,
+{
+    name: String,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Tuple> Param<T> {
+    /// Creates a new [`Param`] whose value is bound to `name`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the name of the receiver.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<T> Expression<T> for Param<T>
+where
+    T: Tuple,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: super::Visitor,
+    {
+        visitor.visit_param(self)
+    }
+}
+
+/// Binds the names of [`Param`] expressions to their values for an evaluation.
+#[derive(Default)]
+pub struct Params {
+    values: HashMap<String, Box<dyn Any>>,
+}
+
+impl Params {
+    /// Creates a new empty set of bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `value` to the parameters named `name`, replacing any previous binding of
+    /// `name`, and returns the receiver.
+    pub fn bind<T, S>(mut self, name: S, value: T) -> Self
+    where
+        T: Tuple + 'static,
+        S: Into<String>,
+    {
+        self.values.insert(name.into(), Box::new(value));
+        self
+    }
+
+    /// Returns the value that is bound to `param`, if a value of its type exists.
+    pub fn get<T>(&self, param: &Param<T>) -> Option<&T>
+    where
+        T: Tuple + 'static,
+    {
+        self.values
+            .get(param.name())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params() {
+        let params = Params::new().bind("x", 42).bind("y", "y".to_string());
+        assert_eq!(Some(&42), params.get(&Param::<i32>::new("x")));
+        assert_eq!(
+            Some(&"y".to_string()),
+            params.get(&Param::<String>::new("y"))
+        );
+        assert_eq!(None, params.get(&Param::<i32>::new("y")));
+        assert_eq!(None, params.get(&Param::<i32>::new("z")));
+    }
+}
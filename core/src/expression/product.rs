@@ -1,10 +1,16 @@
 use super::{view::ViewRef, Expression, IntoExpression, Visitor};
-use crate::Tuple;
-use std::{
-    cell::{RefCell, RefMut},
-    marker::PhantomData,
-    rc::Rc,
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
 };
+use std::marker::PhantomData;
+
+/// Is the type of [`Product`] closures that map pairs of tuples to tuples of type `T`.
+type Mapper<L, R, T> = closure!(FnMut(&L, &R) -> T);
+
+/// Is the type of [`Product`] closures that map the pairs of tuples that they keep to tuples
+/// of type `T`.
+type Filter<L, R, T> = closure!(FnMut(&L, &R) -> Option<T>);
 
 /// Corresponds to the cartesian product of two expression.
 ///
@@ -24,6 +30,7 @@ use std::{
 /// assert_eq!(vec![0, 2, 4, 8], db.evaluate(&prod).unwrap().into_tuples());
 /// ```
 #[derive(Clone)]
+#[allow(clippy::type_complexity)]
 pub struct Product<L, R, Left, Right, T>
 where
     L: Tuple,
@@ -34,7 +41,8 @@ where
 {
     left: Left,
     right: Right,
-    mapper: Rc<RefCell<dyn FnMut(&L, &R) -> T>>,
+    mapper: Shared<Lock<Mapper<L, R, T>>>,
+    filter: Option<Shared<Lock<Filter<L, R, T>>>>,
     relation_deps: Vec<String>,
     view_deps: Vec<ViewRef>,
 }
@@ -49,7 +57,11 @@ where
 {
     /// Creates a [`Product`] expression over `left` and `right` with `mapper` as the closure
     /// that produces the tuples of the resulting expression from tuples of `left` and `right`.
-    pub fn new<IL, IR>(left: IL, right: IR, project: impl FnMut(&L, &R) -> T + 'static) -> Self
+    pub fn new<IL, IR>(
+        left: IL,
+        right: IR,
+        project: impl FnMut(&L, &R) -> T + MaybeSync + 'static,
+    ) -> Self
     where
         IL: IntoExpression<L, Left>,
         IR: IntoExpression<R, Right>,
@@ -66,30 +78,93 @@ where
         Self {
             left,
             right,
-            mapper: Rc::new(RefCell::new(project)),
+            mapper: Shared::new(Lock::new(project)),
+            filter: None,
             relation_deps: relation_deps.into_iter().collect(),
             view_deps: view_deps.into_iter().collect(),
         }
     }
 
+    /// Creates a [`Product`] expression that maps and filters the pairs of tuples of `left`
+    /// and `right` in one pass: a pair contributes the tuple that `filter` returns for it,
+    /// and nothing if `filter` returns `None`. This saves a separate [`Select`] over a
+    /// product that discards most of its pairs.
+    ///
+    /// **Note**: [`Product::mapper_mut`] of the resulting expression must not be called;
+    /// its pairs are only produced by `filter`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::Product};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("R").unwrap();
+    /// let s = db.add_relation::<i32>("S").unwrap();
+    ///
+    /// db.insert(&r, vec![1, 2, 3].into());
+    /// db.insert(&s, vec![2, 3].into());
+    ///
+    /// let lt = Product::filtered(&r, &s, |&l, &r| if l < r { Some((l, r)) } else { None });
+    ///
+    /// assert_eq!(vec![(1, 2), (1, 3), (2, 3)], db.evaluate(&lt).unwrap().into_tuples());
+    /// ```
+    ///
+    /// [`Select`]: crate::expression::Select
+    pub fn filtered<IL, IR>(
+        left: IL,
+        right: IR,
+        filter: impl FnMut(&L, &R) -> Option<T> + MaybeSync + 'static,
+    ) -> Self
+    where
+        IL: IntoExpression<L, Left>,
+        IR: IntoExpression<R, Right>,
+    {
+        let mut product = Self::new(left, right, |_, _| {
+            unreachable!("a filtered product is evaluated by its filtering closure")
+        });
+        product.filter = Some(Shared::new(Lock::new(filter)));
+        product
+    }
+
     /// Returns a reference to the left sub-expression.
     #[inline(always)]
     pub fn left(&self) -> &Left {
         &self.left
     }
 
+    /// Returns a mutable reference to the left sub-expression.
+    #[inline(always)]
+    pub(crate) fn left_mut(&mut self) -> &mut Left {
+        &mut self.left
+    }
+
     /// Returns a reference to the right sub-expression.
     #[inline(always)]
     pub fn right(&self) -> &Right {
         &self.right
     }
 
+    /// Returns a mutable reference to the right sub-expression.
+    #[inline(always)]
+    pub(crate) fn right_mut(&mut self) -> &mut Right {
+        &mut self.right
+    }
+
     /// Returns a mutable reference (of type [`RefMut`]) to the mapping closure.
     #[inline(always)]
-    pub fn mapper_mut(&self) -> RefMut<dyn FnMut(&L, &R) -> T> {
+    #[allow(clippy::type_complexity)]
+    pub fn mapper_mut(&self) -> RefMut<'_, Mapper<L, R, T>> {
         self.mapper.borrow_mut()
     }
 
+    /// Returns a mutable reference (of type [`RefMut`]) to the filtering closure of the
+    /// receiver if it is created by [`Product::filtered`].
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn filter_mut(&self) -> Option<RefMut<'_, Filter<L, R, T>>> {
+        self.filter.as_ref().map(|f| f.borrow_mut())
+    }
+
     /// Returns a reference to the relation dependencies of the receiver.
     #[inline(always)]
     pub(crate) fn relation_deps(&self) -> &[String] {
@@ -115,12 +190,13 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_product(&self);
+        visitor.visit_product(self);
     }
 }
 
 // A hack for debugging purposes:
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Debuggable<L, R, Left, Right>
 where
     L: Tuple,
@@ -1,10 +1,16 @@
 use super::{view::ViewRef, Expression, IntoExpression, Visitor};
-use crate::Tuple;
-use std::{
-    cell::{RefCell, RefMut},
-    marker::PhantomData,
-    rc::Rc,
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
 };
+use std::{any::Any, marker::PhantomData};
+
+/// Is the type of [`Project`] closures that map tuples of type `S` to tuples of type `T`.
+type Mapper<S, T> = closure!(FnMut(&S) -> T);
+
+/// Is the type of the closures of [`Project::with_context`], which return `None` if the
+/// context is not of the expected type.
+type ContextualMapper<S, T> = closure!(FnMut(&dyn Any, &S) -> Option<T>);
 
 /// Projects the tuples of an inner sub-expression of type `S` to tuples of type `T`.
 ///
@@ -25,6 +31,7 @@ use std::{
 /// assert_eq!(vec!["apple", "banana", "cherry"], db.evaluate(&lower).unwrap().into_tuples());
 /// ```
 #[derive(Clone)]
+#[allow(clippy::type_complexity)]
 pub struct Project<S, T, E>
 where
     S: Tuple,
@@ -32,7 +39,8 @@ where
     E: Expression<S>,
 {
     expression: E,
-    mapper: Rc<RefCell<dyn FnMut(&S) -> T>>,
+    mapper: Shared<Lock<Mapper<S, T>>>,
+    contextual: Option<Shared<Lock<ContextualMapper<S, T>>>>,
     relation_deps: Vec<String>,
     view_deps: Vec<ViewRef>,
 }
@@ -45,7 +53,7 @@ where
 {
     /// Creates a new [`Project`] expression over `expression` with a closure `mapper` that
     /// projects tuples of `expression` to the resulting tuples.
-    pub fn new<I>(expression: I, mapper: impl FnMut(&S) -> T + 'static) -> Self
+    pub fn new<I>(expression: I, mapper: impl FnMut(&S) -> T + MaybeSync + 'static) -> Self
     where
         I: IntoExpression<S, E>,
     {
@@ -58,24 +66,95 @@ where
 
         Self {
             expression,
-            mapper: Rc::new(RefCell::new(mapper)),
+            mapper: Shared::new(Lock::new(mapper)),
+            contextual: None,
             relation_deps: relation_deps.into_iter().collect(),
             view_deps: view_deps.into_iter().collect(),
         }
     }
 
+    /// Creates a new [`Project`] expression over `expression` whose `mapper` also reads a
+    /// context of type `C`, which is supplied when the expression is evaluated by
+    /// [`Database::evaluate_with`].
+    ///
+    /// **Note**: the context is only known at evaluation; therefore, such a [`Project`]
+    /// cannot be used in views.
+    ///
+    /// [`Database::evaluate_with`]: crate::Database::evaluate_with
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::Project};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2].into()).unwrap();
+    ///
+    /// let names = Project::with_context(&r, |names: &HashMap<i32, &str>, t| names[t].to_string());
+    /// let context = HashMap::from([(1, "one"), (2, "two")]);
+    ///
+    /// assert_eq!(vec!["one", "two"], db.evaluate_with(&names, &context).unwrap().into_tuples());
+    /// ```
+    pub fn with_context<I, C, M>(expression: I, mut mapper: M) -> Self
+    where
+        S: 'static,
+        T: 'static,
+        I: IntoExpression<S, E>,
+        C: 'static,
+        M: FnMut(&C, &S) -> T + MaybeSync + 'static,
+    {
+        let mut project = Self::new(expression, |_| {
+            unreachable!("a projection with context is evaluated by its contextual mapper")
+        });
+        project.contextual = Some(Shared::new(Lock::new(move |context: &dyn Any, s: &S| {
+            context
+                .downcast_ref::<C>()
+                .map(|context| mapper(context, s))
+        })));
+        project
+    }
+
     /// Returns a reference to the underlying sub-expression.
     #[inline(always)]
     pub fn expression(&self) -> &E {
         &self.expression
     }
 
+    /// Returns a mutable reference to the underlying sub-expression.
+    #[inline(always)]
+    pub(crate) fn expression_mut(&mut self) -> &mut E {
+        &mut self.expression
+    }
+
     /// Returns a mutable reference (of type [`RefMut`]) to the projecting closure.
     #[inline(always)]
-    pub(crate) fn mapper_mut(&self) -> RefMut<dyn FnMut(&S) -> T> {
+    pub(crate) fn mapper_mut(&self) -> RefMut<'_, Mapper<S, T>> {
         self.mapper.borrow_mut()
     }
 
+    /// Returns the shared cell of the projecting closure.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn mapper_cell(&self) -> Shared<Lock<Mapper<S, T>>> {
+        self.mapper.clone()
+    }
+
+    /// Returns true if the receiver is created by [`Project::with_context`].
+    #[inline(always)]
+    pub(crate) fn is_contextual(&self) -> bool {
+        self.contextual.is_some()
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the projecting closure of the
+    /// receiver if it is created by [`Project::with_context`]. The closure returns `None`
+    /// if the context is not of its expected type.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn contextual_mut(&self) -> Option<RefMut<'_, ContextualMapper<S, T>>> {
+        self.contextual.as_ref().map(|c| c.borrow_mut())
+    }
+
     /// Returns a reference to the relation dependencies of the receiver.
     #[inline(always)]
     pub(crate) fn relation_deps(&self) -> &[String] {
@@ -99,12 +178,13 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_project(&self);
+        visitor.visit_project(self);
     }
 }
 
 // A hack:
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Debuggable<S, E>
 where
     S: Tuple,
@@ -64,7 +64,16 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_relation(&self);
+        visitor.visit_relation(self);
+    }
+}
+
+impl<T> std::fmt::Display for Relation<T>
+where
+    T: Tuple,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "relation {}", self.name)
     }
 }
 
@@ -78,6 +87,19 @@ mod tests {
         assert_eq!("a".to_string(), Relation::<i32>::new("a").name);
     }
 
+    #[test]
+    fn test_display() {
+        assert_eq!("relation r", Relation::<i32>::new("r").to_string());
+    }
+
+    #[test]
+    fn test_tuple_type_name() {
+        assert!(Relation::<i32>::new("r").tuple_type_name().contains("i32"));
+        assert!(Relation::<(i32, String)>::new("r")
+            .tuple_type_name()
+            .contains("String"));
+    }
+
     #[test]
     fn test_clone() {
         let mut database = Database::new();
@@ -0,0 +1,170 @@
+use super::{view::ViewRef, Expression, IntoExpression, Visitor};
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
+};
+use std::marker::PhantomData;
+
+/// Is the type of [`Scan`] closures that fold tuples of type `T` into values of type `A`.
+type Accumulator<T, A> = closure!(FnMut(&A, &T) -> A);
+
+/// Pairs every tuple of its sub-expression with a running accumulation of the tuples up to
+/// and including that tuple in their sorted order. The accumulation starts from an initial
+/// value and is updated by a closure for every tuple, e.g., to compute prefix sums.
+///
+/// **Note**: an accumulated value depends on every tuple that precedes it in the sorted
+/// order; therefore, the values are computed when the sub-expression is fully materialized,
+/// not incrementally. For the same reason, [`Scan`] cannot be used in views.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, expression::Scan};
+///
+/// let mut db = Database::new();
+/// let r = db.add_relation::<i32>("r").unwrap();
+///
+/// db.insert(&r, vec![3, 1, 2].into());
+///
+/// let sums = Scan::new(&r, 0, |sum, t| sum + t);
+///
+/// assert_eq!(vec![(1, 1), (2, 3), (3, 6)], db.evaluate(&sums).unwrap().into_tuples());
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct Scan<T, A, E>
+where
+    T: Tuple,
+    A: Tuple,
+    E: Expression<T>,
+{
+    expression: E,
+    init: A,
+    accumulator: Shared<Lock<Accumulator<T, A>>>,
+    relation_deps: Vec<String>,
+    view_deps: Vec<ViewRef>,
+}
+
+impl<T, A, E> Scan<T, A, E>
+where
+    T: Tuple,
+    A: Tuple,
+    E: Expression<T>,
+{
+    /// Creates a new [`Scan`] expression over `expression` that accumulates its tuples in
+    /// their sorted order with `accumulator`, starting from `init`.
+    pub fn new<I>(
+        expression: I,
+        init: A,
+        accumulator: impl FnMut(&A, &T) -> A + MaybeSync + 'static,
+    ) -> Self
+    where
+        I: IntoExpression<T, E>,
+    {
+        use super::dependency;
+        let expression = expression.into_expression();
+
+        let mut deps = dependency::DependencyVisitor::new();
+        expression.visit(&mut deps);
+        let (relation_deps, view_deps) = deps.into_dependencies();
+
+        Self {
+            expression,
+            init,
+            accumulator: Shared::new(Lock::new(accumulator)),
+            relation_deps: relation_deps.into_iter().collect(),
+            view_deps: view_deps.into_iter().collect(),
+        }
+    }
+
+    /// Returns a reference to the underlying sub-expression.
+    #[inline(always)]
+    pub fn expression(&self) -> &E {
+        &self.expression
+    }
+
+    /// Returns a reference to the initial value of the accumulation.
+    #[inline(always)]
+    pub fn init(&self) -> &A {
+        &self.init
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the accumulating closure.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn accumulator_mut(&self) -> RefMut<'_, Accumulator<T, A>> {
+        self.accumulator.borrow_mut()
+    }
+
+    /// Returns a reference to the relation dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn relation_deps(&self) -> &[String] {
+        &self.relation_deps
+    }
+
+    /// Returns a reference to the view dependencies of the receiver.
+    #[inline(always)]
+    pub(crate) fn view_deps(&self) -> &[ViewRef] {
+        &self.view_deps
+    }
+}
+
+impl<T, A, E> Expression<(T, A)> for Scan<T, A, E>
+where
+    T: Tuple,
+    A: Tuple,
+    E: Expression<T>,
+{
+    fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor,
+    {
+        visitor.visit_scan(self);
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Debuggable<T, A, E>
+where
+    T: Tuple,
+    A: Tuple,
+    E: Expression<T>,
+{
+    expression: E,
+    init: A,
+    _marker: PhantomData<T>,
+}
+
+impl<T, A, E> std::fmt::Debug for Scan<T, A, E>
+where
+    T: Tuple,
+    A: Tuple,
+    E: Expression<T>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debuggable {
+            expression: self.expression.clone(),
+            init: self.init.clone(),
+            _marker: PhantomData,
+        }
+        .fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, Tuples};
+
+    #[test]
+    fn test_clone() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3].into()).unwrap();
+        let s = Scan::new(&r, 0, |count, _| count + 1).clone();
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(1, 1), (2, 2), (3, 3)]),
+            database.evaluate(&s).unwrap()
+        );
+    }
+}
@@ -1,10 +1,20 @@
 use super::{view::ViewRef, Expression, IntoExpression, Visitor};
-use crate::Tuple;
-use std::{
-    cell::{RefCell, RefMut},
-    marker::PhantomData,
-    rc::Rc,
+use crate::{
+    shared::{Lock, RefMut, Shared},
+    MaybeSync, Tuple,
 };
+use std::{any::Any, cmp::Ordering, marker::PhantomData};
+
+/// Is the type of [`Select`] predicates over tuples of type `T`.
+type Predicate<T> = closure!(FnMut(&T) -> bool);
+
+/// Is the type of the closures of [`Select::ranged`] that compare tuples of type `T` with the
+/// selected range.
+type Range<T> = closure!(FnMut(&T) -> Ordering);
+
+/// Is the type of the predicates of [`Select::with_context`], which return `None` if the
+/// context is not of the expected type.
+type ContextualPredicate<T> = closure!(FnMut(&dyn Any, &T) -> Option<bool>);
 
 /// Selects tuples of the underlying sub-expression according to a given predicate.
 ///
@@ -25,15 +35,21 @@ use std::{
 /// assert_eq!(vec!["Apple", "BANANA"], db.evaluate(&select).unwrap().into_tuples());
 /// ```
 #[derive(Clone)]
+#[allow(clippy::type_complexity)]
 pub struct Select<T, E>
 where
     T: Tuple,
     E: Expression<T>,
 {
     expression: E,
-    predicate: Rc<RefCell<dyn FnMut(&T) -> bool>>,
+    predicate: Shared<Lock<Predicate<T>>>,
+    range: Option<Shared<Lock<Range<T>>>>,
+    contextual: Option<Shared<Lock<ContextualPredicate<T>>>>,
     relation_deps: Vec<String>,
     view_deps: Vec<ViewRef>,
+
+    /// Identifies the receiver and its clones as the same node of an expression tree.
+    node: Shared<()>,
 }
 
 impl<T, E> Select<T, E>
@@ -45,7 +61,7 @@ where
     pub fn new<I, P>(expression: I, predicate: P) -> Self
     where
         I: IntoExpression<T, E>,
-        P: FnMut(&T) -> bool + 'static,
+        P: FnMut(&T) -> bool + MaybeSync + 'static,
     {
         use super::dependency;
         let expression = expression.into_expression();
@@ -56,24 +72,139 @@ where
 
         Self {
             expression,
-            predicate: Rc::new(RefCell::new(predicate)),
+            predicate: Shared::new(Lock::new(predicate)),
+            range: None,
+            contextual: None,
             relation_deps: relation_deps.into_iter().collect(),
             view_deps: view_deps.into_iter().collect(),
+            node: Shared::new(()),
         }
     }
 
+    /// Creates a new [`Select`] expression over `expression` that selects a contiguous range
+    /// of the sorted tuples of `expression`. The `range` closure returns [`Ordering::Less`]
+    /// for tuples that come before the range, [`Ordering::Equal`] for tuples in the range
+    /// and [`Ordering::Greater`] for tuples that come after the range.
+    ///
+    /// **Note**: `range` must be monotone over the sorted order of tuples; that is, it may
+    /// never return a smaller [`Ordering`] for a larger tuple. Under this assumption, the
+    /// boundaries of the range are found by galloping over every sorted batch of tuples
+    /// instead of scanning them.
+    pub fn ranged<I, R>(expression: I, range: R) -> Self
+    where
+        T: 'static,
+        I: IntoExpression<T, E>,
+        R: FnMut(&T) -> Ordering + MaybeSync + 'static,
+    {
+        let range = Shared::new(Lock::new(range));
+        let inner = range.clone();
+        let mut select = Self::new(expression, move |t| {
+            (inner.borrow_mut())(t) == Ordering::Equal
+        });
+        select.range = Some(range);
+        select
+    }
+
+    /// Creates a new [`Select`] expression over `expression` whose `predicate` also reads a
+    /// context of type `C`, which is supplied when the expression is evaluated by
+    /// [`Database::evaluate_with`].
+    ///
+    /// **Note**: the context is only known at evaluation; therefore, such a [`Select`]
+    /// cannot be used in views.
+    ///
+    /// [`Database::evaluate_with`]: crate::Database::evaluate_with
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::Select};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+    ///
+    /// let above = Select::with_context(&r, |min: &i32, t| t > min);
+    ///
+    /// assert_eq!(vec![3, 4], db.evaluate_with(&above, &2).unwrap().into_tuples());
+    /// ```
+    pub fn with_context<I, C, P>(expression: I, mut predicate: P) -> Self
+    where
+        T: 'static,
+        I: IntoExpression<T, E>,
+        C: 'static,
+        P: FnMut(&C, &T) -> bool + MaybeSync + 'static,
+    {
+        let mut select = Self::new(expression, |_| false);
+        select.contextual = Some(Shared::new(Lock::new(move |context: &dyn Any, t: &T| {
+            context
+                .downcast_ref::<C>()
+                .map(|context| predicate(context, t))
+        })));
+        select
+    }
+
     /// Returns a reference to the underlying sub-expression.
     #[inline(always)]
     pub fn expression(&self) -> &E {
         &self.expression
     }
 
+    /// Returns a mutable reference to the underlying sub-expression. Since the sub-expression
+    /// may be changed, the receiver is no longer identified with its clones.
+    #[inline(always)]
+    pub(crate) fn expression_mut(&mut self) -> &mut E {
+        self.node = Shared::new(());
+        &mut self.expression
+    }
+
     /// Returns a mutable reference (of type [`RefMut`]) to the select predicate.
     #[inline(always)]
-    pub(crate) fn predicate_mut(&self) -> RefMut<dyn FnMut(&T) -> bool> {
+    pub(crate) fn predicate_mut(&self) -> RefMut<'_, Predicate<T>> {
         self.predicate.borrow_mut()
     }
 
+    /// Returns the shared cell of the select predicate.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn predicate_cell(&self) -> Shared<Lock<Predicate<T>>> {
+        self.predicate.clone()
+    }
+
+    /// Returns an identifier of the receiver that is shared by its clones, or `None` if the
+    /// receiver has never been cloned.
+    #[inline(always)]
+    pub(crate) fn shared_id(&self) -> Option<usize> {
+        (Shared::strong_count(&self.node) > 1).then_some(Shared::as_ptr(&self.node) as usize)
+    }
+
+    /// Returns true if the receiver is created by [`Select::ranged`].
+    #[inline(always)]
+    pub(crate) fn is_ranged(&self) -> bool {
+        self.range.is_some()
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the range closure of the receiver
+    /// if it is created by [`Select::ranged`].
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn range_mut(&self) -> Option<RefMut<'_, Range<T>>> {
+        self.range.as_ref().map(|r| r.borrow_mut())
+    }
+
+    /// Returns true if the receiver is created by [`Select::with_context`].
+    #[inline(always)]
+    pub(crate) fn is_contextual(&self) -> bool {
+        self.contextual.is_some()
+    }
+
+    /// Returns a mutable reference (of type [`RefMut`]) to the predicate of the receiver
+    /// if it is created by [`Select::with_context`]. The predicate returns `None` if the
+    /// context is not of its expected type.
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn contextual_mut(&self) -> Option<RefMut<'_, ContextualPredicate<T>>> {
+        self.contextual.as_ref().map(|c| c.borrow_mut())
+    }
+
     /// Returns a reference to the relation dependencies of the receiver.
     #[inline(always)]
     pub(crate) fn relation_deps(&self) -> &[String] {
@@ -96,11 +227,12 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_select(&self);
+        visitor.visit_select(self);
     }
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct Debuggable<T, E>
 where
     T: Tuple,
@@ -140,4 +272,26 @@ mod tests {
             database.evaluate(&p).unwrap()
         );
     }
+
+    #[test]
+    fn test_ranged() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database
+            .insert(&r, (0..20).collect::<Vec<_>>().into())
+            .unwrap();
+        let p = Select::ranged(&r, |&t| {
+            if t < 5 {
+                Ordering::Less
+            } else if t < 8 {
+                Ordering::Equal
+            } else {
+                Ordering::Greater
+            }
+        });
+        assert_eq!(
+            Tuples::<i32>::from(vec![5, 6, 7]),
+            database.evaluate(&p).unwrap()
+        );
+    }
 }
@@ -0,0 +1,693 @@
+/*! Implements [`Shape`], a serializable descriptor of the operator tree of an [`Expression`].
+
+Closures and singleton tuples cannot be serialized; a [`Shape`] refers to them by opaque
+placeholder names. A [`Mono`] expression is reconstructed from a [`Shape`] by supplying the
+closures and tuples of the placeholders in a [`Closures`] registry.
+*/
+use super::{closures::take, view::ViewRef, *};
+use crate::{Database, Error};
+use serde::{Deserialize, Serialize};
+
+/// Is a normalized descriptor of the structure of an [`Expression`] where closures and
+/// singleton tuples are represented by placeholder names.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{Database, Expression, expression::{Closures, Shape}};
+///
+/// let mut db = Database::new();
+/// let r = db.add_relation::<i32>("r").unwrap();
+/// db.insert(&r, vec![1, 2, 3].into());
+///
+/// let select = r.builder().select(|t| t % 2 == 1).build();
+/// let shape = Shape::of(&select);
+///
+/// let closures = Closures::new().predicate("select#0", |t| t % 2 == 0);
+/// let mono = shape.to_mono(closures).unwrap();
+///
+/// assert_eq!(vec![2], db.evaluate(&mono).unwrap().into_tuples());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Shape {
+    Full,
+    Empty,
+    Singleton {
+        tuple: String,
+    },
+    Param {
+        name: String,
+    },
+    Relation {
+        name: String,
+    },
+    Select {
+        predicate: String,
+        expression: Box<Shape>,
+    },
+    Project {
+        mapper: String,
+        expression: Box<Shape>,
+    },
+    FilterMap {
+        mapper: String,
+        expression: Box<Shape>,
+    },
+    FlatProject {
+        mapper: String,
+        expression: Box<Shape>,
+    },
+    TryProject {
+        mapper: String,
+        expression: Box<Shape>,
+    },
+    Union {
+        left: Box<Shape>,
+        right: Box<Shape>,
+    },
+    Intersect {
+        left: Box<Shape>,
+        right: Box<Shape>,
+    },
+    Difference {
+        left: Box<Shape>,
+        right: Box<Shape>,
+    },
+    Product {
+        mapper: String,
+        left: Box<Shape>,
+        right: Box<Shape>,
+    },
+    Join {
+        left_key: String,
+        right_key: String,
+        mapper: String,
+        left: Box<Shape>,
+        right: Box<Shape>,
+        #[serde(default)]
+        mode: JoinMode,
+    },
+    Enumerate {
+        expression: Box<Shape>,
+    },
+    GroupBy {
+        key: String,
+        fold: String,
+        expression: Box<Shape>,
+    },
+    Scan {
+        accumulator: String,
+        expression: Box<Shape>,
+    },
+    View {
+        reference: u64,
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+impl Shape {
+    /// Returns the shape of `expression`. The placeholders of the shape are named after
+    /// their operators and numbered in the pre-order of the operator tree (e.g., `select#0`).
+    pub fn of<T, E>(expression: &E) -> Self
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        let mut visitor = ShapeVisitor::new();
+        expression.visit(&mut visitor);
+        visitor.into_shape()
+    }
+
+    /// Consumes `closures` and reconstructs a [`Mono`] expression with the structure of the
+    /// receiver, using the closures and tuples of `closures` for the placeholders of the
+    /// receiver.
+    ///
+    /// **Note**: unlike [`Mono::deep_clone`], which copies an expression that is at hand,
+    /// reconstructing an expression from its shape works for a shape that is deserialized.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, expression::{Closures, Mono, Select, Shape}};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// db.insert(&r, vec![1, 2, 3].into());
+    ///
+    /// let odd = |t: &i32| t % 2 == 1;
+    /// let mono: Mono<i32> = Select::new(Mono::from(r), odd).into();
+    /// let copy = Shape::of(&mono)
+    ///     .to_mono(Closures::new().predicate("select#0", odd))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vec![1, 3], db.evaluate(&copy).unwrap().into_tuples());
+    /// ```
+    pub fn to_mono<T>(&self, mut closures: Closures<T>) -> Result<Mono<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        self.build(&mut closures, None)
+    }
+
+    /// Reconstructs a [`Mono`] expression like [`Shape::to_mono`] whose views are resolved
+    /// in `database` by [`Database::resolve_view`]: a named view is bound to the view of
+    /// `database` with the same name, which makes a shape that is persisted in one run
+    /// usable in another. Fails if a view of the receiver does not exist in `database`.
+    ///
+    /// **Example**:
+    /// ```rust
+    /// use codd::{Database, Expression, expression::{Closures, Shape}};
+    ///
+    /// let mut db = Database::new();
+    /// let r = db.add_relation::<i32>("r").unwrap();
+    /// let odds = db.store_named_view("odds", r.builder().select(|t| t % 2 == 1)).unwrap();
+    /// let json = serde_json::to_string(&Shape::of(&odds)).unwrap();
+    ///
+    /// // in another run:
+    /// let mut other = Database::new();
+    /// let r = other.add_relation::<i32>("r").unwrap();
+    /// other.store_view(r.builder().project(|t| t * 2)).unwrap();
+    /// other.store_named_view("odds", r.builder().select(|t| t % 2 == 1)).unwrap();
+    /// other.insert(&r, vec![1, 2, 3].into()).unwrap();
+    ///
+    /// let shape: Shape = serde_json::from_str(&json).unwrap();
+    /// let odds = shape.to_mono_in::<i32>(&other, Closures::new()).unwrap();
+    /// assert_eq!(vec![1, 3], other.evaluate(&odds).unwrap().into_tuples());
+    /// ```
+    ///
+    /// [`Database::resolve_view`]: crate::Database::resolve_view
+    pub fn to_mono_in<T>(
+        &self,
+        database: &Database,
+        mut closures: Closures<T>,
+    ) -> Result<Mono<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        self.build(&mut closures, Some(database))
+    }
+
+    fn build<T>(
+        &self,
+        closures: &mut Closures<T>,
+        database: Option<&Database>,
+    ) -> Result<Mono<T>, Error>
+    where
+        T: Tuple + 'static,
+    {
+        Ok(match self {
+            Shape::Full => Full::new().into(),
+            Shape::Empty => Empty::new().into(),
+            Shape::Singleton { tuple } => Singleton::new(take(&mut closures.tuples, tuple)?).into(),
+            Shape::Param { name } => Param::new(name).into(),
+            Shape::Relation { name } => Relation::new(name).into(),
+            Shape::Select {
+                predicate,
+                expression,
+            } => Select::new(
+                expression.build(closures, database)?,
+                take(&mut closures.predicates, predicate)?,
+            )
+            .into(),
+            Shape::Project { mapper, expression } => Project::new(
+                expression.build(closures, database)?,
+                take(&mut closures.mappers, mapper)?,
+            )
+            .into(),
+            Shape::FilterMap { mapper, expression } => FilterMap::new(
+                expression.build(closures, database)?,
+                take(&mut closures.filter_mappers, mapper)?,
+            )
+            .into(),
+            Shape::FlatProject { .. } => {
+                // closures that return vectors are not registered in `Closures`:
+                return Err(Error::UnsupportedExpression {
+                    name: "FlatProject".to_string(),
+                    operation: "Reconstruct".to_string(),
+                });
+            }
+            Shape::TryProject { .. } => {
+                // fallible closures are not registered in `Closures`:
+                return Err(Error::UnsupportedExpression {
+                    name: "TryProject".to_string(),
+                    operation: "Reconstruct".to_string(),
+                });
+            }
+            Shape::Union { left, right } => Union::new(
+                left.build(closures, database)?,
+                right.build(closures, database)?,
+            )
+            .into(),
+            Shape::Intersect { left, right } => Intersect::new(
+                left.build(closures, database)?,
+                right.build(closures, database)?,
+            )
+            .into(),
+            Shape::Difference { left, right } => Difference::new(
+                left.build(closures, database)?,
+                right.build(closures, database)?,
+            )
+            .into(),
+            Shape::Product {
+                mapper,
+                left,
+                right,
+            } => {
+                let mapper = take(&mut closures.product_mappers, mapper)?;
+                Product::new(
+                    left.build(closures, database)?,
+                    right.build(closures, database)?,
+                    mapper,
+                )
+                .into()
+            }
+            Shape::Join { mode, .. } if *mode != JoinMode::Inner => {
+                // closures over rows with optional right tuples are not registered in `Closures`:
+                return Err(Error::UnsupportedExpression {
+                    name: format!("{:?} Join", mode),
+                    operation: "Reconstruct".to_string(),
+                });
+            }
+            Shape::Join {
+                left_key,
+                right_key,
+                mapper,
+                left,
+                right,
+                ..
+            } => {
+                let left_key = take(&mut closures.mappers, left_key)?;
+                let right_key = take(&mut closures.mappers, right_key)?;
+                let mapper = take(&mut closures.join_mappers, mapper)?;
+                Join::new(
+                    left.build(closures, database)?,
+                    right.build(closures, database)?,
+                    left_key,
+                    right_key,
+                    mapper,
+                )
+                .into()
+            }
+            Shape::Enumerate { .. } => {
+                // `Enumerate` changes the type of tuples, so it cannot be a `Mono`:
+                return Err(Error::UnsupportedExpression {
+                    name: "Enumerate".to_string(),
+                    operation: "Reconstruct".to_string(),
+                });
+            }
+            Shape::GroupBy { .. } => {
+                // `GroupBy` changes the type of tuples, so it cannot be a `Mono`:
+                return Err(Error::UnsupportedExpression {
+                    name: "GroupBy".to_string(),
+                    operation: "Reconstruct".to_string(),
+                });
+            }
+            Shape::Scan { .. } => {
+                // `Scan` changes the type of tuples, so it cannot be a `Mono`:
+                return Err(Error::UnsupportedExpression {
+                    name: "Scan".to_string(),
+                    operation: "Reconstruct".to_string(),
+                });
+            }
+            Shape::View { reference, name } => {
+                let reference = match name {
+                    Some(name) => ViewRef::new(*reference).with_name(name),
+                    None => ViewRef::new(*reference),
+                };
+                match database {
+                    Some(database) => {
+                        View::new(database.resolve_view(&reference).ok_or_else(|| {
+                            Error::DanglingViewRef {
+                                name: format!("{:?}", reference),
+                            }
+                        })?)
+                    }
+                    None => View::new(reference),
+                }
+                .into()
+            }
+        })
+    }
+}
+
+/// Implements [`Visitor`] to compute the [`Shape`] of an expression.
+struct ShapeVisitor {
+    /// Is the number of placeholders that have been named.
+    counter: usize,
+
+    /// Is a stack of the shapes of the visited sub-expressions.
+    stack: Vec<Shape>,
+}
+
+impl ShapeVisitor {
+    fn new() -> Self {
+        Self {
+            counter: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns a new placeholder name for a closure of `operator`.
+    fn placeholder(&mut self, operator: &str) -> String {
+        let name = format!("{}#{}", operator, self.counter);
+        self.counter += 1;
+        name
+    }
+
+    fn pop(&mut self) -> Box<Shape> {
+        Box::new(self.stack.pop().unwrap())
+    }
+
+    fn pop_pair(&mut self) -> (Box<Shape>, Box<Shape>) {
+        let right = self.pop();
+        let left = self.pop();
+        (left, right)
+    }
+
+    fn into_shape(mut self) -> Shape {
+        self.stack.pop().unwrap()
+    }
+}
+
+impl Visitor for ShapeVisitor {
+    fn visit_full<T>(&mut self, _: &Full<T>)
+    where
+        T: Tuple,
+    {
+        self.stack.push(Shape::Full);
+    }
+
+    fn visit_empty<T>(&mut self, _: &Empty<T>)
+    where
+        T: Tuple,
+    {
+        self.stack.push(Shape::Empty);
+    }
+
+    fn visit_singleton<T>(&mut self, _: &Singleton<T>)
+    where
+        T: Tuple,
+    {
+        let tuple = self.placeholder("singleton");
+        self.stack.push(Shape::Singleton { tuple });
+    }
+
+    fn visit_param<T>(&mut self, param: &Param<T>)
+    where
+        T: Tuple,
+    {
+        self.stack.push(Shape::Param {
+            name: param.name().to_string(),
+        });
+    }
+
+    fn visit_relation<T>(&mut self, relation: &Relation<T>)
+    where
+        T: Tuple,
+    {
+        self.stack.push(Shape::Relation {
+            name: relation.name().to_string(),
+        });
+    }
+
+    fn visit_select<T, E>(&mut self, select: &Select<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        let predicate = self.placeholder("select");
+        select.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::Select {
+            predicate,
+            expression,
+        });
+    }
+
+    fn visit_union<T, L, R>(&mut self, union: &Union<T, L, R>)
+    where
+        T: Tuple,
+        L: Expression<T>,
+        R: Expression<T>,
+    {
+        union.left().visit(self);
+        union.right().visit(self);
+        let (left, right) = self.pop_pair();
+        self.stack.push(Shape::Union { left, right });
+    }
+
+    fn visit_intersect<T, L, R>(&mut self, intersect: &Intersect<T, L, R>)
+    where
+        T: Tuple,
+        L: Expression<T>,
+        R: Expression<T>,
+    {
+        intersect.left().visit(self);
+        intersect.right().visit(self);
+        let (left, right) = self.pop_pair();
+        self.stack.push(Shape::Intersect { left, right });
+    }
+
+    fn visit_difference<T, L, R>(&mut self, difference: &Difference<T, L, R>)
+    where
+        T: Tuple,
+        L: Expression<T>,
+        R: Expression<T>,
+    {
+        difference.left().visit(self);
+        difference.right().visit(self);
+        let (left, right) = self.pop_pair();
+        self.stack.push(Shape::Difference { left, right });
+    }
+
+    fn visit_project<S, T, E>(&mut self, project: &Project<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        let mapper = self.placeholder("project");
+        project.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::Project { mapper, expression });
+    }
+
+    fn visit_filter_map<S, T, E>(&mut self, filter_map: &FilterMap<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        let mapper = self.placeholder("filter_map");
+        filter_map.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::FilterMap { mapper, expression });
+    }
+
+    fn visit_flat_project<S, T, E>(&mut self, flat_project: &FlatProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        let mapper = self.placeholder("flat_project");
+        flat_project.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::FlatProject { mapper, expression });
+    }
+
+    fn visit_try_project<S, T, E>(&mut self, try_project: &TryProject<S, T, E>)
+    where
+        T: Tuple,
+        S: Tuple,
+        E: Expression<S>,
+    {
+        let mapper = self.placeholder("try_project");
+        try_project.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::TryProject { mapper, expression });
+    }
+
+    fn visit_product<L, R, Left, Right, T>(&mut self, product: &Product<L, R, Left, Right, T>)
+    where
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: Expression<L>,
+        Right: Expression<R>,
+    {
+        let mapper = self.placeholder("product");
+        product.left().visit(self);
+        product.right().visit(self);
+        let (left, right) = self.pop_pair();
+        self.stack.push(Shape::Product {
+            mapper,
+            left,
+            right,
+        });
+    }
+
+    fn visit_join<K, L, R, Left, Right, T>(&mut self, join: &Join<K, L, R, Left, Right, T>)
+    where
+        K: Tuple,
+        L: Tuple,
+        R: Tuple,
+        T: Tuple,
+        Left: Expression<L>,
+        Right: Expression<R>,
+    {
+        let left_key = self.placeholder("join_left_key");
+        let right_key = self.placeholder("join_right_key");
+        let mapper = self.placeholder("join");
+        join.left().visit(self);
+        join.right().visit(self);
+        let (left, right) = self.pop_pair();
+        self.stack.push(Shape::Join {
+            left_key,
+            right_key,
+            mapper,
+            left,
+            right,
+            mode: join.mode(),
+        });
+    }
+
+    fn visit_enumerate<T, E>(&mut self, enumerate: &Enumerate<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        enumerate.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::Enumerate { expression });
+    }
+
+    fn visit_group_by<S, K, A, E>(&mut self, group_by: &GroupBy<S, K, A, E>)
+    where
+        S: Tuple,
+        K: Tuple,
+        A: Tuple,
+        E: Expression<S>,
+    {
+        let key = self.placeholder("group_by_key");
+        let fold = self.placeholder("group_by");
+        group_by.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::GroupBy {
+            key,
+            fold,
+            expression,
+        });
+    }
+
+    fn visit_scan<T, A, E>(&mut self, scan: &Scan<T, A, E>)
+    where
+        T: Tuple,
+        A: Tuple,
+        E: Expression<T>,
+    {
+        let accumulator = self.placeholder("scan");
+        scan.expression().visit(self);
+        let expression = self.pop();
+        self.stack.push(Shape::Scan {
+            accumulator,
+            expression,
+        });
+    }
+
+    fn visit_view<T, E>(&mut self, view: &View<T, E>)
+    where
+        T: Tuple,
+        E: Expression<T>,
+    {
+        self.stack.push(Shape::View {
+            reference: view.reference().id(),
+            name: view.reference().name().map(str::to_string),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        let exp = r.builder().project(|t| t * 10).select(|&t| t > 10).build();
+        let shape = Shape::of(&exp);
+        assert_eq!(
+            Shape::Select {
+                predicate: "select#0".to_string(),
+                expression: Box::new(Shape::Project {
+                    mapper: "project#1".to_string(),
+                    expression: Box::new(Shape::Relation {
+                        name: "r".to_string()
+                    }),
+                }),
+            },
+            shape
+        );
+
+        let json = serde_json::to_string(&shape).unwrap();
+        let shape: Shape = serde_json::from_str(&json).unwrap();
+
+        let closures = Closures::new()
+            .predicate("select#0", |&t| t > 10)
+            .mapper("project#1", |t| t * 10);
+        let mono = shape.to_mono(closures).unwrap();
+        assert_eq!(Shape::of(&mono), shape);
+        assert_eq!(
+            database.evaluate(&exp).unwrap(),
+            database.evaluate(&mono).unwrap()
+        );
+
+        assert!(matches!(
+            shape.to_mono(Closures::<i32>::new().mapper("project#1", |t| *t)),
+            Err(Error::ClosureNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_view_resolved_by_name() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        let evens = database
+            .store_named_view("evens", r.builder().select(|t| t % 2 == 0))
+            .unwrap();
+        let json = serde_json::to_string(&Shape::of(&evens)).unwrap();
+        let shape: Shape = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            Shape::View {
+                reference: evens.reference().id(),
+                name: Some("evens".to_string()),
+            },
+            shape
+        );
+
+        let mut other = Database::new();
+        let r = other.add_relation::<i32>("r").unwrap();
+        other.store_view(r.builder().project(|t| t + 1)).unwrap();
+        let expected = other
+            .store_named_view("evens", r.builder().select(|t| t % 2 == 0))
+            .unwrap();
+        other.insert(&r, vec![1, 2, 3, 4].into()).unwrap();
+
+        let mono = shape.to_mono_in::<i32>(&other, Closures::new()).unwrap();
+        assert_eq!(
+            Shape::View {
+                reference: expected.reference().id(),
+                name: Some("evens".to_string()),
+            },
+            Shape::of(&mono)
+        );
+        assert_eq!(vec![2, 4], other.evaluate(&mono).unwrap().into_tuples());
+
+        assert!(matches!(
+            shape.to_mono_in::<i32>(&Database::new(), Closures::new()),
+            Err(Error::DanglingViewRef { .. })
+        ));
+    }
+}
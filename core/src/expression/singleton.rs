@@ -44,7 +44,7 @@ where
     where
         V: super::Visitor,
     {
-        visitor.visit_singleton(&self)
+        visitor.visit_singleton(self)
     }
 }
 
@@ -62,4 +62,12 @@ mod tests {
         let s = Singleton::new(42);
         assert_eq!(42, s.clone().into_tuple());
     }
+
+    #[test]
+    fn test_into_expression() {
+        use crate::expression::IntoExpression;
+
+        let s: Singleton<_> = 42.into_expression();
+        assert_eq!(42, s.into_tuple());
+    }
 }
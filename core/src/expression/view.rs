@@ -2,9 +2,81 @@ use super::{Expression, Visitor};
 use crate::Tuple;
 use std::marker::PhantomData;
 
-/// Is the type of the view identifiers in a database.
-#[derive(PartialEq, Eq, Clone, Hash, Debug)]
-pub struct ViewRef(pub(crate) i32);
+/// Is the type of the view identifiers in a database. A view is identified by a number
+/// that is assigned by the database when the view is stored; a view that is stored by
+/// [`Database::store_named_view`] carries its name as well.
+///
+/// **Note**: the numeric identifier of a view depends on the order in which views are
+/// stored; hence, it may change from one run to another. The identifier of a view that is
+/// dropped by [`Database::drop_view`] is reused by the next view that is stored. The name
+/// of a view is stable and can be used to resolve a persisted [`ViewRef`] in a database by
+/// [`Database::resolve_view`]. Two references are equal if their numeric identifiers are
+/// equal.
+///
+/// [`Database::store_named_view`]: crate::Database::store_named_view
+/// [`Database::drop_view`]: crate::Database::drop_view
+/// [`Database::resolve_view`]: crate::Database::resolve_view
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViewRef(pub(crate) u64, pub(crate) Option<String>);
+
+impl ViewRef {
+    /// Creates a new unnamed [`ViewRef`] with the numeric identifier `id`.
+    pub(crate) fn new(id: u64) -> Self {
+        Self(id, None)
+    }
+
+    /// Returns a copy of the receiver that carries `name`.
+    pub(crate) fn with_name(&self, name: &str) -> Self {
+        Self(self.0, Some(name.into()))
+    }
+
+    /// Returns the numeric identifier of the view.
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the name of the view if it is stored by [`Database::store_named_view`].
+    ///
+    /// [`Database::store_named_view`]: crate::Database::store_named_view
+    #[inline(always)]
+    pub fn name(&self) -> Option<&str> {
+        self.1.as_deref()
+    }
+}
+
+impl PartialEq for ViewRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for ViewRef {}
+
+impl PartialOrd for ViewRef {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ViewRef {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl std::hash::Hash for ViewRef {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl std::fmt::Display for ViewRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
 
 /// Represents a view in the database.
 ///
@@ -71,7 +143,7 @@ where
 
     /// Returns the reference to this view.
     #[inline(always)]
-    pub(crate) fn reference(&self) -> &ViewRef {
+    pub fn reference(&self) -> &ViewRef {
         &self.reference
     }
 
@@ -91,13 +163,56 @@ where
     where
         V: Visitor,
     {
-        visitor.visit_view(&self);
+        visitor.visit_view(self);
+    }
+}
+
+impl<T, E> std::fmt::Display for View<T, E>
+where
+    T: Tuple,
+    E: Expression<T>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "view {}", self.reference)
+    }
+}
+
+/// Is a handle to a view that counts the distinct tuples arriving in it, which is stored by
+/// [`Database::store_counter`]. Unlike the content of the view, which reflects the current
+/// state of the database, the count accumulates across updates and is returned by
+/// [`Database::count`].
+///
+/// [`Database::store_counter`]: crate::Database::store_counter
+/// [`Database::count`]: crate::Database::count
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Counter<T, E>
+where
+    T: Tuple,
+    E: Expression<T>,
+{
+    view: View<T, E>,
+}
+
+impl<T, E> Counter<T, E>
+where
+    T: Tuple,
+    E: Expression<T>,
+{
+    /// Creates a new [`Counter`] over `view`.
+    pub(crate) fn new(view: View<T, E>) -> Self {
+        Self { view }
+    }
+
+    /// Returns the view whose tuples are counted by the receiver.
+    #[inline(always)]
+    pub fn view(&self) -> &View<T, E> {
+        &self.view
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Database, Tuples};
+    use crate::{Database, Expression, Tuples};
 
     #[test]
     fn test_clone() {
@@ -110,4 +225,16 @@ mod tests {
             database.evaluate(&v).unwrap()
         );
     }
+
+    #[test]
+    fn test_display() {
+        let mut database = Database::new();
+        let r = database.add_relation::<i32>("r").unwrap();
+        database.store_view(r.clone()).unwrap();
+        let v = database
+            .store_view(r.builder().select(|t| t % 2 == 0))
+            .unwrap();
+        assert_eq!("view #1", v.to_string());
+        assert_eq!("#1", v.reference().to_string());
+    }
 }
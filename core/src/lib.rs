@@ -1,20 +1,28 @@
 /*! Implements a minimal [database][Database] and relational algebraic [expressions][expression] for evaluating queries in the database.
  */
+#[macro_use]
+mod shared;
 mod database;
 pub mod expression;
 
 #[cfg(feature = "unstable")]
 mod macros;
 
-pub use database::{Database, Tuples};
+#[cfg(feature = "arena")]
+pub use database::Arena;
+pub use database::{
+    diff_databases, ColumnStats, Database, DatabaseBuilder, DatabaseDiff, Internable, Interner,
+    Mutation, RelationDiff, RelationStats, Tuples,
+};
 pub use expression::Expression;
+pub use shared::MaybeSync;
 use thiserror::Error;
 
 /// Is the trait of tuples. Tuples are the smallest unit of data stored in databases.
 ///
 /// **Note**: Tuples are analogous to the rows of a table in a conventional database.
-pub trait Tuple: Ord + Clone + std::fmt::Debug {}
-impl<T: Ord + Clone + std::fmt::Debug> Tuple for T {}
+pub trait Tuple: Ord + Clone + std::fmt::Debug + MaybeSync {}
+impl<T: Ord + Clone + std::fmt::Debug + MaybeSync> Tuple for T {}
 
 /// Is the type of errors returned by `codd`.
 #[derive(Error, Debug)]
@@ -30,4 +38,62 @@ pub enum Error {
     /// Is returned when attempting to re-define an existing instance in a database.
     #[error("database instance `{name:?}` already exists")]
     InstanceExists { name: String },
+
+    /// Is returned when a relation or a view is given a name that is empty, consists of
+    /// whitespace only, or contains control characters.
+    #[error("invalid instance name `{name:?}`")]
+    InvalidName { name: String },
+
+    /// Is returned when a given relation instance exists but its tuples are of a different type.
+    #[error("database instance `{name:?}` has a different tuple type")]
+    TypeMismatch { name: String },
+
+    /// Is returned when no closure or tuple is registered for a placeholder of an expression shape.
+    #[error("no closure is registered for placeholder `{name:?}`")]
+    ClosureNotFound { name: String },
+
+    /// Is returned when no value of the expected type is bound to a parameter of an evaluated
+    /// expression.
+    #[error("no value of the expected type is bound to parameter `{name:?}`")]
+    UnboundParam { name: String },
+
+    /// Is returned when an expression whose closures read a context is evaluated without a
+    /// context of the expected type.
+    #[error("no context of the expected type is supplied to the evaluation")]
+    MissingContext,
+
+    /// Is returned when strictly inserting tuples that already exist in a relation instance.
+    #[error("database instance `{name:?}` already contains some of the inserted tuples")]
+    DuplicateInsert { name: String },
+
+    /// Is returned when an expression refers to a view that doesn't exist in the database,
+    /// e.g., a view of another database.
+    #[error("view `{name:?}` does not exist in the database")]
+    DanglingViewRef { name: String },
+
+    /// Is returned when a view is dropped while other views depend on it.
+    #[error("view `{name:?}` cannot be dropped while other views depend on it")]
+    ViewInUse { name: String },
+
+    /// Is returned when the internal invariants of a database are violated.
+    #[error("database is corrupted: {detail}")]
+    Corrupted { detail: String },
+
+    /// Is returned when the closure of a [`TryProject`] expression fails to project a tuple.
+    ///
+    /// [`TryProject`]: crate::expression::TryProject
+    #[error("failed to project tuple: {message}")]
+    ProjectionFailed { message: String },
+
+    /// Is returned when an evaluation is cancelled before it completes.
+    #[error("evaluation was cancelled")]
+    Cancelled,
+
+    /// Is returned when an expression that is expected to be non-empty evaluates to no tuples.
+    #[error("expression evaluated to an empty result")]
+    UnexpectedEmpty,
+
+    /// Is returned when the tuples of a relation instance cannot be spilled to or loaded from disk.
+    #[error("failed to spill tuples of database instance `{name:?}`: {reason}")]
+    SpillFailed { name: String, reason: String },
 }
@@ -7,7 +7,7 @@ macro_rules! query {
         $crate::relexp!(@select ($($rel_exp)*) $(@pred -> [$($pred)*])?)
     };
     ($db:ident, create relation $name:literal:<$schema:ty>) => {
-        $db.add_relation::<$schema>($name);
+        $db.add_relation::<$schema>($name)
     };
     ($db:ident, create view as
      (select [$proj:expr] from ($($rel_exp:tt)*) $(where [$($pred:tt)*])?)) => {
@@ -15,14 +15,14 @@ macro_rules! query {
             let inner_exp = $crate::relexp!(@select ($($rel_exp)*)
                                             @proj -> [$proj]
                                             $(@pred -> [$($pred)*])?);
-            $db.store_view(inner_exp.clone())
+            $crate::query!(@store_view $db, inner_exp)
         }
     };
     ($db:ident, create view as
      (select * from ($($rel_exp:tt)*) $(where [$($pred:tt)*])?)) => {
         {
             let inner_exp = $crate::relexp!(@select ($($rel_exp)*) $(@pred -> [$($pred)*])?);
-            $db.store_view(inner_exp.clone())
+            $crate::query!(@store_view $db, inner_exp)
         }
     };
     ($db:ident, insert into ($relation:ident) values [$($value:expr),*]) => {
@@ -35,6 +35,14 @@ macro_rules! query {
             $db.insert(&$relation, vec![$($value,)+].into())
         }
     };
+    (@store_view $db:ident, $exp:ident) => {
+        // views over expressions that cannot be maintained incrementally (e.g., `minus`)
+        // are maintained by recomputation:
+        match $db.store_view($exp.clone()) {
+            Err($crate::Error::UnsupportedExpression { .. }) => $db.store_view_recompute($exp),
+            result => result,
+        }
+    };
 }
 
 #[macro_export]
@@ -66,6 +74,9 @@ macro_rules! relexp {
     (($($left:tt)*) minus ($($right:tt)*)) => {
         $crate::relexp!(@minus ($($left)*) ($($right)*))
     };
+    (($($rel_exp:tt)*) group [$key:expr] agg [$init:expr ; $fold:expr]) => {
+        $crate::relexp!(@group ($($rel_exp)*) @key -> [$key] @init -> [$init] @fold -> [$fold])
+    };
     (@select ($($rel_exp:tt)*) @proj -> [$proj:expr] @pred -> [$($pred:tt)*]) => {{
         let rel_exp = $crate::relexp!($($rel_exp)*);
         let sel_exp = $crate::expression::Select::new(rel_exp, $($pred)*);
@@ -92,6 +103,10 @@ macro_rules! relexp {
         let right = $crate::relexp!($($right)*);
         $crate::expression::Join::new(left, right, $lkey, $rkey, $mapper)
     }};
+    (@group ($($rel_exp:tt)*) @key -> [$key:expr] @init -> [$init:expr] @fold -> [$fold:expr]) => {{
+        let rel_exp = $crate::relexp!($($rel_exp)*);
+        $crate::expression::GroupBy::new(rel_exp, $key, $init, $fold)
+    }};
     (@union ($($left:tt)*) ($($right:tt)*)) => {{
         let left = $crate::relexp!($($left)*);
         let right = $crate::relexp!($($right)*);
@@ -109,9 +124,76 @@ macro_rules! relexp {
     }};
 }
 
+/// Adds a relation instance to a database for every `name: type` pair and binds the
+/// resulting [`Relation`] to a variable called `name`. The relations are added in order
+/// and the macro returns early with `?` on the first one that fails (e.g., if its name is
+/// taken); therefore, it must be used in a function that returns a `Result` whose error
+/// type is convertible from [`Error`].
+///
+/// **Example**:
+/// ```rust
+/// use codd::{schema, Database, Error};
+///
+/// fn setup(db: &mut Database) -> Result<(), Error> {
+///     schema! { db; users: (i32, String), items: String };
+///     db.insert(&users, vec![(1, "alice".to_string())].into())?;
+///     db.insert(&items, vec!["book".to_string()].into())?;
+///     Ok(())
+/// }
+///
+/// let mut db = Database::new();
+/// assert!(setup(&mut db).is_ok());
+/// assert!(setup(&mut db).is_err()); // `users` already exists
+/// ```
+///
+/// [`Relation`]: crate::expression::Relation
+/// [`Error`]: enum@crate::Error
+#[macro_export]
+macro_rules! schema {
+    ($db:ident; $($name:ident : $schema:ty),* $(,)?) => {
+        // every relation is named after the variable that it is bound to:
+        $(let $name = $db.add_relation::<$schema>(stringify!($name))?;)*
+    };
+}
+
+/// Binds intermediate expressions to names, like the common table expressions of a SQL
+/// `WITH` clause, and evaluates to the expression after `;`. Every binding is stored as a
+/// view, named after its binding, so that the bound expression is computed once no matter
+/// how many times it is referenced. The macro returns a `Result` that fails if any of the
+/// views cannot be stored.
+///
+/// **Example**:
+/// ```rust
+/// use codd::{with, Database, Expression};
+///
+/// let mut db = Database::new();
+/// let numbers = db.add_relation::<i32>("numbers").unwrap();
+/// db.insert(&numbers, vec![1, 2, 3, 4].into());
+///
+/// let squares = with!(db;
+///     evens = numbers.builder().select(|t| t % 2 == 0).build();
+///     evens.builder().project(|t| t * t).build()
+/// )
+/// .unwrap();
+///
+/// assert_eq!(vec![4, 16], db.evaluate(&squares).unwrap().into_tuples());
+/// assert!(db.named_view("evens").is_some());
+/// ```
+#[macro_export]
+macro_rules! with {
+    ($db:ident; $($name:ident = $exp:expr),+ ; $body:expr) => {
+        (|| -> ::std::result::Result<_, $crate::Error> {
+            $(
+                #[allow(unused_variables)]
+                let $name = $db.store_named_view(stringify!($name), $exp)?;
+            )+
+            Ok($body)
+        })()
+    };
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{query, relexp};
     use crate::{Database, Tuples};
 
     macro_rules! create_relation {
@@ -177,6 +259,27 @@ mod tests {
             let v = query! { database, create view as (select [|&x| x > 0] from (r))}.unwrap();
             assert!(database.evaluate(&v).is_ok());
         }
+        {
+            let mut database = Database::new();
+            let r = create_relation!(database, "r", i32);
+            let s = create_relation!(database, "s", i32);
+            let v = query! { database, create view as (select * from ((r) minus (s)))}.unwrap();
+            query! (database, insert into (r) values [1, 2, 3, 4]).unwrap();
+            assert_eq!(
+                Tuples::<i32>::from(vec![1, 2, 3, 4]),
+                database.evaluate(&v).unwrap()
+            );
+            query! (database, insert into (s) values [2, 4, 5]).unwrap();
+            assert_eq!(
+                Tuples::<i32>::from(vec![1, 3]),
+                database.evaluate(&v).unwrap()
+            );
+            query! (database, insert into (r) values [5, 6]).unwrap();
+            assert_eq!(
+                Tuples::<i32>::from(vec![1, 3, 6]),
+                database.evaluate(&v).unwrap()
+            );
+        }
         {
             let database = Database::new();
             let exp = query! { select * from (([42]) union ([43]))};
@@ -197,6 +300,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_schema() -> Result<(), crate::Error> {
+        let mut database = Database::new();
+        schema! { database; users: (i32, String), orders: (i32, i32), items: String };
+        query! (database, insert into (users) values [(1, "alice".to_string())]).unwrap();
+        query! (database, insert into (orders) values [(1, 10), (1, 11)]).unwrap();
+        query! (database, insert into (items) values ["book".to_string()]).unwrap();
+
+        assert_eq!("users", users.name());
+        assert_eq!(
+            Tuples::<(i32, String)>::from(vec![(1, "alice".into())]),
+            database.evaluate(&users).unwrap()
+        );
+        assert_eq!(
+            Tuples::<(i32, i32)>::from(vec![(1, 10), (1, 11)]),
+            database.evaluate(&orders).unwrap()
+        );
+        assert_eq!(
+            Tuples::<String>::from(vec!["book".into()]),
+            database.evaluate(&items).unwrap()
+        );
+
+        let declare = |database: &mut Database| -> Result<(), crate::Error> {
+            schema! { database; fresh: i32, users: i32 };
+            let _ = (fresh, users);
+            Ok(())
+        };
+        assert!(matches!(
+            declare(&mut database),
+            Err(crate::Error::InstanceExists { .. })
+        ));
+        // the relations before the failing one are added:
+        assert!(database.add_relation::<i32>("fresh").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_relexp() {
         {
@@ -379,4 +518,72 @@ mod tests {
             assert_eq!(Tuples::<i32>::from(vec![101, 201, 301]), result);
         }
     }
+    #[test]
+    fn test_group() {
+        use crate::Expression;
+
+        let mut database = Database::new();
+        let r = create_relation!(database, "r", (char, i32));
+        query! (database, insert into (r) values [
+            ('a', 1), ('b', 2), ('a', 3), ('c', 4), ('a', 5), ('b', 6)
+        ])
+        .unwrap();
+
+        let exp = relexp!((r) group [|t: &(char, i32)| t.0] agg [0; |count, _| count + 1]);
+        let expected = r
+            .builder()
+            .group_by(|t| t.0, 0, |count, _| count + 1)
+            .build();
+        let result = database.evaluate(&exp).unwrap();
+        assert_eq!(Tuples::from(vec![('a', 3), ('b', 2), ('c', 1)]), result);
+        assert_eq!(database.evaluate(&expected).unwrap(), result);
+
+        let exp = query!(select [|&(k, count)| (count, k)] from
+                             ((r) group [|t: &(char, i32)| t.0] agg [0; |sum, t: &(char, i32)| sum + t.1])
+                         where [|&(_, sum)| sum > 5]);
+        let result = database.evaluate(&exp).unwrap();
+        assert_eq!(Tuples::from(vec![(8, 'b'), (9, 'a')]), result);
+
+        let v = query! { database, create view as
+            (select * from ((r) group [|t: &(char, i32)| t.0] agg [0; |count, _| count + 1]))
+        }
+        .unwrap();
+        query! (database, insert into (r) values [('c', 7)]).unwrap();
+        let result = database.evaluate(&v).unwrap();
+        assert_eq!(Tuples::from(vec![('a', 3), ('b', 2), ('c', 2)]), result);
+    }
+
+    #[test]
+    fn test_with() {
+        use crate::Expression;
+
+        let mut database = Database::new();
+        let r = create_relation!(database, "r", (i32, i32));
+        query! (database, insert into (r) values [(1, 10), (2, 20), (3, 30), (4, 40)]).unwrap();
+
+        let join = with!(database;
+            base = r.builder().select(|t| t.0 > 1).build(),
+            left = base.builder().project(|t| (t.0, t.1)).build(),
+            right = base.builder().project(|t| (t.0, t.1 * 2)).build();
+            left.builder()
+                .with_key(|t| t.0)
+                .join(right.builder().with_key(|t| t.0))
+                .on(|_, l, r| (l.1, r.1))
+                .build()
+        )
+        .unwrap();
+
+        assert_eq!(
+            Tuples::from(vec![(20, 40), (30, 60), (40, 80)]),
+            database.evaluate(&join).unwrap()
+        );
+        // `base` is stored once and feeds both `left` and `right`:
+        assert_eq!(3, database.dependent_views_transitive("r").len());
+        assert!(["base", "left", "right"]
+            .iter()
+            .all(|name| database.named_view(name).is_some()));
+
+        let result = with!(database; base = r.clone(); r.clone());
+        assert!(matches!(result, Err(crate::Error::InstanceExists { .. })));
+    }
 }
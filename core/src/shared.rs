@@ -0,0 +1,184 @@
+/*! Implements the "cell kind" of the shared state of expressions and databases.
+
+By default, the closures of expressions and the tuples of database instances are shared in
+`Rc<RefCell<..>>` cells, which makes expressions and databases single-threaded. With the
+`sync` feature, they are shared in `Arc<RwLock<..>>` cells instead; hence, [`Database`] is
+[`Send`] and [`Sync`] and can be evaluated from multiple threads at the same time, provided
+that the tuples and the closures of its expressions are [`Send`] and [`Sync`] (see
+[`MaybeSync`]).
+
+[`Database`]: crate::Database
+*/
+#[cfg(feature = "sync")]
+pub(crate) use std::sync::{Arc as Shared, RwLockReadGuard as Ref, RwLockWriteGuard as RefMut};
+#[cfg(not(feature = "sync"))]
+pub(crate) use std::{
+    cell::{Cell as Atomic, Ref, RefCell as Lock, RefMut},
+    rc::Rc as Shared,
+};
+
+/// Is the type of the closures of expressions, which are also [`Send`] and [`Sync`] with the
+/// `sync` feature.
+#[cfg(not(feature = "sync"))]
+macro_rules! closure {
+    ($($closure:tt)*) => { dyn $($closure)* }
+}
+
+/// Is the type of the closures of expressions, which are also [`Send`] and [`Sync`] with the
+/// `sync` feature.
+#[cfg(feature = "sync")]
+macro_rules! closure {
+    ($($closure:tt)*) => { dyn $($closure)* + Send + Sync }
+}
+
+/// Is implemented by every type, or by the types that are [`Send`] and [`Sync`] with the
+/// `sync` feature. Tuples and the closures of expressions must implement [`MaybeSync`].
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// Is implemented by every type, or by the types that are [`Send`] and [`Sync`] with the
+/// `sync` feature. Tuples and the closures of expressions must implement [`MaybeSync`].
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T: Send + Sync + ?Sized> MaybeSync for T {}
+
+/// Is a [`RefCell`] that can be shared across threads. A poisoned lock is recovered since
+/// the values that it guards are consistent between the operations of a database.
+///
+/// [`RefCell`]: std::cell::RefCell
+#[cfg(feature = "sync")]
+#[derive(Default)]
+pub(crate) struct Lock<T: ?Sized>(std::sync::RwLock<T>);
+
+#[cfg(feature = "sync")]
+impl<T> Lock<T> {
+    pub fn new(value: T) -> Self {
+        Self(std::sync::RwLock::new(value))
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized> Lock<T> {
+    /// Locks the value for reading; other threads may read it at the same time.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Locks the value for writing.
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: Clone> Clone for Lock<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.borrow().clone())
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: PartialEq + ?Sized> PartialEq for Lock<T> {
+    fn eq(&self, other: &Self) -> bool {
+        *self.borrow() == *other.borrow()
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: std::fmt::Debug + ?Sized> std::fmt::Debug for Lock<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Lock").field(&&*self.borrow()).finish()
+    }
+}
+
+/// Is a [`Cell`] that can be shared across threads.
+///
+/// [`Cell`]: std::cell::Cell
+#[cfg(feature = "sync")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Atomic<T: Copy>(Lock<T>);
+
+#[cfg(feature = "sync")]
+impl<T: Copy> Atomic<T> {
+    pub fn new(value: T) -> Self {
+        Self(Lock::new(value))
+    }
+
+    pub fn get(&self) -> T {
+        *self.0.borrow()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.0.borrow_mut() = value;
+    }
+
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.0.borrow_mut(), value)
+    }
+}
+
+/// Lets the evaluations of a [`Database`] read its instances at the same time while the
+/// operations that modify the instances, including the stabilization of the relations and
+/// views that an evaluation reads, run one at a time. Without the `sync` feature, a
+/// database is never accessed by more than one thread and the gate does nothing.
+///
+/// **Note**: the gate is not reentrant; it must be entered at the public operations of a
+/// database and not by the operations that they call.
+///
+/// [`Database`]: crate::Database
+#[derive(Default)]
+pub(crate) struct Gate {
+    #[cfg(feature = "sync")]
+    lock: std::sync::RwLock<()>,
+}
+
+/// Is the guard of a [`Gate`] that is entered for reading.
+#[cfg(not(feature = "sync"))]
+pub(crate) type Reader<'a> = std::marker::PhantomData<&'a ()>;
+
+/// Is the guard of a [`Gate`] that is entered for reading.
+#[cfg(feature = "sync")]
+pub(crate) type Reader<'a> = std::sync::RwLockReadGuard<'a, ()>;
+
+/// Is the guard of a [`Gate`] that is entered for writing.
+#[cfg(not(feature = "sync"))]
+pub(crate) type Writer<'a> = std::marker::PhantomData<&'a ()>;
+
+/// Is the guard of a [`Gate`] that is entered for writing.
+#[cfg(feature = "sync")]
+pub(crate) type Writer<'a> = std::sync::RwLockWriteGuard<'a, ()>;
+
+impl Gate {
+    /// Enters the gate for reading the instances of the database.
+    #[inline(always)]
+    pub fn read(&self) -> Reader<'_> {
+        #[cfg(feature = "sync")]
+        return self
+            .lock
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        #[cfg(not(feature = "sync"))]
+        std::marker::PhantomData
+    }
+
+    /// Enters the gate for modifying the instances of the database.
+    #[inline(always)]
+    pub fn write(&self) -> Writer<'_> {
+        #[cfg(feature = "sync")]
+        return self
+            .lock
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        #[cfg(not(feature = "sync"))]
+        std::marker::PhantomData
+    }
+}